@@ -4,6 +4,8 @@ use crate::{
     ConceptualSpace, ConceptualSpaceId, ConceptualPoint, ConvexRegion,
     DimensionId, ConceptualMetric, ConceptualError, ConceptualResult,
 };
+use crate::queries::{FindSimilarConcepts, SimilarConcepts};
+use crate::spatial_index::SpatialIndexConfig;
 use cim_domain::{AggregateRoot, DomainError};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
@@ -31,6 +33,12 @@ impl ConceptualSpaceAggregate {
         }
     }
 
+    /// Use a different spatial index implementation for neighbour queries
+    pub fn with_spatial_index(mut self, config: SpatialIndexConfig) -> ConceptualResult<Self> {
+        self.space = self.space.with_spatial_index(config)?;
+        Ok(self)
+    }
+
     /// Get the conceptual space ID
     pub fn space_id(&self) -> ConceptualSpaceId {
         self.space.id
@@ -49,6 +57,34 @@ impl ConceptualSpaceAggregate {
         Ok(id)
     }
 
+    /// Move a point to new coordinates
+    pub fn move_point(&mut self, id: &Uuid, coordinates: Vec<f64>) -> ConceptualResult<()> {
+        if self.deleted {
+            return Err(ConceptualError::DomainError(DomainError::InvalidOperation {
+                reason: "Cannot move point in deleted aggregate".to_string(),
+            }));
+        }
+
+        self.space.move_point(id, coordinates)?;
+        self.version += 1;
+        Ok(())
+    }
+
+    /// Remove a point from the conceptual space
+    pub fn remove_point(&mut self, id: &Uuid) -> ConceptualResult<ConceptualPoint> {
+        if self.deleted {
+            return Err(ConceptualError::DomainError(DomainError::InvalidOperation {
+                reason: "Cannot remove point from deleted aggregate".to_string(),
+            }));
+        }
+
+        let point = self.space.remove_point(id)?.ok_or_else(|| {
+            ConceptualError::InvalidPoint(format!("Point {id} not found"))
+        })?;
+        self.version += 1;
+        Ok(point)
+    }
+
     /// Add a convex region to the space
     pub fn add_region(&mut self, region: ConvexRegion) -> ConceptualResult<()> {
         if self.deleted {
@@ -62,6 +98,21 @@ impl ConceptualSpaceAggregate {
         Ok(())
     }
 
+    /// Remove a convex region from the space
+    pub fn remove_region(&mut self, id: &Uuid) -> ConceptualResult<ConvexRegion> {
+        if self.deleted {
+            return Err(ConceptualError::DomainError(DomainError::InvalidOperation {
+                reason: "Cannot remove region from deleted aggregate".to_string(),
+            }));
+        }
+
+        let region = self.space.remove_region(id).ok_or_else(|| {
+            ConceptualError::InvalidPoint(format!("Region {id} not found"))
+        })?;
+        self.version += 1;
+        Ok(region)
+    }
+
     /// Find all regions containing a point
     pub fn find_containing_regions(&self, point: &ConceptualPoint) -> Vec<&ConvexRegion> {
         if self.deleted {
@@ -82,6 +133,17 @@ impl ConceptualSpaceAggregate {
         self.space.k_nearest_neighbors(point, k)
     }
 
+    /// Answer a `FindSimilarConcepts` query through the space's index
+    pub fn find_similar_concepts(&self, query: &FindSimilarConcepts) -> ConceptualResult<SimilarConcepts> {
+        if self.deleted {
+            return Err(ConceptualError::DomainError(DomainError::InvalidOperation {
+                reason: "Cannot query deleted aggregate".to_string(),
+            }));
+        }
+
+        query.execute(&self.space)
+    }
+
     /// Get current metric weights
     pub fn get_metric_weights(&self) -> Vec<f64> {
         if self.deleted {
@@ -109,10 +171,12 @@ impl ConceptualSpaceAggregate {
         }
 
         // Convert f64 weights to DimensionWeight::Constant
-        self.space.metric.dimension_weights = weights
+        let mut metric = self.space.metric.clone();
+        metric.dimension_weights = weights
             .into_iter()
             .map(crate::DimensionWeight::constant)
             .collect();
+        self.space.set_metric(metric);
         
         self.version += 1;
         Ok(())
@@ -363,6 +427,52 @@ mod tests {
         assert!(neighbors[0].1 < neighbors[1].1);
     }
 
+    /// Test that the index follows moved and removed points
+    ///
+    /// ```mermaid
+    /// graph TD
+    ///     A[Add Points] --> B[Move Point]
+    ///     B --> C[Query KNN]
+    ///     C --> D[Remove Point]
+    ///     D --> E[Query KNN]
+    /// ```
+    #[test]
+    fn test_index_tracks_point_mutations() {
+        for config in [SpatialIndexConfig::Flat, SpatialIndexConfig::KdTree] {
+            let dimensions = vec![DimensionId::new(), DimensionId::new()];
+            let mut aggregate = ConceptualSpaceAggregate::new(
+                "Test Space".to_string(),
+                dimensions,
+                create_test_metric(),
+            )
+            .with_spatial_index(config)
+            .unwrap();
+
+            let mut ids = Vec::new();
+            for coords in [[0.0, 0.0], [5.0, 5.0], [10.0, 10.0]] {
+                let mut point = create_test_point();
+                point.coordinates = nalgebra::DVector::from_vec(coords.to_vec());
+                ids.push(aggregate.add_point(point).unwrap());
+            }
+
+            let mut query = create_test_point();
+            query.coordinates = nalgebra::DVector::from_vec(vec![9.0, 9.0]);
+            query.id = None;
+
+            // Moving the origin point next to the query makes it the nearest
+            aggregate.move_point(&ids[0], vec![9.0, 9.5]).unwrap();
+            let neighbors = aggregate.k_nearest_neighbors(&query, 1).unwrap();
+            assert_eq!(*neighbors[0].0, ids[0]);
+
+            // Removing it hands the spot back to the next closest point
+            aggregate.remove_point(&ids[0]).unwrap();
+            let neighbors = aggregate.k_nearest_neighbors(&query, 3).unwrap();
+            assert_eq!(neighbors.len(), 2);
+            assert_eq!(*neighbors[0].0, ids[2]);
+            assert_eq!(aggregate.version(), 5);
+        }
+    }
+
     /// Test region operations
     ///
     /// ```mermaid
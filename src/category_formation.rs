@@ -6,11 +6,17 @@
 
 use crate::{
     ConceptualPoint, ConceptualSpace, ConvexRegion, Hyperplane, 
-    ConceptualError, ConceptualResult, DistanceMetric, SpatialIndex, RTreeIndex
+    ConceptualError, ConceptualResult, DistanceMetric
+};
+use crate::{category_quality, density_clustering, hierarchy, partitioning};
+use crate::{
+    CategoryQualityReport, Dendrogram, DensityClustering, KMeansConfig, KSelection,
+    KSelectionResult, Linkage, MedoidMethod, Partition, PartitionAlgorithm,
 };
 use nalgebra::DVector;
 use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
+use std::ops::RangeInclusive;
 use uuid::Uuid;
 
 /// Voronoi-based category formation algorithms
@@ -23,16 +29,12 @@ pub struct CategoryFormation {
 
     /// Maximum distance for points to be considered in same category
     max_category_radius: f64,
-
-    /// Spatial index for efficient neighbor search
-    spatial_index: RTreeIndex,
 }
 
 impl CategoryFormation {
     /// Create a new category formation engine
     pub fn new(metric: DistanceMetric) -> Self {
         Self {
-            spatial_index: RTreeIndex::new(metric.clone()),
             metric,
             min_points_per_category: 3,
             max_category_radius: 2.0,
@@ -51,13 +53,32 @@ impl CategoryFormation {
     }
 
     /// Add points to the spatial index for category formation
-    pub fn add_points(&mut self, points: Vec<ConceptualPoint>) -> ConceptualResult<()> {
-        for point in points {
-            self.spatial_index.insert(point)?;
-        }
+    ///
+    /// Category formation now reads points through the space's own index,
+    /// so the points are ignored.
+    #[deprecated(since = "0.3.0", note = "add points to the `ConceptualSpace` instead")]
+    pub fn add_points(&mut self, _points: Vec<ConceptualPoint>) -> ConceptualResult<()> {
         Ok(())
     }
 
+    /// The metric this engine was configured with
+    ///
+    /// Neighbourhood queries during category detection go through the
+    /// space's own spatial index and metric instead.
+    pub fn metric(&self) -> &DistanceMetric {
+        &self.metric
+    }
+
+    /// Minimum number of points a category needs
+    pub fn min_points_per_category(&self) -> usize {
+        self.min_points_per_category
+    }
+
+    /// Neighbourhood radius within which points share a category
+    pub fn max_category_radius(&self) -> f64 {
+        self.max_category_radius
+    }
+
     /// Detect natural categories using Voronoi tessellation and density-based clustering
     ///
     /// ```mermaid
@@ -69,7 +90,7 @@ impl CategoryFormation {
     ///     E --> F[Natural Categories]
     /// ```
     pub fn detect_categories(&self, space: &ConceptualSpace) -> ConceptualResult<Vec<ConvexRegion>> {
-        let points: Vec<_> = space.points.values().cloned().collect();
+        let points: Vec<_> = space.points.values().collect();
         
         if points.len() < self.min_points_per_category {
             return Ok(Vec::new());
@@ -79,7 +100,7 @@ impl CategoryFormation {
         let voronoi = self.generate_voronoi_tessellation(&points)?;
         
         // Analyze density and merge cells to form categories
-        let categories = self.form_categories_from_voronoi(&voronoi, &points)?;
+        let categories = self.form_categories_from_voronoi(&voronoi, space)?;
 
         Ok(categories)
     }
@@ -177,12 +198,12 @@ impl CategoryFormation {
     fn form_categories_from_voronoi(
         &self,
         voronoi: &VoronoiTessellation,
-        points: &[ConceptualPoint],
+        space: &ConceptualSpace,
     ) -> ConceptualResult<Vec<ConvexRegion>> {
         // Calculate density for each cell
         let mut cells_with_density = voronoi.cells.clone();
         for cell in &mut cells_with_density {
-            cell.density = self.calculate_cell_density(&cell.seed_point, points)?;
+            cell.density = self.calculate_cell_density(&cell.seed_point, space)?;
         }
 
         // Find high-density cells
@@ -195,7 +216,7 @@ impl CategoryFormation {
             .collect();
 
         // Group adjacent dense cells into categories
-        let cell_groups = self.group_adjacent_cells(&dense_cells)?;
+        let cell_groups = self.group_adjacent_cells(&dense_cells, space)?;
 
         // Convert each group to a convex region
         let mut categories = Vec::new();
@@ -210,33 +231,44 @@ impl CategoryFormation {
     }
 
     /// Calculate density around a point using kernel density estimation
+    ///
+    /// The Gaussian kernel is truncated at four bandwidths so only the
+    /// neighbourhood returned by the space's index contributes.
     fn calculate_cell_density(
         &self,
         center: &ConceptualPoint,
-        all_points: &[ConceptualPoint],
+        space: &ConceptualSpace,
     ) -> ConceptualResult<f64> {
         let bandwidth = self.max_category_radius / 2.0;
         let mut density = 0.0;
 
-        for point in all_points {
-            let distance = self.metric.calculate(center, point)?;
-            
+        for (_, distance) in space.range_search(center, bandwidth * 4.0)? {
             // Gaussian kernel
             let kernel_value = (-0.5 * (distance / bandwidth).powi(2)).exp();
             density += kernel_value;
         }
 
         // Normalize by number of points and bandwidth
-        density /= all_points.len() as f64 * bandwidth;
+        density /= space.points.len() as f64 * bandwidth;
         
         Ok(density)
     }
 
     /// Group adjacent Voronoi cells based on proximity
-    fn group_adjacent_cells(&self, cells: &[VoronoiCell]) -> ConceptualResult<Vec<Vec<VoronoiCell>>> {
+    fn group_adjacent_cells(
+        &self,
+        cells: &[VoronoiCell],
+        space: &ConceptualSpace,
+    ) -> ConceptualResult<Vec<Vec<VoronoiCell>>> {
         let mut groups = Vec::new();
         let mut visited = HashSet::new();
 
+        // Map seed ids back to cell positions for index lookups
+        let cell_by_id: HashMap<Uuid, usize> = cells.iter()
+            .enumerate()
+            .filter_map(|(i, cell)| cell.seed_point.id.map(|id| (id, i)))
+            .collect();
+
         for (i, _cell) in cells.iter().enumerate() {
             if visited.contains(&i) {
                 continue;
@@ -254,10 +286,10 @@ impl CategoryFormation {
                 group.push(cells[idx].clone());
 
                 // Find adjacent cells
-                for (j, other_cell) in cells.iter().enumerate() {
-                    if !visited.contains(&j) {
-                        let distance = self.metric.calculate(&cells[idx].seed_point, &other_cell.seed_point)?;
-                        if distance <= self.max_category_radius {
+                let nearby = space.range_search(&cells[idx].seed_point, self.max_category_radius)?;
+                for (id, _) in nearby {
+                    if let Some(&j) = cell_by_id.get(id) {
+                        if !visited.contains(&j) {
                             to_visit.push(j);
                         }
                     }
@@ -365,6 +397,155 @@ impl CategoryFormation {
 
         Ok(boundaries)
     }
+
+    /// Partition the space's points with k-means++ seeded Lloyd iterations
+    ///
+    /// Centroids are coordinate means, so the cost minimised is squared
+    /// distance under the engine's metric; for metrics where a mean is not a
+    /// sensible centre use `k_medoids`.
+    ///
+    /// ```mermaid
+    /// graph TD
+    ///     A[Points] --> B[k-means++ Seeding]
+    ///     B --> C[Assign to Nearest Centroid]
+    ///     C --> D[Move Centroids to Means]
+    ///     D -->|moved| C
+    ///     D -->|converged| E[Best Restart]
+    ///     E --> F[Voronoi Cell Regions]
+    /// ```
+    pub fn k_means(&self, space: &ConceptualSpace, k: usize, config: &KMeansConfig) -> ConceptualResult<Partition> {
+        partitioning::k_means(self, space, k, config)
+    }
+
+    /// Partition the space's points around k medoids
+    ///
+    /// Medoids are actual points, so only distances are needed and any metric
+    /// the engine is configured with works; without flat bisectors the
+    /// regions are member hulls rather than Voronoi cells. PAM searches the
+    /// full distance matrix; CLARA runs PAM on subsamples for large spaces.
+    ///
+    /// ```mermaid
+    /// graph TD
+    ///     A[Points] --> B{Method}
+    ///     B -->|PAM| C[Distance Matrix]
+    ///     B -->|CLARA| D[Subsample] --> C
+    ///     C --> E[BUILD Greedy Medoids]
+    ///     E --> F[SWAP Until No Gain]
+    ///     F --> G[Voronoi Cell Regions]
+    /// ```
+    pub fn k_medoids(&self, space: &ConceptualSpace, k: usize, method: MedoidMethod) -> ConceptualResult<Partition> {
+        partitioning::k_medoids(self, space, k, method)
+    }
+
+    /// Run a partitioning algorithm for every k in a range and keep the best
+    ///
+    /// The elbow criterion takes the knee of the cost curve; silhouette takes
+    /// the k with the widest mean silhouette (k = 1 scores zero).
+    pub fn select_k(
+        &self,
+        space: &ConceptualSpace,
+        candidates: RangeInclusive<usize>,
+        algorithm: PartitionAlgorithm,
+        criterion: KSelection,
+    ) -> ConceptualResult<KSelectionResult> {
+        partitioning::select_k(self, space, candidates, algorithm, criterion)
+    }
+
+    /// Mean silhouette width of a partition under the engine's metric
+    pub fn silhouette_score(&self, space: &ConceptualSpace, partition: &Partition) -> ConceptualResult<f64> {
+        partitioning::silhouette_score(self, space, partition)
+    }
+
+    /// Cluster the space's points with DBSCAN
+    ///
+    /// Uses the engine's category radius as ε and its minimum category size
+    /// as the core-point threshold (counting the point itself). Distances are
+    /// measured with the engine's metric, like every other clustering method;
+    /// neighbourhoods come from the space's spatial index when it measures
+    /// distance the same way, and from a scan of the point store otherwise.
+    ///
+    /// ```mermaid
+    /// graph TD
+    ///     A[Points] --> B[ε-Neighbourhoods via Index or Scan]
+    ///     B --> C{≥ min points?}
+    ///     C -->|yes| D[Core Point: Expand Cluster]
+    ///     C -->|no| E[Border or Noise]
+    ///     D --> F[Convex Hull Regions]
+    /// ```
+    pub fn dbscan(&self, space: &ConceptualSpace) -> ConceptualResult<DensityClustering> {
+        density_clustering::dbscan(self, space)
+    }
+
+    /// Cluster the space's points with HDBSCAN
+    ///
+    /// Builds the minimum spanning tree of the mutual reachability distance
+    /// under the engine's metric, condenses the single-linkage hierarchy so
+    /// that splits smaller than the minimum category size count as points
+    /// falling out, and keeps the clusters of greatest excess of mass. The
+    /// root is never selected, so a space without a real split is all noise.
+    ///
+    /// The tree is grown with Prim's algorithm, scoring the newest tree
+    /// vertex against every store row in one batched pass per step. That is
+    /// O(n²) distance evaluations but only O(n) memory; no pairwise distance
+    /// matrix is ever held.
+    ///
+    /// ```mermaid
+    /// graph TD
+    ///     A[Points] --> B[Core Distances via Index or Scan]
+    ///     B --> C[Mutual Reachability MST]
+    ///     C --> D[Single-Linkage Hierarchy]
+    ///     D --> E[Condensed Cluster Tree]
+    ///     E --> F[Select by Stability]
+    ///     F --> G[Convex Hull Regions]
+    /// ```
+    pub fn hdbscan(&self, space: &ConceptualSpace) -> ConceptualResult<DensityClustering> {
+        density_clustering::hdbscan(self, space)
+    }
+
+    /// Agglomerative clustering of the space's points under the engine's metric
+    ///
+    /// Cluster distances are updated with the Lance–Williams recurrence, so
+    /// each step only revisits the merged cluster's row.
+    ///
+    /// ```mermaid
+    /// graph TD
+    ///     A[Points as Singletons] --> B[Distance Matrix]
+    ///     B --> C[Merge Closest Pair]
+    ///     C --> D[Lance-Williams Update]
+    ///     D -->|clusters remain| C
+    ///     D -->|one cluster| E[Dendrogram]
+    ///     E --> F[Cuts at Levels]
+    ///     F --> G[Taxonomy of Nested Regions]
+    /// ```
+    pub fn agglomerate(&self, space: &ConceptualSpace, linkage: Linkage) -> ConceptualResult<Dendrogram> {
+        hierarchy::agglomerate(self, space, linkage)
+    }
+
+    /// Score category regions against the points of a space
+    ///
+    /// A point belongs to the first region listing it as a member; points
+    /// no region lists fall to the containing region with the nearest
+    /// prototype, and otherwise stay unassigned. Distances use the engine's
+    /// metric.
+    ///
+    /// ```mermaid
+    /// graph TD
+    ///     A[Regions + Points] --> B[Assign Points]
+    ///     B --> C[Centroids & Dispersion]
+    ///     C --> D[Silhouette / Davies-Bouldin / Calinski-Harabasz]
+    ///     B --> E[Containment Checks]
+    ///     E --> F[Outside Members, Overlap, Convexity]
+    ///     D --> G[Quality Report]
+    ///     F --> G
+    /// ```
+    pub fn evaluate(&self, space: &ConceptualSpace, regions: &[ConvexRegion]) -> ConceptualResult<CategoryQualityReport> {
+        category_quality::evaluate(self, space, regions)
+    }
+
+    /// Score the regions already stored in a space
+    pub fn evaluate_space(&self, space: &ConceptualSpace) -> ConceptualResult<CategoryQualityReport> {
+        category_quality::evaluate_space(self, space)
+    }
 }
 
 /// Voronoi tessellation representation
@@ -391,13 +572,29 @@ pub struct VoronoiCell {
     pub density: f64,
 }
 
-/// Boundary detection algorithms for conceptual spaces using Voronoi tessellations
+/// Boundary detection from changes in category membership density
+///
+/// Categories come from the space's regions, or from DBSCAN when the space
+/// has none. Each category's members define a Gaussian kernel density; a
+/// boundary sits where the membership share of two categories crosses one
+/// half, and its strength grows with the steepness of that crossing.
 pub struct CategoryBoundaryDetection {
-    /// Gradient threshold for boundary detection
+    /// Minimum strength of a reported boundary
     gradient_threshold: f64,
 
-    /// Smoothing parameter for density estimation
+    /// Kernel bandwidth of the membership density estimate
     smoothing_factor: f64,
+
+    /// Category formation used when the space has no regions
+    fallback: CategoryFormation,
+}
+
+/// A category's members as seen by boundary detection
+struct MembershipCategory {
+    region: ConvexRegion,
+    stored: bool,
+    members: Vec<DVector<f64>>,
+    centroid: DVector<f64>,
 }
 
 impl CategoryBoundaryDetection {
@@ -406,6 +603,7 @@ impl CategoryBoundaryDetection {
         Self {
             gradient_threshold: 0.5,
             smoothing_factor: 1.0,
+            fallback: CategoryFormation::new(DistanceMetric::Euclidean),
         }
     }
 
@@ -416,207 +614,205 @@ impl CategoryBoundaryDetection {
         self
     }
 
-    /// Detect conceptual boundaries using Voronoi tessellation edges
+    /// Category formation whose DBSCAN parameters apply to spaces without regions
+    pub fn with_fallback(mut self, fallback: CategoryFormation) -> Self {
+        self.fallback = fallback;
+        self
+    }
+
+    /// Detect boundaries where category membership density changes sharply
+    ///
+    /// For each pair of categories the membership share is traced along the
+    /// segment between their centroids. Where it crosses one half, and no
+    /// third category is denser, the gradient of the share gives the
+    /// boundary's normal and strength.
     ///
     /// ```mermaid
     /// graph TD
-    ///     A[Conceptual Space] --> B[Generate Voronoi Tessellation]
-    ///     B --> C[Analyze Edge Densities]
-    ///     C --> D[Find High-Gradient Edges]
-    ///     D --> E[Extract Boundary Lines]
-    ///     E --> F[Category Boundaries]
+    ///     A[Conceptual Space] --> B{Regions?}
+    ///     B -->|yes| C[Assign Points to Regions]
+    ///     B -->|no| D[DBSCAN Categories]
+    ///     C --> E[Kernel Membership Densities]
+    ///     D --> E
+    ///     E --> F[Locate Share Crossings per Pair]
+    ///     F --> G[Gradient: Normal + Strength]
+    ///     G --> H[Classify Boundary Type]
+    ///     H --> I[Category Boundaries]
     /// ```
     pub fn detect_boundaries(
         &self,
         space: &ConceptualSpace,
     ) -> ConceptualResult<Vec<ConceptualBoundary>> {
-        let points: Vec<_> = space.points.values().collect();
-        
-        if points.is_empty() {
+        if space.points.is_empty() {
             return Ok(Vec::new());
         }
 
-        // Generate Voronoi tessellation
-        let voronoi = self.generate_voronoi_for_boundaries(&points)?;
-
-        // Analyze edge densities and find boundaries
-        let boundaries = self.extract_boundaries_from_voronoi(&voronoi)?;
-
-        Ok(boundaries)
-    }
+        let categories = self.categories(space)?;
+        let bandwidth = self.smoothing_factor.max(f64::EPSILON);
+        let mut boundaries = Vec::new();
 
-    /// Generate Voronoi tessellation for boundary detection
-    fn generate_voronoi_for_boundaries(
-        &self,
-        points: &[&ConceptualPoint],
-    ) -> ConceptualResult<VoronoiTessellation> {
-        let points_owned: Vec<_> = points.iter().cloned().cloned().collect();
-        let mut cells = Vec::new();
-        
-        for (i, point) in points_owned.iter().enumerate() {
-            let cell = self.compute_boundary_voronoi_cell(point, &points_owned, i)?;
-            cells.push(cell);
+        for a in 0..categories.len() {
+            for b in a + 1..categories.len() {
+                if let Some(boundary) = self.boundary_between(&categories, a, b, bandwidth) {
+                    if boundary.strength >= self.gradient_threshold {
+                        boundaries.push(boundary);
+                    }
+                }
+            }
         }
 
-        Ok(VoronoiTessellation {
-            cells,
-            seed_points: points_owned,
-        })
+        Ok(boundaries)
     }
 
-    /// Compute Voronoi cell for boundary detection
-    fn compute_boundary_voronoi_cell(
-        &self,
-        seed: &ConceptualPoint,
-        all_points: &[ConceptualPoint],
-        seed_index: usize,
-    ) -> ConceptualResult<VoronoiCell> {
-        let mut boundaries = Vec::new();
-        let mut neighbors = Vec::new();
-        
-        // Find neighboring points and create bisecting planes
-        for (i, other_point) in all_points.iter().enumerate() {
-            if i == seed_index {
-                continue;
+    /// The space's regions with the points assigned to them, or DBSCAN clusters
+    ///
+    /// A point belongs to the first region listing it as a member, otherwise
+    /// to the containing region whose prototype is nearest under the space's
+    /// metric.
+    fn categories(&self, space: &ConceptualSpace) -> ConceptualResult<Vec<MembershipCategory>> {
+        let mut categories = Vec::new();
+        if space.regions.is_empty() {
+            for cluster in self.fallback.dbscan(space)?.clusters {
+                let members = cluster.members.iter()
+                    .filter_map(|id| space.points.get(id))
+                    .map(|point| point.coordinates.clone())
+                    .collect();
+                categories.push((cluster.region, false, members));
             }
-
-            let distance = seed.coordinates.iter()
-                .zip(other_point.coordinates.iter())
-                .map(|(a, b)| (a - b).powi(2))
-                .sum::<f64>()
-                .sqrt();
-
-            // Only consider nearby points for boundary formation
-            if distance < self.smoothing_factor * 3.0 {
-                neighbors.push((i, distance));
-                
-                let hyperplane = self.compute_bisecting_hyperplane(seed, other_point)?;
-                boundaries.push(hyperplane);
+        } else {
+            let mut regions: Vec<&ConvexRegion> = space.regions.values().collect();
+            regions.sort_by_key(|region| region.id);
+            let mut members = vec![Vec::new(); regions.len()];
+            for (id, point) in space.points.iter() {
+                let owner = match regions.iter().position(|region| region.member_points.contains(id)) {
+                    Some(owner) => Some(owner),
+                    None => regions.iter()
+                        .enumerate()
+                        .filter(|(_, region)| region.contains(&point))
+                        .map(|(r, region)| Ok((r, space.metric.distance(&point, &region.prototype)?)))
+                        .collect::<ConceptualResult<Vec<_>>>()?
+                        .into_iter()
+                        .min_by(|x, y| x.1.total_cmp(&y.1))
+                        .map(|(r, _)| r),
+                };
+                if let Some(owner) = owner {
+                    members[owner].push(point.coordinates.clone());
+                }
+            }
+            for (region, members) in regions.into_iter().zip(members) {
+                categories.push((region.clone(), true, members));
             }
         }
 
-        // Calculate local density
-        let density = neighbors.len() as f64 / (self.smoothing_factor.powi(seed.coordinates.len() as i32));
-
-        Ok(VoronoiCell {
-            seed_point: seed.clone(),
-            seed_index,
-            boundaries,
-            volume: 1.0 / (density + 1e-6), // Inverse density approximates volume
-            density,
-        })
+        Ok(categories.into_iter()
+            .filter(|(_, _, members)| !members.is_empty())
+            .map(|(region, stored, members)| {
+                let centroid = members.iter().fold(DVector::zeros(members[0].len()), |sum, m| sum + m)
+                    / members.len() as f64;
+                MembershipCategory { region, stored, members, centroid }
+            })
+            .collect())
     }
 
-    /// Compute bisecting hyperplane between two points
-    fn compute_bisecting_hyperplane(
+    /// Boundary between two categories, if their membership share crosses one half
+    fn boundary_between(
         &self,
-        point1: &ConceptualPoint,
-        point2: &ConceptualPoint,
-    ) -> ConceptualResult<Hyperplane> {
-        let dimensions = point1.coordinates.len();
-        
-        // Midpoint
-        let mut midpoint = DVector::zeros(dimensions);
-        for i in 0..dimensions {
-            midpoint[i] = (point1.coordinates[i] + point2.coordinates[i]) / 2.0;
-        }
-
-        // Normal vector
-        let mut normal = DVector::zeros(dimensions);
-        for i in 0..dimensions {
-            normal[i] = point2.coordinates[i] - point1.coordinates[i];
-        }
-
-        // Normalize
-        let magnitude = normal.norm();
-        if magnitude > 0.0 {
-            normal /= magnitude;
+        categories: &[MembershipCategory],
+        a: usize,
+        b: usize,
+        bandwidth: f64,
+    ) -> Option<ConceptualBoundary> {
+        let (first, second) = (&categories[a], &categories[b]);
+        let segment = &second.centroid - &first.centroid;
+        let length = segment.norm();
+        if length == 0.0 {
+            return None;
         }
 
-        let offset = normal.dot(&midpoint);
-
-        Ok(Hyperplane::new(normal, offset))
-    }
-
-    /// Extract boundaries from Voronoi tessellation by analyzing density gradients
-    fn extract_boundaries_from_voronoi(
-        &self,
-        voronoi: &VoronoiTessellation,
-    ) -> ConceptualResult<Vec<ConceptualBoundary>> {
-        let mut boundaries = Vec::new();
-
-        // Find edges between cells with significant density differences
-        for (i, cell1) in voronoi.cells.iter().enumerate() {
-            for (j, cell2) in voronoi.cells.iter().enumerate() {
-                if i >= j {
-                    continue;
-                }
-
-                // Check if cells are adjacent (share a boundary)
-                if self.cells_are_adjacent(cell1, cell2)? {
-                    let density_diff = (cell1.density - cell2.density).abs();
-                    let max_density = cell1.density.max(cell2.density);
-                    
-                    if max_density > 0.0 {
-                        let gradient_strength = density_diff / max_density;
-                        
-                        if gradient_strength > self.gradient_threshold {
-                            // Create boundary at the edge between cells
-                            let boundary = self.create_boundary_from_edge(cell1, cell2, gradient_strength)?;
-                            boundaries.push(boundary);
-                        }
-                    }
-                }
+        // Log-odds of membership in `first` over `second` along the segment
+        let at = |t: f64| &first.centroid + &segment * t;
+        let log_odds = |t: f64| {
+            let x = at(t);
+            log_kernel_density(&first.members, &x, bandwidth).0 - log_kernel_density(&second.members, &x, bandwidth).0
+        };
+
+        const STEPS: usize = 32;
+        let (mut low, mut high) = (1..=STEPS)
+            .map(|step| ((step - 1) as f64 / STEPS as f64, step as f64 / STEPS as f64))
+            .find(|&(low, high)| log_odds(low) >= 0.0 && log_odds(high) < 0.0)?;
+        for _ in 0..48 {
+            let mid = 0.5 * (low + high);
+            if log_odds(mid) >= 0.0 {
+                low = mid;
+            } else {
+                high = mid;
             }
         }
-
-        Ok(boundaries)
-    }
-
-    /// Check if two Voronoi cells are adjacent
-    fn cells_are_adjacent(&self, cell1: &VoronoiCell, cell2: &VoronoiCell) -> ConceptualResult<bool> {
-        // Two cells are adjacent if the distance between their seed points is small
-        let distance = cell1.seed_point.coordinates.iter()
-            .zip(cell2.seed_point.coordinates.iter())
-            .map(|(a, b)| (a - b).powi(2))
-            .sum::<f64>()
-            .sqrt();
-
-        Ok(distance <= self.smoothing_factor * 2.0)
-    }
-
-    /// Create a boundary from an edge between two cells
-    fn create_boundary_from_edge(
-        &self,
-        cell1: &VoronoiCell,
-        cell2: &VoronoiCell,
-        strength: f64,
-    ) -> ConceptualResult<ConceptualBoundary> {
-        // Boundary position is midpoint between seed points
-        let dimensions = cell1.seed_point.coordinates.len();
-        let mut boundary_coords = Vec::with_capacity(dimensions);
-        
-        for i in 0..dimensions {
-            let midpoint = (cell1.seed_point.coordinates[i] + cell2.seed_point.coordinates[i]) / 2.0;
-            boundary_coords.push(midpoint);
+        let t = 0.5 * (low + high);
+        let crossing = at(t);
+
+        // A denser third category means the pair does not actually meet here
+        let (density, first_mean) = log_kernel_density(&first.members, &crossing, bandwidth);
+        let (_, second_mean) = log_kernel_density(&second.members, &crossing, bandwidth);
+        let intervened = categories.iter()
+            .enumerate()
+            .filter(|&(c, _)| c != a && c != b)
+            .any(|(_, other)| log_kernel_density(&other.members, &crossing, bandwidth).0 > density);
+        if intervened {
+            return None;
         }
 
-        let mut dimension_map = HashMap::new();
-        for (i, _) in boundary_coords.iter().enumerate() {
-            dimension_map.insert(crate::DimensionId::new(), i);
+        // ∇ share = share(1 - share) ∇ log-odds, with share = ½ on the boundary
+        let gradient = (&first_mean - &second_mean) / (bandwidth * bandwidth);
+        let steepness = 0.25 * gradient.norm();
+        if steepness == 0.0 {
+            return None;
         }
-
-        let position = ConceptualPoint::new(boundary_coords, dimension_map);
-
-        Ok(ConceptualBoundary {
+        let normal = gradient.normalize();
+        let hyperplane = Hyperplane::new(normal.clone(), normal.dot(&crossing));
+
+        let boundary_type = if [first, second].iter().any(|category| {
+            category.stored && category.region.boundaries.iter().any(|face| {
+                let norm = face.normal.norm();
+                norm > 0.0
+                    && (face.normal.dot(&normal) / norm).abs() > 0.9
+                    && ((face.normal.dot(&crossing) - face.offset) / norm).abs() <= bandwidth
+            })
+        }) {
+            BoundaryType::RegionBoundary
+        } else if (t - 0.5).abs() <= 0.05 && (normal.dot(&segment) / length).abs() > 0.9 {
+            BoundaryType::VoronoiEdge
+        } else {
+            BoundaryType::DensityGradient
+        };
+
+        Some(ConceptualBoundary {
             id: Uuid::new_v4(),
-            position,
-            strength,
-            boundary_type: BoundaryType::VoronoiEdge,
+            position: ConceptualPoint::new(crossing.as_slice().to_vec(), first.region.prototype.dimension_map.clone()),
+            hyperplane,
+            strength: 1.0 - (-steepness * bandwidth).exp(),
+            boundary_type,
+            regions: (first.region.id, second.region.id),
         })
     }
 }
 
+/// Log of the Gaussian kernel density of `members` at `x`, with the
+/// kernel-weighted mean of the members
+fn log_kernel_density(members: &[DVector<f64>], x: &DVector<f64>, bandwidth: f64) -> (f64, DVector<f64>) {
+    let exponents: Vec<f64> = members.iter()
+        .map(|m| -(m - x).norm_squared() / (2.0 * bandwidth * bandwidth))
+        .collect();
+    let max = exponents.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    let weights: Vec<f64> = exponents.iter().map(|e| (e - max).exp()).collect();
+    let total: f64 = weights.iter().sum();
+    let mean = members.iter()
+        .zip(&weights)
+        .fold(DVector::zeros(x.len()), |sum, (m, w)| sum + m * *w)
+        / total;
+    (max + total.ln(), mean)
+}
+
 impl Default for CategoryBoundaryDetection {
     fn default() -> Self {
         Self::new()
@@ -632,15 +828,21 @@ pub struct ConceptualBoundary {
     /// Position of the boundary
     pub position: ConceptualPoint,
 
+    /// Boundary hyperplane through `position`; its normal points into the first region
+    pub hyperplane: Hyperplane,
+
     /// Strength of the boundary (0.0 - 1.0)
     pub strength: f64,
 
     /// Type of boundary
     pub boundary_type: BoundaryType,
+
+    /// The regions the boundary separates
+    pub regions: (Uuid, Uuid),
 }
 
 /// Types of conceptual boundaries
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum BoundaryType {
     /// Boundary detected from density gradients
     DensityGradient,
@@ -671,8 +873,96 @@ impl ConvexRegion {
             prototype,
             boundaries,
             member_points,
-            name: Some("Generated Boundary".to_string()),
-            description: Some("Boundary generated from Voronoi edge".to_string()),
+            name: Some("Generated Region".to_string()),
+            description: Some("Region bounded by computed hyperplanes".to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ConceptualMetric, DimensionId};
+
+    fn space() -> ConceptualSpace {
+        ConceptualSpace::new(
+            "Boundaries".to_string(),
+            vec![DimensionId::new(), DimensionId::new()],
+            ConceptualMetric::uniform(2, 2.0),
+        )
+    }
+
+    fn blob(space: &mut ConceptualSpace, cx: f64, offsets: &[(f64, f64)]) -> Vec<ConceptualPoint> {
+        offsets.iter()
+            .map(|(dx, dy)| {
+                let mut point = ConceptualPoint::new(vec![cx + dx, *dy], HashMap::new());
+                point.id = Some(space.add_point(point.clone()).unwrap());
+                point
+            })
+            .collect()
+    }
+
+    fn square(cx: f64, members: &[ConceptualPoint]) -> ConvexRegion {
+        let plane = |x: f64, y: f64, offset: f64| Hyperplane::new(DVector::from_vec(vec![x, y]), offset);
+        ConvexRegion::from_prototype_with_boundaries(
+            ConceptualPoint::new(vec![cx, 0.0], HashMap::new()),
+            vec![plane(1.0, 0.0, cx - 0.5), plane(-1.0, 0.0, -cx - 0.5), plane(0.0, 1.0, -0.5), plane(0.0, -1.0, -0.5)],
+            members.iter().filter_map(|p| p.id).collect(),
+        )
+    }
+
+    const CROSS: [(f64, f64); 5] = [(0.0, 0.0), (0.4, 0.0), (-0.4, 0.0), (0.0, 0.4), (0.0, -0.4)];
+
+    #[test]
+    fn test_dbscan_fallback_finds_density_gradient_between_clusters() {
+        let mut space = space();
+        blob(&mut space, 0.0, &CROSS);
+        let wide: Vec<(f64, f64)> = CROSS.iter().map(|(dx, dy)| (dx * 4.0, dy * 4.0)).collect();
+        blob(&mut space, 6.0, &wide);
+
+        let boundaries = CategoryBoundaryDetection::new().detect_boundaries(&space).unwrap();
+        assert_eq!(boundaries.len(), 1);
+        let boundary = &boundaries[0];
+        assert!(boundary.strength > 0.5);
+        assert_eq!(boundary.boundary_type, BoundaryType::DensityGradient);
+        assert!(boundary.hyperplane.normal[0].abs() > 0.99);
+        // The wider category reaches further: the crossing leans towards the tight one
+        assert!(boundary.position.coordinates[0] > 1.0 && boundary.position.coordinates[0] < 2.9);
+        assert!(boundary.hyperplane.signed_distance(&boundary.position).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_region_faces_are_classified_as_region_boundaries() {
+        let mut space = space();
+        for cx in [0.0, 2.5] {
+            let points = blob(&mut space, cx, &CROSS);
+            space.add_region(square(cx, &points)).unwrap();
+        }
+
+        let boundaries = CategoryBoundaryDetection::new().with_params(0.1, 1.0).detect_boundaries(&space).unwrap();
+        assert_eq!(boundaries.len(), 1);
+        let boundary = &boundaries[0];
+        assert_eq!(boundary.boundary_type, BoundaryType::RegionBoundary);
+        assert!((boundary.position.coordinates[0] - 1.25).abs() < 1e-6);
+        assert!(space.regions.contains_key(&boundary.regions.0) && space.regions.contains_key(&boundary.regions.1));
+
+        // The normal points into the first region of the pair
+        let first = &space.regions[&boundary.regions.0];
+        assert!(boundary.hyperplane.signed_distance(&first.prototype) > 0.0);
+    }
+
+    #[test]
+    fn test_weak_boundaries_fall_below_threshold() {
+        let mut space = space();
+        for cx in [0.0, 0.6] {
+            let points = blob(&mut space, cx, &CROSS);
+            space.add_region(square(cx, &points)).unwrap();
         }
+
+        let detector = CategoryBoundaryDetection::new().with_params(0.0, 1.0);
+        let all = detector.detect_boundaries(&space).unwrap();
+        assert_eq!(all.len(), 1);
+        assert!(all[0].strength < 0.5);
+        assert!(CategoryBoundaryDetection::new().detect_boundaries(&space).unwrap().is_empty());
     }
-} 
\ No newline at end of file
+}
@@ -0,0 +1,346 @@
+//! Quality measures for detected categories
+//!
+//! Scores a set of category regions against the points of a space: classic
+//! clustering indices (silhouette, Davies–Bouldin, Calinski–Harabasz), the
+//! within/between dispersion they are built from, and the geometric checks
+//! Gärdenfors' theory asks of categories — members inside their own region,
+//! little overlap between regions and no foreign points inside a region.
+
+use crate::partitioning::{distance_matrix, silhouette_values};
+use crate::{
+    CategoryFormation, ConceptualPoint, ConceptualResult, ConceptualSpace, ConceptualSpaceId,
+    ConvexRegion,
+};
+use nalgebra::{DMatrix, DVector};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// Quality of a single category region
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RegionQuality {
+    /// The region scored
+    pub region_id: Uuid,
+
+    /// Name of the region, if it has one
+    pub name: Option<String>,
+
+    /// Points assigned to the region
+    pub size: usize,
+
+    /// Mean silhouette of the region's points
+    pub silhouette: f64,
+
+    /// Mean distance of the region's points to their centroid
+    pub dispersion: f64,
+
+    /// Assigned points lying outside the region
+    pub members_outside: usize,
+
+    /// Points of other categories lying inside the region
+    pub foreign_points: usize,
+
+    /// Fraction of the points inside the region that are also inside another region
+    pub overlap_fraction: f64,
+
+    /// Whether segments between the region's points stay inside it
+    pub convex: bool,
+}
+
+/// Two regions sharing points of the space
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RegionOverlap {
+    /// First region of the pair
+    pub first: Uuid,
+
+    /// Second region of the pair
+    pub second: Uuid,
+
+    /// Points of the space inside both regions
+    pub shared_points: usize,
+}
+
+/// Quality of a categorisation of a space
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CategoryQualityReport {
+    /// The space evaluated
+    pub space_id: ConceptualSpaceId,
+
+    /// Points assigned to some category
+    pub assigned_points: usize,
+
+    /// Points belonging to no category
+    pub unassigned_points: usize,
+
+    /// Mean silhouette over assigned points; higher is better
+    pub silhouette: f64,
+
+    /// Davies–Bouldin index, lower is better; `None` with fewer than two categories
+    pub davies_bouldin: Option<f64>,
+
+    /// Calinski–Harabasz index, higher is better; `None` when undefined
+    pub calinski_harabasz: Option<f64>,
+
+    /// Sum of squared distances of points to their category centroid
+    pub within_dispersion: f64,
+
+    /// Size-weighted sum of squared distances of centroids to the overall centroid
+    pub between_dispersion: f64,
+
+    /// Fraction of assigned points outside their own region
+    pub outside_fraction: f64,
+
+    /// Pairs of regions sharing points
+    pub overlaps: Vec<RegionOverlap>,
+
+    /// Foreign points inside regions plus regions failing the convexity test
+    pub convexity_violations: usize,
+
+    /// Per-region breakdown, in the order the regions were given
+    pub regions: Vec<RegionQuality>,
+}
+
+/// Score category regions against the points of a space
+pub(crate) fn evaluate(formation: &CategoryFormation, space: &ConceptualSpace, regions: &[ConvexRegion]) -> ConceptualResult<CategoryQualityReport> {
+    let k = regions.len();
+    let points: Vec<(&Uuid, ConceptualPoint)> = space.points.iter().collect();
+    let inside: Vec<Vec<bool>> = points.iter()
+        .map(|(_, point)| regions.iter().map(|r| r.contains(point)).collect())
+        .collect();
+
+    let mut labels = Vec::with_capacity(points.len());
+    for (index, (id, point)) in points.iter().enumerate() {
+        let label = match regions.iter().position(|r| r.member_points.contains(*id)) {
+            Some(label) => Some(label),
+            None => {
+                let mut best: Option<(usize, f64)> = None;
+                for (r, region) in regions.iter().enumerate().filter(|(r, _)| inside[index][*r]) {
+                    let d = formation.metric().calculate(point, &region.prototype)?;
+                    if best.is_none_or(|(_, closest)| d < closest) {
+                        best = Some((r, d));
+                    }
+                }
+                best.map(|(r, _)| r)
+            }
+        };
+        labels.push(label);
+    }
+
+    let assigned: Vec<usize> = (0..points.len()).filter(|&i| labels[i].is_some()).collect();
+    let dimensions = space.points.dimensions();
+    let mut rows = DMatrix::zeros(assigned.len(), dimensions);
+    for (row, &i) in assigned.iter().enumerate() {
+        rows.row_mut(row).tr_copy_from(&points[i].1.coordinates);
+    }
+    let assigned_labels: Vec<usize> = assigned.iter().filter_map(|&i| labels[i]).collect();
+    let silhouettes = silhouette_values(&distance_matrix(formation.metric(), &rows)?, &assigned_labels, k);
+
+    // Centroids of the assigned points and the overall centroid
+    let mut sizes = vec![0usize; k];
+    let mut sums = vec![DVector::zeros(dimensions); k];
+    for (row, &label) in assigned_labels.iter().enumerate() {
+        sizes[label] += 1;
+        sums[label] += rows.row(row).transpose();
+    }
+    let as_point = |coordinates: DVector<f64>| ConceptualPoint::new(coordinates.as_slice().to_vec(), HashMap::new());
+    let centroids: Vec<Option<ConceptualPoint>> = (0..k)
+        .map(|c| (sizes[c] > 0).then(|| as_point(&sums[c] / sizes[c] as f64)))
+        .collect();
+
+    let mut spread = vec![0.0; k];
+    let mut within_dispersion = 0.0;
+    for (row, &label) in assigned_labels.iter().enumerate() {
+        if let Some(centroid) = &centroids[label] {
+            let d = formation.metric().calculate(&points[assigned[row]].1, centroid)?;
+            spread[label] += d;
+            within_dispersion += d * d;
+        }
+    }
+
+    let mut between_dispersion = 0.0;
+    if !assigned.is_empty() {
+        let overall = as_point(sums.iter().fold(DVector::zeros(dimensions), |sum, s| sum + s) / assigned.len() as f64);
+        for (c, centroid) in centroids.iter().enumerate() {
+            if let Some(centroid) = centroid {
+                let d = formation.metric().calculate(centroid, &overall)?;
+                between_dispersion += sizes[c] as f64 * d * d;
+            }
+        }
+    }
+
+    let occupied: Vec<usize> = (0..k).filter(|&c| sizes[c] > 0).collect();
+    let scatter: Vec<f64> = (0..k).map(|c| if sizes[c] > 0 { spread[c] / sizes[c] as f64 } else { 0.0 }).collect();
+    let davies_bouldin = if occupied.len() < 2 {
+        None
+    } else {
+        let mut total = 0.0;
+        for &a in &occupied {
+            let mut worst: f64 = 0.0;
+            for &b in occupied.iter().filter(|&&b| b != a) {
+                if let (Some(ca), Some(cb)) = (&centroids[a], &centroids[b]) {
+                    let separation = formation.metric().calculate(ca, cb)?;
+                    let ratio = if separation > 0.0 { (scatter[a] + scatter[b]) / separation } else { f64::INFINITY };
+                    worst = worst.max(ratio);
+                }
+            }
+            total += worst;
+        }
+        Some(total / occupied.len() as f64)
+    };
+    let calinski_harabasz = (occupied.len() >= 2 && assigned.len() > occupied.len() && within_dispersion > 0.0)
+        .then(|| (between_dispersion / (occupied.len() - 1) as f64) / (within_dispersion / (assigned.len() - occupied.len()) as f64));
+
+    // Geometric checks against the regions themselves
+    let mut overlaps = Vec::new();
+    for a in 0..k {
+        for b in a + 1..k {
+            let shared_points = inside.iter().filter(|flags| flags[a] && flags[b]).count();
+            if shared_points > 0 {
+                overlaps.push(RegionOverlap { first: regions[a].id, second: regions[b].id, shared_points });
+            }
+        }
+    }
+
+    let mut qualities = Vec::with_capacity(k);
+    for (r, region) in regions.iter().enumerate() {
+        let own: Vec<usize> = (0..points.len()).filter(|&i| labels[i] == Some(r)).collect();
+        let members_outside = own.iter().filter(|&&i| !inside[i][r]).count();
+        let foreign_points = (0..points.len())
+            .filter(|&i| inside[i][r] && labels[i].is_some_and(|label| label != r))
+            .count();
+        let contained = inside.iter().filter(|flags| flags[r]).count();
+        let shared = inside.iter().filter(|flags| flags[r] && flags.iter().filter(|&&f| f).count() > 1).count();
+        let own_points: Vec<ConceptualPoint> = own.iter().map(|&i| points[i].1.clone()).collect();
+        let silhouette = if own.is_empty() {
+            0.0
+        } else {
+            assigned_labels.iter().zip(&silhouettes)
+                .filter(|(&label, _)| label == r)
+                .map(|(_, s)| s)
+                .sum::<f64>() / own.len() as f64
+        };
+
+        qualities.push(RegionQuality {
+            region_id: region.id,
+            name: region.name.clone(),
+            size: own.len(),
+            silhouette,
+            dispersion: scatter[r],
+            members_outside,
+            foreign_points,
+            overlap_fraction: if contained > 0 { shared as f64 / contained as f64 } else { 0.0 },
+            convex: region.is_convex(&own_points),
+        });
+    }
+
+    let outside: usize = qualities.iter().map(|q| q.members_outside).sum();
+    let convexity_violations = qualities.iter()
+        .map(|q| q.foreign_points + usize::from(!q.convex))
+        .sum();
+
+    Ok(CategoryQualityReport {
+        space_id: space.id,
+        assigned_points: assigned.len(),
+        unassigned_points: points.len() - assigned.len(),
+        silhouette: if silhouettes.is_empty() { 0.0 } else { silhouettes.iter().sum::<f64>() / silhouettes.len() as f64 },
+        davies_bouldin,
+        calinski_harabasz,
+        within_dispersion,
+        between_dispersion,
+        outside_fraction: if assigned.is_empty() { 0.0 } else { outside as f64 / assigned.len() as f64 },
+        overlaps,
+        convexity_violations,
+        regions: qualities,
+    })
+}
+
+/// Score the regions already stored in a space
+pub(crate) fn evaluate_space(formation: &CategoryFormation, space: &ConceptualSpace) -> ConceptualResult<CategoryQualityReport> {
+    let mut regions: Vec<ConvexRegion> = space.regions.values().cloned().collect();
+    regions.sort_by_key(|r| r.id);
+    evaluate(formation, space, &regions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ConceptualMetric, DimensionId, DistanceMetric};
+
+    fn space_with_blobs() -> (ConceptualSpace, Vec<Vec<Uuid>>) {
+        let mut space = ConceptualSpace::new(
+            "Loans".to_string(),
+            vec![DimensionId::new(), DimensionId::new()],
+            ConceptualMetric::uniform(2, 2.0),
+        );
+        let mut groups = Vec::new();
+        for (cx, cy) in [(0.0, 0.0), (10.0, 0.0)] {
+            let mut ids = Vec::new();
+            for (dx, dy) in [(0.0, 0.0), (0.5, 0.0), (0.0, 0.5), (-0.5, 0.0), (0.0, -0.5)] {
+                ids.push(space.add_point(ConceptualPoint::new(vec![cx + dx, cy + dy], HashMap::new())).unwrap());
+            }
+            groups.push(ids);
+        }
+        (space, groups)
+    }
+
+    fn region(space: &ConceptualSpace, ids: &[Uuid]) -> ConvexRegion {
+        let points: Vec<ConceptualPoint> = ids.iter().map(|id| space.points.get(id).unwrap()).collect();
+        ConvexRegion::hull_around_centroid(&points).unwrap()
+    }
+
+    #[test]
+    fn test_well_separated_categories_score_well() {
+        let (space, groups) = space_with_blobs();
+        let regions: Vec<ConvexRegion> = groups.iter().map(|ids| region(&space, ids)).collect();
+        let report = CategoryFormation::new(DistanceMetric::Euclidean).evaluate(&space, &regions).unwrap();
+
+        assert_eq!(report.assigned_points, 10);
+        assert_eq!(report.unassigned_points, 0);
+        assert!(report.silhouette > 0.9);
+        assert!(report.davies_bouldin.unwrap() < 0.1);
+        assert!(report.calinski_harabasz.unwrap() > 100.0);
+        assert!(report.between_dispersion > report.within_dispersion);
+        assert_eq!(report.outside_fraction, 0.0);
+        assert!(report.overlaps.is_empty());
+        assert_eq!(report.convexity_violations, 0);
+        assert!(report.regions.iter().all(|q| q.size == 5 && q.convex));
+
+        let json = serde_json::to_string(&report).unwrap();
+        let restored: CategoryQualityReport = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored, report);
+    }
+
+    #[test]
+    fn test_overlapping_categories_are_flagged() {
+        let (space, groups) = space_with_blobs();
+        // One region spans both blobs but only claims the first as members
+        let all: Vec<Uuid> = groups.concat();
+        let mut wide = region(&space, &all);
+        wide.member_points = groups[0].iter().copied().collect();
+        let narrow = region(&space, &groups[1]);
+        let report = CategoryFormation::new(DistanceMetric::Euclidean).evaluate(&space, &[wide, narrow]).unwrap();
+
+        assert_eq!(report.overlaps.len(), 1);
+        assert_eq!(report.overlaps[0].shared_points, 5);
+        assert_eq!(report.regions[0].foreign_points, 5);
+        assert_eq!(report.regions[0].overlap_fraction, 0.5);
+        assert_eq!(report.regions[1].overlap_fraction, 1.0);
+        assert_eq!(report.convexity_violations, 5);
+    }
+
+    #[test]
+    fn test_members_outside_region_and_unassigned_points() {
+        let (space, groups) = space_with_blobs();
+        let mut first = region(&space, &groups[0][..3]);
+        first.member_points = groups[0].iter().copied().collect();
+        let report = CategoryFormation::new(DistanceMetric::Euclidean).evaluate(&space, &[first]).unwrap();
+
+        assert_eq!(report.assigned_points, 5);
+        assert_eq!(report.unassigned_points, 5);
+        assert_eq!(report.regions[0].members_outside, 2);
+        assert_eq!(report.outside_fraction, 0.4);
+        assert!(report.davies_bouldin.is_none());
+        assert!(report.calinski_harabasz.is_none());
+        assert!(!report.regions[0].convex);
+    }
+}
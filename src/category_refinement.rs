@@ -0,0 +1,478 @@
+//! Split and merge recommendations for category regions
+//!
+//! Regions drift as concepts arrive: some end up covering two clusters,
+//! others duplicate a neighbour. The analyzer scans a space's regions and
+//! proposes splitting regions whose members are bimodal along their
+//! principal axis and merging regions whose prototypes are close and whose
+//! members overlap. Every proposal carries an estimated gain and the
+//! commands that carry it out.
+
+use crate::commands::{AddRegion, RemoveRegion};
+use crate::{ConceptualPoint, ConceptualResult, ConceptualSpace, ConvexRegion, DistanceMetric};
+use nalgebra::{DMatrix, DVector};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use uuid::Uuid;
+
+/// Thresholds for split and merge proposals
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct RefinementConfig {
+    /// Bimodality coefficient above which a region is split; 5/9 is that of a uniform distribution
+    pub bimodality_threshold: f64,
+
+    /// Fewest members a region needs to be considered for a split
+    pub min_split_size: usize,
+
+    /// Prototype distance within which two regions may merge
+    pub merge_distance: f64,
+
+    /// Fraction of the pair's members inside both regions needed to merge
+    pub min_overlap: f64,
+}
+
+impl Default for RefinementConfig {
+    fn default() -> Self {
+        Self {
+            bimodality_threshold: 5.0 / 9.0,
+            min_split_size: 8,
+            merge_distance: 1.0,
+            min_overlap: 0.2,
+        }
+    }
+}
+
+/// What a proposal does to its regions
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ProposalKind {
+    /// Replace one region by two
+    Split,
+
+    /// Replace two regions by one
+    Merge,
+}
+
+/// A command needed to carry out a proposal
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum RefinementCommand {
+    /// Remove a region being replaced
+    RemoveRegion(RemoveRegion),
+
+    /// Add a replacement region
+    AddRegion(Box<AddRegion>),
+}
+
+/// A recommended split or merge
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RefinementProposal {
+    /// Split or merge
+    pub kind: ProposalKind,
+
+    /// Regions the proposal replaces
+    pub regions: Vec<Uuid>,
+
+    /// Regions replacing them
+    pub replacements: Vec<ConvexRegion>,
+
+    /// Bimodality coefficient for splits, member overlap for merges
+    pub evidence: f64,
+
+    /// Estimated quality gain in [0, 1]
+    ///
+    /// For a split, the fraction of the region's dispersion explained by
+    /// separating the halves; for a merge, the fraction of the combined
+    /// dispersion the separation fails to explain.
+    pub gain: f64,
+
+    /// Removals then additions, ready to dispatch in order
+    pub commands: Vec<RefinementCommand>,
+}
+
+/// Scans a space's regions for split and merge opportunities
+#[derive(Debug, Clone)]
+pub struct CategoryRefinementAnalyzer {
+    metric: DistanceMetric,
+    config: RefinementConfig,
+}
+
+impl CategoryRefinementAnalyzer {
+    /// Create an analyzer with default thresholds
+    pub fn new(metric: DistanceMetric) -> Self {
+        Self { metric, config: RefinementConfig::default() }
+    }
+
+    /// Replace the thresholds
+    pub fn with_config(mut self, config: RefinementConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Propose splits and merges for the space's regions, best gain first
+    ///
+    /// A region's members are the points it lists, or the points it
+    /// contains when it lists none. No two proposals touch the same region:
+    /// when a split and a merge, or two merges, would replace one region,
+    /// only the proposal with the higher gain is kept, so every proposal
+    /// returned can be dispatched in turn.
+    ///
+    /// ```mermaid
+    /// graph TD
+    ///     A[Space Regions] --> B[Members per Region]
+    ///     B --> C[Project on Principal Axis]
+    ///     C --> D{Bimodal?}
+    ///     D -->|yes| E[Split at Best 1D Cut]
+    ///     B --> F[Pairs with Close Prototypes]
+    ///     F --> G{Members Overlap?}
+    ///     G -->|yes| H[Merge into Hull]
+    ///     E --> I[Gain + Commands]
+    ///     H --> I
+    /// ```
+    pub fn analyze(&self, space: &ConceptualSpace) -> ConceptualResult<Vec<RefinementProposal>> {
+        let mut regions: Vec<&ConvexRegion> = space.regions.values().collect();
+        regions.sort_by_key(|region| region.id);
+        let members: Vec<Vec<ConceptualPoint>> = regions.iter()
+            .map(|region| {
+                let listed: Vec<ConceptualPoint> = space.points.iter()
+                    .filter(|(id, _)| region.member_points.contains(*id))
+                    .map(|(_, point)| point)
+                    .collect();
+                if !listed.is_empty() || !region.member_points.is_empty() {
+                    return listed;
+                }
+                space.points.values()
+                    .filter(|point| region.contains(point))
+                    .collect()
+            })
+            .collect();
+
+        let mut proposals = Vec::new();
+        for (region, points) in regions.iter().zip(&members) {
+            if let Some(proposal) = self.propose_split(space, region, points)? {
+                proposals.push(proposal);
+            }
+        }
+        for a in 0..regions.len() {
+            for b in a + 1..regions.len() {
+                if let Some(proposal) = self.propose_merge(space, [regions[a], regions[b]], [&members[a], &members[b]])? {
+                    proposals.push(proposal);
+                }
+            }
+        }
+
+        proposals.sort_by(|x, y| y.gain.total_cmp(&x.gain));
+        let mut claimed = HashSet::new();
+        proposals.retain(|proposal| {
+            let free = proposal.regions.iter().all(|id| !claimed.contains(id));
+            if free {
+                claimed.extend(proposal.regions.iter().copied());
+            }
+            free
+        });
+        Ok(proposals)
+    }
+
+    fn propose_split(
+        &self,
+        space: &ConceptualSpace,
+        region: &ConvexRegion,
+        members: &[ConceptualPoint],
+    ) -> ConceptualResult<Option<RefinementProposal>> {
+        let n = members.len();
+        if n < self.config.min_split_size.max(4) {
+            return Ok(None);
+        }
+
+        let centroid = centroid(members);
+        let dimensions = centroid.len();
+        let mut centred = DMatrix::zeros(n, dimensions);
+        for (row, point) in members.iter().enumerate() {
+            centred.row_mut(row).tr_copy_from(&(&point.coordinates - &centroid));
+        }
+        let eigen = (centred.transpose() * &centred / n as f64).symmetric_eigen();
+        let principal = eigen.eigenvalues.imax();
+        let projections: Vec<f64> = (&centred * eigen.eigenvectors.column(principal)).iter().copied().collect();
+
+        let Some(coefficient) = bimodality_coefficient(&projections) else {
+            return Ok(None);
+        };
+        if coefficient <= self.config.bimodality_threshold {
+            return Ok(None);
+        }
+
+        // Best two-group cut of the sorted projections
+        let mut order: Vec<usize> = (0..n).collect();
+        order.sort_by(|&i, &j| projections[i].total_cmp(&projections[j]));
+        let sorted: Vec<f64> = order.iter().map(|&i| projections[i]).collect();
+        let cut = (2..=n - 2)
+            .min_by(|&x, &y| split_cost(&sorted, x).total_cmp(&split_cost(&sorted, y)))
+            .unwrap_or(n / 2);
+        let halves: [Vec<ConceptualPoint>; 2] = [
+            order[..cut].iter().map(|&i| members[i].clone()).collect(),
+            order[cut..].iter().map(|&i| members[i].clone()).collect(),
+        ];
+
+        let total = self.dispersion(members)?;
+        if total == 0.0 {
+            return Ok(None);
+        }
+        let remaining = self.dispersion(&halves[0])? + self.dispersion(&halves[1])?;
+
+        let name = region.name.clone().unwrap_or_else(|| "Region".to_string());
+        let replacements: Vec<ConvexRegion> = halves.iter()
+            .zip(["A", "B"])
+            .map(|(half, suffix)| {
+                Ok(ConvexRegion::hull_around_centroid(half)?
+                    .with_name(format!("{name} {suffix}"))
+                    .with_description(format!("Split from {}", region.id)))
+            })
+            .collect::<ConceptualResult<_>>()?;
+
+        Ok(Some(proposal(space, ProposalKind::Split, vec![region.id], replacements, coefficient, 1.0 - remaining / total)))
+    }
+
+    fn propose_merge(
+        &self,
+        space: &ConceptualSpace,
+        regions: [&ConvexRegion; 2],
+        members: [&Vec<ConceptualPoint>; 2],
+    ) -> ConceptualResult<Option<RefinementProposal>> {
+        let separation = self.metric.calculate(&regions[0].prototype, &regions[1].prototype)?;
+        if separation > self.config.merge_distance {
+            return Ok(None);
+        }
+
+        let mut union: Vec<ConceptualPoint> = members[0].clone();
+        for point in members[1] {
+            if !union.iter().any(|p| p.id == point.id) {
+                union.push(point.clone());
+            }
+        }
+        if union.is_empty() {
+            return Ok(None);
+        }
+        let shared = union.iter().filter(|p| regions[0].contains(p) && regions[1].contains(p)).count();
+        let overlap = shared as f64 / union.len() as f64;
+        if overlap < self.config.min_overlap {
+            return Ok(None);
+        }
+
+        let combined = self.dispersion(&union)?;
+        let apart = self.dispersion(members[0])? + self.dispersion(members[1])?;
+        let gain = if combined > 0.0 { (apart / combined).min(1.0) } else { 1.0 };
+
+        let names: Vec<String> = regions.iter()
+            .map(|r| r.name.clone().unwrap_or_else(|| "Region".to_string()))
+            .collect();
+        let merged = ConvexRegion::hull_around_centroid(&union)?
+            .with_name(format!("{} + {}", names[0], names[1]))
+            .with_description(format!("Merged from {} and {}", regions[0].id, regions[1].id));
+
+        Ok(Some(proposal(space, ProposalKind::Merge, vec![regions[0].id, regions[1].id], vec![merged], overlap, gain)))
+    }
+
+    /// Sum of squared distances of points to their centroid under the metric
+    fn dispersion(&self, points: &[ConceptualPoint]) -> ConceptualResult<f64> {
+        if points.is_empty() {
+            return Ok(0.0);
+        }
+        let centre = ConceptualPoint::new(centroid(points).as_slice().to_vec(), points[0].dimension_map.clone());
+        points.iter().try_fold(0.0, |sum, point| {
+            let d = self.metric.calculate(point, &centre)?;
+            Ok(sum + d * d)
+        })
+    }
+}
+
+fn proposal(
+    space: &ConceptualSpace,
+    kind: ProposalKind,
+    regions: Vec<Uuid>,
+    replacements: Vec<ConvexRegion>,
+    evidence: f64,
+    gain: f64,
+) -> RefinementProposal {
+    let commands = regions.iter()
+        .map(|&region_id| RefinementCommand::RemoveRegion(RemoveRegion { space_id: space.id, region_id }))
+        .chain(replacements.iter().map(|region| {
+            RefinementCommand::AddRegion(Box::new(AddRegion { space_id: space.id, region: region.clone() }))
+        }))
+        .collect();
+    RefinementProposal { kind, regions, replacements, evidence, gain: gain.clamp(0.0, 1.0), commands }
+}
+
+fn centroid(points: &[ConceptualPoint]) -> DVector<f64> {
+    let sum = points.iter().fold(DVector::zeros(points[0].coordinates.len()), |sum, p| sum + &p.coordinates);
+    sum / points.len() as f64
+}
+
+/// Sarle's bimodality coefficient from sample skewness and excess kurtosis
+///
+/// `None` for fewer than four values or no spread.
+fn bimodality_coefficient(values: &[f64]) -> Option<f64> {
+    let n = values.len() as f64;
+    if values.len() < 4 {
+        return None;
+    }
+    let mean = values.iter().sum::<f64>() / n;
+    let moment = |k: i32| values.iter().map(|v| (v - mean).powi(k)).sum::<f64>() / n;
+    let m2 = moment(2);
+    if m2 <= 0.0 {
+        return None;
+    }
+    let skewness = moment(3) / m2.powf(1.5) * (n * (n - 1.0)).sqrt() / (n - 2.0);
+    let correction = (n - 2.0) * (n - 3.0);
+    let kurtosis = ((n + 1.0) * moment(4) / (m2 * m2) - 3.0 * (n - 1.0)) * (n - 1.0) / correction;
+    Some((skewness * skewness + 1.0) / (kurtosis + 3.0 * (n - 1.0).powi(2) / correction))
+}
+
+/// Within-group sum of squares of sorted values cut before index `cut`
+fn split_cost(sorted: &[f64], cut: usize) -> f64 {
+    let sse = |values: &[f64]| {
+        let mean = values.iter().sum::<f64>() / values.len() as f64;
+        values.iter().map(|v| (v - mean).powi(2)).sum::<f64>()
+    };
+    sse(&sorted[..cut]) + sse(&sorted[cut..])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::aggregate::ConceptualSpaceAggregate;
+    use crate::sampling::SplitMix64;
+    use crate::{ConceptualMetric, DimensionId};
+    use std::collections::HashMap;
+
+    fn aggregate() -> ConceptualSpaceAggregate {
+        ConceptualSpaceAggregate::new(
+            "Loans".to_string(),
+            vec![DimensionId::new(), DimensionId::new()],
+            ConceptualMetric::uniform(2, 2.0),
+        )
+    }
+
+    /// Roughly normal blob from sums of uniforms
+    fn blob(aggregate: &mut ConceptualSpaceAggregate, rng: &mut SplitMix64, cx: f64, n: usize) -> Vec<ConceptualPoint> {
+        (0..n)
+            .map(|_| {
+                let mut coordinate = || (0..4).map(|_| rng.next_f64()).sum::<f64>() - 2.0;
+                let mut point = ConceptualPoint::new(vec![cx + 0.5 * coordinate(), 0.5 * coordinate()], HashMap::new());
+                point.id = Some(aggregate.add_point(point.clone()).unwrap());
+                point
+            })
+            .collect()
+    }
+
+    fn dispatch(aggregate: &mut ConceptualSpaceAggregate, proposal: &RefinementProposal) {
+        for command in &proposal.commands {
+            match command {
+                RefinementCommand::RemoveRegion(remove) => {
+                    aggregate.remove_region(&remove.region_id).unwrap();
+                }
+                RefinementCommand::AddRegion(add) => aggregate.add_region(add.region.clone()).unwrap(),
+            }
+        }
+    }
+
+    #[test]
+    fn test_bimodal_region_is_split() {
+        let mut aggregate = aggregate();
+        let mut rng = SplitMix64::new(3);
+        let mut points = blob(&mut aggregate, &mut rng, 0.0, 20);
+        points.extend(blob(&mut aggregate, &mut rng, 6.0, 20));
+        let region = ConvexRegion::hull_around_centroid(&points).unwrap().with_name("Bridge".to_string());
+        aggregate.add_region(region.clone()).unwrap();
+
+        let analyzer = CategoryRefinementAnalyzer::new(DistanceMetric::Euclidean);
+        let proposals = analyzer.analyze(aggregate.space()).unwrap();
+        assert_eq!(proposals.len(), 1);
+        let split = &proposals[0];
+        assert_eq!(split.kind, ProposalKind::Split);
+        assert_eq!(split.regions, vec![region.id]);
+        assert!(split.evidence > 5.0 / 9.0);
+        assert!(split.gain > 0.8);
+        assert_eq!(split.commands.len(), 3);
+
+        dispatch(&mut aggregate, split);
+        let space = aggregate.space();
+        assert_eq!(space.regions.len(), 2);
+        for point in &points {
+            assert_eq!(space.find_containing_regions(point).len(), 1);
+        }
+        assert!(analyzer.analyze(space).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_duplicate_regions_are_merged() {
+        let mut aggregate = aggregate();
+        let mut rng = SplitMix64::new(5);
+        let points = blob(&mut aggregate, &mut rng, 0.0, 12);
+        let first = ConvexRegion::hull_around_centroid(&points[..8]).unwrap().with_name("Fix & Flip".to_string());
+        let second = ConvexRegion::hull_around_centroid(&points[4..]).unwrap().with_name("Flip".to_string());
+        aggregate.add_region(first.clone()).unwrap();
+        aggregate.add_region(second.clone()).unwrap();
+
+        let proposals = CategoryRefinementAnalyzer::new(DistanceMetric::Euclidean)
+            .analyze(aggregate.space())
+            .unwrap();
+        let merge = proposals.iter().find(|p| p.kind == ProposalKind::Merge).unwrap();
+        assert!(merge.regions.contains(&first.id) && merge.regions.contains(&second.id));
+        assert!(merge.evidence >= 0.2);
+        assert!(merge.gain > 0.5);
+        assert_eq!(merge.replacements[0].member_points.len(), 12);
+
+        dispatch(&mut aggregate, merge);
+        let space = aggregate.space();
+        assert_eq!(space.regions.len(), 1);
+        assert!(points.iter().all(|p| space.find_containing_regions(p).len() == 1));
+    }
+
+    #[test]
+    fn test_conflicting_proposals_keep_the_higher_gain() {
+        let mut aggregate = aggregate();
+        let mut rng = SplitMix64::new(11);
+        let mut points = blob(&mut aggregate, &mut rng, 0.0, 20);
+        points.extend(blob(&mut aggregate, &mut rng, 6.0, 20));
+        // Bimodal, and a near-duplicate of its copy
+        let bridge = ConvexRegion::hull_around_centroid(&points).unwrap().with_name("Bridge".to_string());
+        let copy = ConvexRegion::hull_around_centroid(&points[1..]).unwrap().with_name("Bridge Copy".to_string());
+        aggregate.add_region(bridge.clone()).unwrap();
+        aggregate.add_region(copy.clone()).unwrap();
+
+        let proposals = CategoryRefinementAnalyzer::new(DistanceMetric::Euclidean)
+            .analyze(aggregate.space())
+            .unwrap();
+        assert_eq!(proposals.len(), 1);
+        assert_eq!(proposals[0].kind, ProposalKind::Merge);
+        assert!(proposals[0].regions.contains(&bridge.id) && proposals[0].regions.contains(&copy.id));
+
+        for proposal in &proposals {
+            dispatch(&mut aggregate, proposal);
+        }
+        assert_eq!(aggregate.space().regions.len(), 1);
+    }
+
+    #[test]
+    fn test_compact_distant_regions_are_left_alone() {
+        let mut aggregate = aggregate();
+        let mut rng = SplitMix64::new(7);
+        for cx in [0.0, 8.0] {
+            let points = blob(&mut aggregate, &mut rng, cx, 30);
+            let region = ConvexRegion::hull_around_centroid(&points).unwrap().with_name(format!("Blob {cx}"));
+            aggregate.add_region(region).unwrap();
+        }
+
+        let proposals = CategoryRefinementAnalyzer::new(DistanceMetric::Euclidean)
+            .analyze(aggregate.space())
+            .unwrap();
+        assert!(proposals.is_empty());
+    }
+
+    #[test]
+    fn test_bimodality_coefficient() {
+        let bimodal: Vec<f64> = (0..40).map(|i| if i % 2 == 0 { 0.0 } else { 5.0 } + 0.01 * i as f64).collect();
+        assert!(bimodality_coefficient(&bimodal).unwrap() > 5.0 / 9.0);
+        let unimodal: Vec<f64> = (0..41).map(|i| (i as f64 - 20.0).powi(3)).collect();
+        assert!(bimodality_coefficient(&unimodal).unwrap() < 5.0 / 9.0);
+        assert!(bimodality_coefficient(&[1.0, 1.0, 1.0, 1.0]).is_none());
+        assert!(bimodality_coefficient(&[1.0, 2.0]).is_none());
+    }
+}
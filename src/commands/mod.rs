@@ -3,11 +3,13 @@
 mod create_space;
 mod add_concept;
 mod add_region;
+mod remove_region;
 mod update_weights;
 
 pub use create_space::*;
 pub use add_concept::*;
 pub use add_region::*;
+pub use remove_region::*;
 pub use update_weights::*;
 
 use crate::ConceptualSpaceId;
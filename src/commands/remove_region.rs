@@ -0,0 +1,30 @@
+//! Command to remove a region from a conceptual space
+
+use crate::ConceptualSpaceId;
+use cim_domain::{Command, EntityId, markers::AggregateMarker};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Command to remove a region from a conceptual space
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RemoveRegion {
+    /// The space to remove the region from
+    pub space_id: ConceptualSpaceId,
+
+    /// The region to remove
+    pub region_id: Uuid,
+}
+
+impl super::ConceptualSpaceCommand for RemoveRegion {
+    fn space_id(&self) -> ConceptualSpaceId {
+        self.space_id
+    }
+}
+
+impl Command for RemoveRegion {
+    type Aggregate = AggregateMarker;
+
+    fn aggregate_id(&self) -> Option<EntityId<Self::Aggregate>> {
+        Some(EntityId::from_uuid(self.space_id.0))
+    }
+}
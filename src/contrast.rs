@@ -0,0 +1,344 @@
+//! Contrast classes for context-sensitive category modifiers
+//!
+//! Gärdenfors reads "red wine" or "tall jockey" through contrast classes: the
+//! modifier is not applied on its full domain but re-scaled to the part of the
+//! domain the modified category occupies. A red wine is red *for a wine*.
+//! Here the reference set's range is mapped affinely onto the modifier's
+//! domain, and the modifier region is pulled back through that map. The same
+//! scaling can be registered as a context of `DimensionWeight::Contextual`
+//! weights so distances inside the contrast class are judged on the
+//! modifier's scale.
+
+use crate::{
+    ConceptualError, ConceptualMetric, ConceptualPoint, ConceptualResult, ConceptualSpace,
+    ConvexRegion, DimensionWeight, Hyperplane,
+};
+use nalgebra::DVector;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Per-dimension range of values
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ValueRange {
+    /// Lowest value per dimension
+    pub lower: DVector<f64>,
+
+    /// Highest value per dimension
+    pub upper: DVector<f64>,
+}
+
+impl ValueRange {
+    /// Create a range from its bounds
+    pub fn new(lower: Vec<f64>, upper: Vec<f64>) -> ConceptualResult<Self> {
+        if lower.len() != upper.len() {
+            return Err(ConceptualError::InvalidDimension(
+                "Range bounds have different dimensions".to_string()
+            ));
+        }
+        if lower.iter().zip(&upper).any(|(l, u)| l.is_nan() || u.is_nan() || l > u) {
+            return Err(ConceptualError::InvalidDimension(
+                "Range lower bound exceeds upper bound".to_string()
+            ));
+        }
+        Ok(Self { lower: DVector::from_vec(lower), upper: DVector::from_vec(upper) })
+    }
+
+    /// Smallest range covering a point set
+    pub fn from_points(points: &[ConceptualPoint]) -> ConceptualResult<Self> {
+        let Some(first) = points.first() else {
+            return Err(ConceptualError::InvalidPoint("Empty reference set".to_string()));
+        };
+        let mut lower = first.coordinates.clone();
+        let mut upper = first.coordinates.clone();
+        for point in &points[1..] {
+            if point.coordinates.len() != lower.len() {
+                return Err(ConceptualError::InvalidPoint("Points have different dimensions".to_string()));
+            }
+            lower = lower.inf(&point.coordinates);
+            upper = upper.sup(&point.coordinates);
+        }
+        Ok(Self { lower, upper })
+    }
+
+    /// Range of a region
+    ///
+    /// Axis-aligned bounded regions give their exact bounds; otherwise the
+    /// range covers the region's members in the space, or the space's points
+    /// inside it when it lists none.
+    pub fn from_region(region: &ConvexRegion, space: &ConceptualSpace) -> ConceptualResult<Self> {
+        let dimensions = region.prototype.coordinates.len();
+        if let Some((lower, upper)) = region.axis_aligned_bounds(dimensions) {
+            if lower.iter().chain(upper.iter()).all(|b| b.is_finite()) {
+                return Ok(Self { lower, upper });
+            }
+        }
+
+        let mut points: Vec<ConceptualPoint> = region.member_points.iter()
+            .filter_map(|id| space.points.get(id))
+            .collect();
+        if points.is_empty() {
+            points = space.points.values().filter(|p| region.contains(p)).collect();
+        }
+        Self::from_points(&points)
+    }
+
+    /// Range of all points in a space
+    pub fn of_space(space: &ConceptualSpace) -> ConceptualResult<Self> {
+        Self::from_points(&space.points.values().collect::<Vec<_>>())
+    }
+
+    /// Number of dimensions
+    pub fn dimensions(&self) -> usize {
+        self.lower.len()
+    }
+}
+
+/// Re-scales modifiers from their domain to a reference set's range
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContrastClass {
+    domain: ValueRange,
+    dimensions: Option<Vec<usize>>,
+}
+
+impl ContrastClass {
+    /// Contrast class over a modifier's domain, re-scaling every dimension
+    pub fn new(domain: ValueRange) -> Self {
+        Self { domain, dimensions: None }
+    }
+
+    /// Only re-scale these dimensions, e.g. the colour domain of "red"
+    pub fn on_dimensions(mut self, dimensions: Vec<usize>) -> Self {
+        self.dimensions = Some(dimensions);
+        self
+    }
+
+    /// Affine map `y = scale ⊙ x + shift` taking the reference range onto the domain
+    ///
+    /// Dimensions outside the contrast, or where the reference range is a
+    /// single value, map to themselves. A domain without width on a contrast
+    /// dimension would collapse it, so it is rejected.
+    pub fn mapping(&self, reference: &ValueRange) -> ConceptualResult<(DVector<f64>, DVector<f64>)> {
+        let n = self.domain.dimensions();
+        if reference.dimensions() != n {
+            return Err(ConceptualError::InvalidDimension(format!(
+                "Reference has {} dimensions, domain has {n}",
+                reference.dimensions()
+            )));
+        }
+
+        let mut scale = DVector::from_element(n, 1.0);
+        let mut shift = DVector::zeros(n);
+        for i in 0..n {
+            if self.dimensions.as_ref().is_some_and(|dims| !dims.contains(&i)) {
+                continue;
+            }
+            let span = self.domain.upper[i] - self.domain.lower[i];
+            if span <= 0.0 {
+                return Err(ConceptualError::InvalidDimension(format!(
+                    "Domain has no width on contrast dimension {i}"
+                )));
+            }
+            let width = reference.upper[i] - reference.lower[i];
+            if width <= 0.0 {
+                continue;
+            }
+            scale[i] = span / width;
+            shift[i] = self.domain.lower[i] - scale[i] * reference.lower[i];
+        }
+        Ok((scale, shift))
+    }
+
+    /// Where a point of the reference set lands in the modifier's domain
+    pub fn to_domain(&self, point: &ConceptualPoint, reference: &ValueRange) -> ConceptualResult<ConceptualPoint> {
+        let (scale, shift) = self.mapping(reference)?;
+        self.check(point)?;
+        Ok(ConceptualPoint {
+            coordinates: point.coordinates.component_mul(&scale) + shift,
+            ..point.clone()
+        })
+    }
+
+    /// The modifier relativized to the reference set
+    ///
+    /// The result holds the points whose image in the modifier's domain lies
+    /// in the modifier: "red" restricted and stretched to the colours of wines.
+    ///
+    /// ```mermaid
+    /// graph TD
+    ///     A[Modifier Region] --> D[Pull Back Half-Spaces]
+    ///     B[Reference Set] --> C[Reference Range]
+    ///     C --> E[Affine Map onto Domain]
+    ///     E --> D
+    ///     D --> F[Relativized Region]
+    /// ```
+    pub fn relativize(&self, modifier: &ConvexRegion, reference: &ValueRange) -> ConceptualResult<ConvexRegion> {
+        let (scale, shift) = self.mapping(reference)?;
+        self.check(&modifier.prototype)?;
+
+        // n·y ≥ b with y = scale ⊙ x + shift becomes (n ⊙ scale)·x ≥ b − n·shift
+        let boundaries = modifier.boundaries.iter()
+            .map(|plane| Hyperplane::new(plane.normal.component_mul(&scale), plane.offset - plane.normal.dot(&shift)))
+            .collect();
+        let prototype = ConceptualPoint {
+            coordinates: (&modifier.prototype.coordinates - &shift).component_div(&scale),
+            id: None,
+            ..modifier.prototype.clone()
+        };
+
+        let name = modifier.name.clone().unwrap_or_else(|| "Region".to_string());
+        Ok(ConvexRegion {
+            id: Uuid::new_v4(),
+            prototype,
+            boundaries,
+            member_points: Default::default(),
+            name: Some(format!("{name} (contrast class)")),
+            description: Some(format!("{name} relativized to a reference range")),
+        })
+    }
+
+    /// A metric whose weights judge distances in `context` on the modifier's scale
+    ///
+    /// Each weight gains a modifier for `context` equal to its value there
+    /// times `scale^p`; constant weights become contextual. Attentional
+    /// weights are left as they are.
+    pub fn contextualize(
+        &self,
+        metric: &ConceptualMetric,
+        context: &str,
+        reference: &ValueRange,
+    ) -> ConceptualResult<ConceptualMetric> {
+        let (scale, _) = self.mapping(reference)?;
+        if metric.dimension_weights.len() != scale.len() {
+            return Err(ConceptualError::InvalidDimension(
+                "Weight vector has incorrect length".to_string()
+            ));
+        }
+
+        let dimension_weights = metric.dimension_weights.iter()
+            .zip(scale.iter())
+            .map(|(weight, factor)| {
+                let contextual = weight.value(Some(context)) * factor.powf(metric.minkowski_p);
+                match weight {
+                    DimensionWeight::Constant(base) => {
+                        DimensionWeight::contextual(*base).with_context(context.to_string(), contextual)
+                    }
+                    DimensionWeight::Contextual { .. } => weight.clone().with_context(context.to_string(), contextual),
+                    DimensionWeight::Attentional { .. } => weight.clone(),
+                }
+            })
+            .collect();
+
+        Ok(ConceptualMetric { dimension_weights, ..metric.clone() })
+    }
+
+    fn check(&self, point: &ConceptualPoint) -> ConceptualResult<()> {
+        if point.coordinates.len() != self.domain.dimensions() {
+            return Err(ConceptualError::InvalidPoint("Point has different dimensions than the domain".to_string()));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ConceptualMetric, DimensionId};
+    use std::collections::HashMap;
+
+    fn point(hue: f64, lightness: f64) -> ConceptualPoint {
+        ConceptualPoint::new(vec![hue, lightness], HashMap::new())
+    }
+
+    fn bounded(prototype: ConceptualPoint, planes: &[(f64, f64, f64)]) -> ConvexRegion {
+        ConvexRegion {
+            boundaries: planes.iter()
+                .map(|&(x, y, offset)| Hyperplane::new(DVector::from_vec(vec![x, y]), offset))
+                .collect(),
+            ..ConvexRegion::from_prototype(prototype)
+        }
+    }
+
+    /// "Red" on a 0–10 redness axis: redness at least 7
+    fn red() -> ConvexRegion {
+        bounded(point(8.5, 5.0), &[(1.0, 0.0, 7.0)]).with_name("Red".to_string())
+    }
+
+    fn colours() -> ContrastClass {
+        ContrastClass::new(ValueRange::new(vec![0.0, 0.0], vec![10.0, 10.0]).unwrap()).on_dimensions(vec![0])
+    }
+
+    #[test]
+    fn test_red_wine_is_red_among_wines() {
+        let wines = [point(2.0, 3.0), point(3.5, 4.0), point(5.0, 2.0)];
+        let reference = ValueRange::from_points(&wines).unwrap();
+        let red_wine = colours().relativize(&red(), &reference).unwrap();
+
+        // Wine redness spans 2..5, so "red" starts at 2 + 0.7 * 3
+        let deep = point(4.5, 3.0);
+        assert!(red_wine.contains(&deep));
+        assert!(!red().contains(&deep));
+        assert!(!red_wine.contains(&point(3.5, 3.0)));
+        assert!((red_wine.boundaries[0].offset / red_wine.boundaries[0].normal[0] - 4.1).abs() < 1e-12);
+
+        // Lightness is outside the contrast and stays put
+        assert_eq!(red_wine.prototype.coordinates[1], 5.0);
+        assert!(colours().to_domain(&deep, &reference).unwrap().coordinates[0] > 7.0);
+    }
+
+    #[test]
+    fn test_region_reference() {
+        let mut space = ConceptualSpace::new(
+            "Colours".to_string(),
+            vec![DimensionId::new(), DimensionId::new()],
+            ConceptualMetric::uniform(2, 2.0),
+        );
+        let box_region = bounded(point(3.5, 3.0), &[(1.0, 0.0, 2.0), (-1.0, 0.0, -5.0), (0.0, 1.0, 1.0), (0.0, -1.0, -4.0)]);
+        let range = ValueRange::from_region(&box_region, &space).unwrap();
+        assert_eq!(range, ValueRange::new(vec![2.0, 1.0], vec![5.0, 4.0]).unwrap());
+
+        // Oblique regions fall back to the points they contain
+        let diagonal = bounded(point(1.0, 1.0), &[(-1.0, -1.0, -3.0)]);
+        assert!(ValueRange::from_region(&diagonal, &space).is_err());
+        space.add_point(point(0.5, 1.0)).unwrap();
+        space.add_point(point(2.0, 0.5)).unwrap();
+        space.add_point(point(6.0, 6.0)).unwrap();
+        let range = ValueRange::from_region(&diagonal, &space).unwrap();
+        assert_eq!(range, ValueRange::new(vec![0.5, 0.5], vec![2.0, 1.0]).unwrap());
+    }
+
+    #[test]
+    fn test_contextual_weights_use_modifier_scale() {
+        let reference = ValueRange::new(vec![2.0, 0.0], vec![5.0, 10.0]).unwrap();
+        let mut metric = ConceptualMetric::uniform(2, 2.0);
+        metric.dimension_weights[1] = DimensionWeight::contextual(1.0).with_context("dessert".to_string(), 0.5);
+        let mut wine = colours().contextualize(&metric, "wine", &reference).unwrap();
+
+        // Without the context nothing changes
+        assert_eq!(wine.distance(&point(2.0, 0.0), &point(5.0, 0.0)).unwrap(), 3.0);
+        wine.current_context = Some("dessert".to_string());
+        assert_eq!(wine.get_weights(), vec![1.0, 0.5]);
+
+        // Among wines the redness span 2..5 is as wide as the whole domain
+        wine.current_context = Some("wine".to_string());
+        assert!((wine.distance(&point(2.0, 0.0), &point(5.0, 0.0)).unwrap() - 10.0).abs() < 1e-9);
+        assert_eq!(wine.get_weights()[1], 1.0);
+    }
+
+    #[test]
+    fn test_mismatched_dimensions_are_rejected() {
+        let reference = ValueRange::new(vec![0.0], vec![1.0]).unwrap();
+        assert!(colours().relativize(&red(), &reference).is_err());
+        assert!(ValueRange::new(vec![1.0], vec![0.0]).is_err());
+    }
+
+    #[test]
+    fn test_zero_width_domain_is_rejected_on_contrast_dimensions() {
+        let flat = ValueRange::new(vec![0.0, 3.0], vec![10.0, 3.0]).unwrap();
+        let reference = ValueRange::new(vec![2.0, 0.0], vec![5.0, 10.0]).unwrap();
+        assert!(ContrastClass::new(flat.clone()).relativize(&red(), &reference).is_err());
+
+        // Outside the contrast the flat dimension is left alone
+        let (scale, _) = ContrastClass::new(flat).on_dimensions(vec![0]).mapping(&reference).unwrap();
+        assert_eq!(scale[1], 1.0);
+    }
+}
@@ -0,0 +1,459 @@
+//! Density-based category formation: DBSCAN and HDBSCAN
+//!
+//! Categories are dense regions of the space separated by sparse ones, so
+//! clusters can take any shape and points in sparse areas are reported as
+//! noise rather than forced into a category. HDBSCAN copes with clusters of
+//! different densities by extracting the most stable clusters from the
+//! whole hierarchy of density levels instead of fixing one radius.
+
+use crate::{
+    CategoryFormation, ConceptualError, ConceptualPoint, ConceptualResult, ConceptualSpace,
+    ConvexRegion,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use uuid::Uuid;
+
+/// A cluster found by density-based clustering
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DensityCluster {
+    /// Member point ids
+    pub members: Vec<Uuid>,
+
+    /// Convex hull of the members with their centroid as prototype
+    pub region: ConvexRegion,
+
+    /// Excess-of-mass stability (HDBSCAN only)
+    pub stability: Option<f64>,
+}
+
+/// Clusters and noise found by density-based clustering
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DensityClustering {
+    /// Clusters in order of discovery
+    pub clusters: Vec<DensityCluster>,
+
+    /// Points in no cluster
+    pub noise: Vec<Uuid>,
+}
+
+impl DensityClustering {
+    /// The regions of all clusters
+    pub fn regions(&self) -> Vec<ConvexRegion> {
+        self.clusters.iter().map(|c| c.region.clone()).collect()
+    }
+
+    /// Index of the cluster a point belongs to, `None` for noise
+    pub fn cluster_of(&self, id: &Uuid) -> Option<usize> {
+        self.clusters.iter().position(|c| c.members.contains(id))
+    }
+}
+
+/// A cluster of the condensed HDBSCAN tree
+#[derive(Debug, Clone)]
+struct CondensedCluster {
+    /// Density level (1 / distance) at which the cluster appears
+    birth: f64,
+    /// Points that drop out of this cluster and the level they leave at
+    fallen: Vec<(usize, f64)>,
+    /// Clusters this one splits into, with their sizes
+    children: Vec<(usize, usize)>,
+}
+
+/// Density level of a merge distance
+fn lambda(distance: f64) -> f64 {
+    1.0 / distance.max(f64::EPSILON)
+}
+
+/// Collect the leaves under a node of the single-linkage tree
+fn leaves(node: usize, n: usize, merges: &[(usize, usize, f64, usize)]) -> Vec<usize> {
+    let mut found = Vec::new();
+    let mut stack = vec![node];
+    while let Some(node) = stack.pop() {
+        if node < n {
+            found.push(node);
+        } else {
+            let (left, right, _, _) = merges[node - n];
+            stack.push(left);
+            stack.push(right);
+        }
+    }
+    found
+}
+
+/// Size of a single-linkage tree node
+fn node_size(node: usize, n: usize, merges: &[(usize, usize, f64, usize)]) -> usize {
+    if node < n { 1 } else { merges[node - n].3 }
+}
+
+/// Cluster the space's points with DBSCAN
+pub(crate) fn dbscan(formation: &CategoryFormation, space: &ConceptualSpace) -> ConceptualResult<DensityClustering> {
+    let (eps, min_points) = density_params(formation);
+    let ids = space.points.ids().to_vec();
+    let row: HashMap<Uuid, usize> = ids.iter().enumerate().map(|(i, id)| (*id, i)).collect();
+
+    let neighbours = |i: usize| -> ConceptualResult<Vec<usize>> {
+        let point = space.points.get(&ids[i]).expect("ids come from the store");
+        Ok(within(formation, space, &point, eps)?
+            .into_iter()
+            .filter_map(|id| row.get(&id).copied())
+            .collect())
+    };
+
+    let mut labels: Vec<Option<usize>> = vec![None; ids.len()];
+    let mut visited = vec![false; ids.len()];
+    let mut cluster_count = 0;
+
+    for start in 0..ids.len() {
+        if visited[start] {
+            continue;
+        }
+        visited[start] = true;
+        let seeds = neighbours(start)?;
+        if seeds.len() < min_points {
+            continue;
+        }
+
+        let cluster = cluster_count;
+        cluster_count += 1;
+        labels[start] = Some(cluster);
+        let mut queue: VecDeque<usize> = seeds.into();
+        while let Some(i) = queue.pop_front() {
+            if labels[i].is_none() {
+                labels[i] = Some(cluster);
+            }
+            if visited[i] {
+                continue;
+            }
+            visited[i] = true;
+            let reach = neighbours(i)?;
+            if reach.len() >= min_points {
+                queue.extend(reach.into_iter().filter(|&j| !visited[j] || labels[j].is_none()));
+            }
+        }
+    }
+
+    let stabilities = vec![None; cluster_count];
+    density_clustering(formation, space, &ids, &labels, &stabilities)
+}
+
+/// Cluster the space's points with HDBSCAN
+pub(crate) fn hdbscan(formation: &CategoryFormation, space: &ConceptualSpace) -> ConceptualResult<DensityClustering> {
+    let (_, min_points) = density_params(formation);
+    let min_cluster_size = min_points.max(2);
+    let ids = space.points.ids().to_vec();
+    let n = ids.len();
+    if n == 0 {
+        return Ok(DensityClustering { clusters: Vec::new(), noise: Vec::new() });
+    }
+
+    let core = ids.iter()
+        .map(|id| {
+            let point = space.points.get(id).expect("ids come from the store");
+            core_distance(formation, space, &point, min_points)
+        })
+        .collect::<ConceptualResult<Vec<f64>>>()?;
+
+    // Prim's algorithm, one row of mutual reachability distances per step
+    let mut in_tree = vec![false; n];
+    let mut best = vec![(f64::INFINITY, 0usize); n];
+    let mut edges = Vec::with_capacity(n.saturating_sub(1));
+    let mut current = 0;
+    in_tree[0] = true;
+    for _ in 1..n {
+        let point = space.points.get(&ids[current]).expect("ids come from the store");
+        let distances = space.points.distances(&point, formation.metric())?;
+        for j in (0..n).filter(|&j| !in_tree[j]) {
+            let d = distances[j].max(core[current]).max(core[j]);
+            if d < best[j].0 {
+                best[j] = (d, current);
+            }
+        }
+        let next = (0..n)
+            .filter(|&j| !in_tree[j])
+            .min_by(|&a, &b| best[a].0.total_cmp(&best[b].0))
+            .expect("a vertex remains outside the tree");
+        edges.push((best[next].1, next, best[next].0));
+        in_tree[next] = true;
+        current = next;
+    }
+    edges.sort_by(|a, b| a.2.total_cmp(&b.2));
+
+    // Single-linkage hierarchy: node n + i is the i-th merge
+    let mut parent: Vec<usize> = (0..n).collect();
+    let mut node_of: Vec<usize> = (0..n).collect();
+    fn find(parent: &mut [usize], mut x: usize) -> usize {
+        while parent[x] != x {
+            parent[x] = parent[parent[x]];
+            x = parent[x];
+        }
+        x
+    }
+    let mut merges: Vec<(usize, usize, f64, usize)> = Vec::with_capacity(n.saturating_sub(1));
+    for (a, b, distance) in edges {
+        let (ra, rb) = (find(&mut parent, a), find(&mut parent, b));
+        let (left, right) = (node_of[ra], node_of[rb]);
+        let size = node_size(left, n, &merges) + node_size(right, n, &merges);
+        merges.push((left, right, distance, size));
+        parent[rb] = ra;
+        node_of[ra] = n + merges.len() - 1;
+    }
+
+    // Condense: walk down from the root, only keeping splits where both
+    // sides are large enough to be clusters
+    let mut condensed = vec![CondensedCluster { birth: 0.0, fallen: Vec::new(), children: Vec::new() }];
+    let mut stack = vec![(2 * n - 2, 0usize)];
+    if n == 1 {
+        condensed[0].fallen.push((0, 0.0));
+        stack.clear();
+    }
+    while let Some((node, cluster)) = stack.pop() {
+        let (left, right, distance, _) = merges[node - n];
+        let level = lambda(distance);
+        let (left_size, right_size) = (node_size(left, n, &merges), node_size(right, n, &merges));
+
+        if left_size >= min_cluster_size && right_size >= min_cluster_size {
+            for (child, size) in [(left, left_size), (right, right_size)] {
+                let id = condensed.len();
+                condensed.push(CondensedCluster { birth: level, fallen: Vec::new(), children: Vec::new() });
+                condensed[cluster].children.push((id, size));
+                stack.push((child, id));
+            }
+            continue;
+        }
+        for (child, size) in [(left, left_size), (right, right_size)] {
+            if size >= min_cluster_size {
+                stack.push((child, cluster));
+            } else {
+                condensed[cluster].fallen.extend(leaves(child, n, &merges).into_iter().map(|p| (p, level)));
+            }
+        }
+    }
+
+    // Excess of mass, chosen bottom-up: children are created after parents
+    let stability: Vec<f64> = condensed.iter()
+        .map(|c| {
+            c.fallen.iter().map(|&(_, level)| level - c.birth).sum::<f64>()
+                + c.children.iter()
+                    .map(|&(child, size)| size as f64 * (condensed[child].birth - c.birth))
+                    .sum::<f64>()
+        })
+        .collect();
+    let mut selected = vec![false; condensed.len()];
+    let mut value = stability.clone();
+    for cluster in (1..condensed.len()).rev() {
+        let below: f64 = condensed[cluster].children.iter().map(|&(child, _)| value[child]).sum();
+        if condensed[cluster].children.is_empty() || stability[cluster] >= below {
+            selected[cluster] = true;
+            let mut descendants: Vec<usize> = condensed[cluster].children.iter().map(|&(c, _)| c).collect();
+            while let Some(d) = descendants.pop() {
+                selected[d] = false;
+                descendants.extend(condensed[d].children.iter().map(|&(c, _)| c));
+            }
+        } else {
+            value[cluster] = below;
+        }
+    }
+
+    let mut labels: Vec<Option<usize>> = vec![None; n];
+    let mut stabilities = Vec::new();
+    for cluster in (1..condensed.len()).filter(|&c| selected[c]) {
+        let label = stabilities.len();
+        stabilities.push(Some(stability[cluster]));
+        let mut subtree = vec![cluster];
+        while let Some(c) = subtree.pop() {
+            for &(p, _) in &condensed[c].fallen {
+                labels[p] = Some(label);
+            }
+            subtree.extend(condensed[c].children.iter().map(|&(child, _)| child));
+        }
+    }
+
+    density_clustering(formation, space, &ids, &labels, &stabilities)
+}
+
+/// Whether the space's index measures distance like the engine
+fn index_agrees(formation: &CategoryFormation, space: &ConceptualSpace) -> bool {
+    formation.metric().is_equivalent(&space.metric.as_distance_metric(), space.points.dimensions())
+}
+
+/// Ids of the points within `radius` of a point under the engine metric
+fn within(formation: &CategoryFormation, space: &ConceptualSpace, point: &ConceptualPoint, radius: f64) -> ConceptualResult<Vec<Uuid>> {
+    if index_agrees(formation, space) {
+        return Ok(space.range_search(point, radius)?.into_iter().map(|(id, _)| *id).collect());
+    }
+    let distances = space.points.distances(point, formation.metric())?;
+    Ok(space.points.ids().iter()
+        .zip(distances.iter())
+        .filter(|(_, d)| **d <= radius)
+        .map(|(id, _)| *id)
+        .collect())
+}
+
+/// Distance to the `k`-th nearest point, the point itself included
+fn core_distance(formation: &CategoryFormation, space: &ConceptualSpace, point: &ConceptualPoint, k: usize) -> ConceptualResult<f64> {
+    if index_agrees(formation, space) {
+        let neighbours = space.k_nearest_neighbors(point, k)?;
+        return Ok(neighbours.last().map_or(0.0, |(_, d)| *d));
+    }
+    let mut distances: Vec<f64> = space.points.distances(point, formation.metric())?.iter().copied().collect();
+    distances.sort_by(f64::total_cmp);
+    Ok(distances.get(k.min(distances.len()).saturating_sub(1)).copied().unwrap_or(0.0))
+}
+
+/// ε and minimum neighbourhood size, validated
+fn density_params(formation: &CategoryFormation) -> (f64, usize) {
+    (formation.max_category_radius(), formation.min_points_per_category().max(1))
+}
+
+/// Turn per-point labels into clusters with hull regions and noise
+fn density_clustering(
+    formation: &CategoryFormation,
+    space: &ConceptualSpace,
+    ids: &[Uuid],
+    labels: &[Option<usize>],
+    stabilities: &[Option<f64>],
+) -> ConceptualResult<DensityClustering> {
+    let mut members = vec![Vec::new(); stabilities.len()];
+    let mut noise = Vec::new();
+    for (id, label) in ids.iter().zip(labels) {
+        match label {
+            Some(cluster) => members[*cluster].push(*id),
+            None => noise.push(*id),
+        }
+    }
+
+    let clusters = members.into_iter()
+        .zip(stabilities)
+        .enumerate()
+        .map(|(index, (members, &stability))| {
+            let points: Vec<ConceptualPoint> = members.iter()
+                .map(|id| space.points.get(id).ok_or_else(|| {
+                    ConceptualError::InvalidPoint(format!("Point {id} is not in the space"))
+                }))
+                .collect::<ConceptualResult<_>>()?;
+            let region = ConvexRegion::hull_around_centroid(&points)?
+                .with_name(format!("Density Category {index}"))
+                .with_description(format!("Convex hull of {} density-connected points", points.len()));
+            Ok(DensityCluster { members, region, stability })
+        })
+        .collect::<ConceptualResult<Vec<_>>>()?;
+
+    Ok(DensityClustering { clusters, noise })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sampling::SplitMix64;
+    use crate::{ConceptualMetric, DimensionId, DistanceMetric};
+
+    /// A tight blob, a loose blob and two isolated outliers
+    fn uneven_space(dimensions: usize) -> (ConceptualSpace, Vec<Uuid>) {
+        let dims: Vec<DimensionId> = (0..dimensions).map(|_| DimensionId::new()).collect();
+        let dimension_map: HashMap<_, _> = dims.iter().enumerate().map(|(i, d)| (*d, i)).collect();
+        let mut space = ConceptualSpace::new(
+            "uneven".to_string(),
+            dims,
+            ConceptualMetric::uniform(dimensions, 2.0),
+        );
+        let mut rng = SplitMix64::new(11);
+        for (centre, spread) in [(0.0, 0.4), (20.0, 4.0)] {
+            for _ in 0..30 {
+                let coordinates = (0..dimensions).map(|_| centre + spread * (rng.next_f64() - 0.5)).collect();
+                space.add_point(ConceptualPoint::new(coordinates, dimension_map.clone())).unwrap();
+            }
+        }
+        let outliers = [-30.0, 60.0].iter()
+            .map(|&c| space.add_point(ConceptualPoint::new(vec![c; dimensions], dimension_map.clone())).unwrap())
+            .collect();
+        (space, outliers)
+    }
+
+    #[test]
+    fn test_dbscan_reports_noise_and_hull_regions() {
+        let (space, outliers) = uneven_space(2);
+        let formation = CategoryFormation::new(DistanceMetric::Euclidean).with_params(4, 1.5);
+        let result = formation.dbscan(&space).unwrap();
+
+        assert_eq!(result.clusters.len(), 2);
+        assert_eq!(result.noise, outliers);
+        for cluster in &result.clusters {
+            assert_eq!(cluster.members.len(), 30);
+            assert!(cluster.stability.is_none());
+            for id in &cluster.members {
+                assert!(cluster.region.contains(&space.points.get(id).unwrap()));
+            }
+        }
+        assert!(result.clusters[0].region.boundaries.iter().all(|b| b.normal.len() == 2));
+    }
+
+    #[test]
+    fn test_dbscan_single_radius_misses_sparse_cluster() {
+        let (space, _) = uneven_space(2);
+        // A radius suited to the tight blob leaves the loose one as noise
+        let formation = CategoryFormation::new(DistanceMetric::Euclidean).with_params(6, 0.3);
+        let result = formation.dbscan(&space).unwrap();
+        assert_eq!(result.clusters.len(), 1);
+        assert_eq!(result.noise.len(), 32);
+    }
+
+    #[test]
+    fn test_hdbscan_handles_varying_density() {
+        let (space, outliers) = uneven_space(3);
+        let formation = CategoryFormation::new(DistanceMetric::Euclidean).with_params(5, 0.3);
+        let result = formation.hdbscan(&space).unwrap();
+
+        assert_eq!(result.clusters.len(), 2);
+        for outlier in &outliers {
+            assert!(result.noise.contains(outlier));
+        }
+        for cluster in &result.clusters {
+            assert!(cluster.stability.unwrap() > 0.0);
+            for id in &cluster.members {
+                assert!(cluster.region.contains(&space.points.get(id).unwrap()));
+            }
+        }
+        let clustered: usize = result.clusters.iter().map(|c| c.members.len()).sum();
+        assert_eq!(clustered + result.noise.len(), space.points.len());
+        assert!(clustered >= 50);
+    }
+
+    #[test]
+    fn test_density_follows_engine_metric() {
+        let dims = [DimensionId::new(), DimensionId::new()];
+        let map: HashMap<_, _> = dims.iter().enumerate().map(|(i, d)| (*d, i)).collect();
+        let mut space = ConceptualSpace::new("diagonal".to_string(), dims.to_vec(), ConceptualMetric::uniform(2, 2.0));
+        for step in 0..4 {
+            let x = step as f64;
+            space.add_point(ConceptualPoint::new(vec![x, x], map.clone())).unwrap();
+        }
+
+        // Diagonal steps are 1.41 apart in the space metric but 2 under Manhattan
+        let euclidean = CategoryFormation::new(DistanceMetric::Euclidean).with_params(2, 1.5);
+        assert_eq!(euclidean.dbscan(&space).unwrap().clusters.len(), 1);
+        let manhattan = CategoryFormation::new(DistanceMetric::Manhattan).with_params(2, 1.5);
+        let result = manhattan.dbscan(&space).unwrap();
+        assert!(result.clusters.is_empty());
+        assert_eq!(result.noise.len(), 4);
+
+        let manhattan = CategoryFormation::new(DistanceMetric::Manhattan).with_params(2, 0.5);
+        let core = manhattan.core_distance(&space, &space.points.values().next().unwrap(), 2).unwrap();
+        assert!((core - 2.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_planar_hull_is_tight() {
+        let dims = [DimensionId::new(), DimensionId::new()];
+        let map: HashMap<_, _> = dims.iter().enumerate().map(|(i, d)| (*d, i)).collect();
+        let square: Vec<ConceptualPoint> = [(0.0, 0.0), (2.0, 0.0), (2.0, 2.0), (0.0, 2.0), (1.0, 1.0)]
+            .iter()
+            .map(|&(x, y)| ConceptualPoint::new(vec![x, y], map.clone()))
+            .collect();
+        let region = ConvexRegion::from_hull(square[4].clone(), &square);
+
+        assert_eq!(region.boundaries.len(), 4);
+        assert!(region.contains(&ConceptualPoint::new(vec![1.9, 0.1], map.clone())));
+        assert!(!region.contains(&ConceptualPoint::new(vec![2.1, 1.0], map)));
+    }
+}
@@ -1,7 +1,8 @@
 //! Quality dimensions and distance metrics
 
 use crate::space::{ConceptualPoint, DimensionId};
-use crate::{ConceptualError, ConceptualResult};
+use crate::{ConceptualError, ConceptualResult, SparsePoint};
+use nalgebra::{DMatrixView, DVector};
 use serde::{Deserialize, Serialize};
 use std::ops::Range;
 
@@ -27,6 +28,8 @@ pub enum DistanceMetric {
     Manhattan,
     /// Weighted Euclidean with dimension weights
     WeightedEuclidean { weights: Vec<f64> },
+    /// Weighted Minkowski distance (the metric of a `ConceptualMetric`)
+    WeightedMinkowski { weights: Vec<f64>, p: f64 },
     /// Cosine similarity (angle between vectors)
     Cosine,
     /// Custom metric with a name
@@ -67,6 +70,9 @@ impl DistanceMetric {
                     .sum();
                 Ok(sum.sqrt())
             }
+            DistanceMetric::WeightedMinkowski { weights, p } => {
+                a.weighted_distance(b, weights, *p)
+            }
             DistanceMetric::Cosine => {
                 let dot_product: f64 = a.coordinates.dot(&b.coordinates);
                 let norm_a = a.coordinates.norm();
@@ -89,6 +95,202 @@ impl DistanceMetric {
             }
         }
     }
+
+    /// Whether two metrics give the same distances between `dimensions`-d points
+    ///
+    /// Euclidean and Manhattan compare as unit-weight Minkowski metrics, so a
+    /// uniform `ConceptualMetric` matches the plain metric of the same order.
+    pub fn is_equivalent(&self, other: &DistanceMetric, dimensions: usize) -> bool {
+        let minkowski = |metric: &DistanceMetric| match metric {
+            DistanceMetric::Euclidean => Some((vec![1.0; dimensions], 2.0)),
+            DistanceMetric::Manhattan => Some((vec![1.0; dimensions], 1.0)),
+            DistanceMetric::WeightedEuclidean { weights } => Some((weights.clone(), 2.0)),
+            DistanceMetric::WeightedMinkowski { weights, p } => Some((weights.clone(), *p)),
+            DistanceMetric::Cosine | DistanceMetric::Custom(_) => None,
+        };
+
+        match (minkowski(self), minkowski(other)) {
+            (Some(a), Some(b)) => a == b,
+            _ => matches!((self, other), (DistanceMetric::Cosine, DistanceMetric::Cosine)),
+        }
+    }
+
+    /// Lower bound on the distance between two points whose coordinates differ
+    /// by `delta` along dimension `dim`
+    ///
+    /// Tree indexes use this to decide whether a splitting plane can be pruned.
+    /// Returns `None` when the metric gives no per-axis bound (e.g. cosine).
+    pub fn axis_lower_bound(&self, dim: usize, delta: f64) -> Option<f64> {
+        let delta = delta.abs();
+        match self {
+            DistanceMetric::Euclidean | DistanceMetric::Manhattan => Some(delta),
+            DistanceMetric::WeightedEuclidean { weights } => {
+                weights.get(dim).map(|w| w.max(0.0).sqrt() * delta)
+            }
+            DistanceMetric::WeightedMinkowski { weights, p } => {
+                weights.get(dim).map(|w| w.max(0.0).powf(1.0 / p) * delta)
+            }
+            DistanceMetric::Cosine | DistanceMetric::Custom(_) => None,
+        }
+    }
+
+    /// Distances from a query to every row of a point matrix
+    ///
+    /// The batched form of `calculate`: `rows` holds one point per row and
+    /// each dimension column is swept once for all points. Entries the
+    /// metric leaves undefined (cosine against a zero row) are `NaN`.
+    pub fn distances_to_rows(&self, query: &DVector<f64>, rows: DMatrixView<'_, f64>) -> ConceptualResult<DVector<f64>> {
+        if query.len() != rows.ncols() {
+            return Err(ConceptualError::InvalidPoint(
+                "Points have different dimensions".to_string()
+            ));
+        }
+
+        let check_weights = |weights: &[f64]| {
+            if weights.len() == query.len() {
+                Ok(())
+            } else {
+                Err(ConceptualError::InvalidDimension(
+                    "Weight vector has incorrect length".to_string()
+                ))
+            }
+        };
+
+        match self {
+            DistanceMetric::Euclidean => {
+                Ok(weighted_power_sums(query, rows, |_| 1.0, |d| d * d).map(f64::sqrt))
+            }
+            DistanceMetric::Manhattan => {
+                Ok(weighted_power_sums(query, rows, |_| 1.0, |d| d))
+            }
+            DistanceMetric::WeightedEuclidean { weights } => {
+                check_weights(weights)?;
+                Ok(weighted_power_sums(query, rows, |j| weights[j], |d| d * d).map(f64::sqrt))
+            }
+            DistanceMetric::WeightedMinkowski { weights, p } => {
+                check_weights(weights)?;
+                Ok(weighted_power_sums(query, rows, |j| weights[j], |d| d.powf(*p))
+                    .map(|sum| sum.powf(1.0 / p)))
+            }
+            DistanceMetric::Cosine => {
+                let norm_query = query.norm();
+                if norm_query == 0.0 {
+                    return Err(ConceptualError::InvalidPoint(
+                        "Cannot calculate cosine distance for zero vector".to_string()
+                    ));
+                }
+
+                let zero = DVector::zeros(query.len());
+                let norms = weighted_power_sums(&zero, rows, |_| 1.0, |d| d * d).map(f64::sqrt);
+                let dots = rows * query;
+                Ok(dots.zip_map(&norms, |dot, norm| {
+                    if norm == 0.0 { f64::NAN } else { 1.0 - dot / (norm_query * norm) }
+                }))
+            }
+            DistanceMetric::Custom(name) => {
+                Err(ConceptualError::InvalidDimension(
+                    format!("Custom metric '{name}' not implemented")
+                ))
+            }
+        }
+    }
+
+    /// Distances from a dense query to each of many sparse rows
+    ///
+    /// Each sum is split into the query's own contribution, computed once,
+    /// plus a correction over the row's non-zero dimensions, so a row costs
+    /// time proportional to its non-zeros. Undefined entries are `NaN`.
+    pub fn distances_to_sparse_rows(&self, query: &DVector<f64>, rows: &[SparsePoint]) -> ConceptualResult<DVector<f64>> {
+        if let Some(row) = rows.iter().find(|row| row.dimensions() != query.len()) {
+            return Err(ConceptualError::InvalidPoint(format!(
+                "Points have different dimensions: {} and {}",
+                query.len(),
+                row.dimensions()
+            )));
+        }
+
+        let check_weights = |weights: &[f64]| {
+            if weights.len() == query.len() {
+                Ok(())
+            } else {
+                Err(ConceptualError::InvalidDimension(
+                    "Weight vector has incorrect length".to_string()
+                ))
+            }
+        };
+
+        match self {
+            DistanceMetric::Euclidean => {
+                Ok(sparse_power_sums(query, rows, |_| 1.0, |d| d * d).map(f64::sqrt))
+            }
+            DistanceMetric::Manhattan => {
+                Ok(sparse_power_sums(query, rows, |_| 1.0, |d| d))
+            }
+            DistanceMetric::WeightedEuclidean { weights } => {
+                check_weights(weights)?;
+                Ok(sparse_power_sums(query, rows, |j| weights[j], |d| d * d).map(f64::sqrt))
+            }
+            DistanceMetric::WeightedMinkowski { weights, p } => {
+                check_weights(weights)?;
+                Ok(sparse_power_sums(query, rows, |j| weights[j], |d| d.powf(*p))
+                    .map(|sum| sum.powf(1.0 / p)))
+            }
+            DistanceMetric::Cosine => {
+                let norm_query = query.norm();
+                if norm_query == 0.0 {
+                    return Err(ConceptualError::InvalidPoint(
+                        "Cannot calculate cosine distance for zero vector".to_string()
+                    ));
+                }
+
+                Ok(DVector::from_iterator(rows.len(), rows.iter().map(|row| {
+                    let norm = row.norm();
+                    if norm == 0.0 { f64::NAN } else { 1.0 - row.dot_dense(query) / (norm_query * norm) }
+                })))
+            }
+            DistanceMetric::Custom(name) => {
+                Err(ConceptualError::InvalidDimension(
+                    format!("Custom metric '{name}' not implemented")
+                ))
+            }
+        }
+    }
+}
+
+/// Per-row sums of `weight(j) * power(|x_j - q_j|)` over sparse rows
+fn sparse_power_sums(
+    query: &DVector<f64>,
+    rows: &[SparsePoint],
+    weight: impl Fn(usize) -> f64,
+    power: impl Fn(f64) -> f64,
+) -> DVector<f64> {
+    // Contribution of the query against an all-zero row
+    let base: f64 = query.iter().enumerate().map(|(j, q)| weight(j) * power(q.abs())).sum();
+
+    DVector::from_iterator(rows.len(), rows.iter().map(|row| {
+        let correction: f64 = row.entries()
+            .map(|(j, x)| weight(j) * (power((query[j] - x).abs()) - power(query[j].abs())))
+            .sum();
+        // Cancellation can leave a tiny negative sum for near-identical points
+        (base + correction).max(0.0)
+    }))
+}
+
+/// Per-row sums of `weight(j) * power(|x_j - q_j|)`, one column at a time
+fn weighted_power_sums(
+    query: &DVector<f64>,
+    rows: DMatrixView<'_, f64>,
+    weight: impl Fn(usize) -> f64,
+    power: impl Fn(f64) -> f64,
+) -> DVector<f64> {
+    let mut sums = DVector::zeros(rows.nrows());
+    for (j, column) in rows.column_iter().enumerate() {
+        let (q, w) = (query[j], weight(j));
+        for (sum, x) in sums.iter_mut().zip(column.iter()) {
+            *sum += w * power((q - x).abs());
+        }
+    }
+    sums
 }
 
 /// A quality dimension in conceptual space
@@ -0,0 +1,74 @@
+//! Events for incrementally learned categories
+
+use crate::{ConceptualPoint, ConceptualSpaceId, ConvexRegion};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Event emitted when a learned category comes into being
+///
+/// A split produces two of these derived from the split category; a merge
+/// produces one derived from both merged categories.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CategoryFormed {
+    /// The space the category belongs to
+    pub space_id: ConceptualSpaceId,
+
+    /// The category's region at formation
+    pub region: ConvexRegion,
+
+    /// Categories this one was split from or merged out of
+    pub derived_from: Vec<Uuid>,
+
+    /// Why the category was formed
+    pub reason: String,
+}
+
+/// Event emitted when a learned category ceases to exist
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CategoryRemoved {
+    /// The space the category belonged to
+    pub space_id: ConceptualSpaceId,
+
+    /// Region id of the removed category
+    pub region_id: Uuid,
+
+    /// Why the category was removed
+    pub reason: String,
+}
+
+/// Event emitted when a concept is assigned to a learned category
+///
+/// Boundaries of learned categories are the Voronoi cells of their
+/// prototypes, so the new prototype is all a read model needs.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConceptCategorized {
+    /// The space the concept belongs to
+    pub space_id: ConceptualSpaceId,
+
+    /// The categorized concept
+    pub concept_id: Uuid,
+
+    /// Region id of the category it joined
+    pub region_id: Uuid,
+
+    /// The category's prototype after the update
+    pub prototype: ConceptualPoint,
+}
+
+/// Event emitted when a re-observed concept leaves its learned category
+///
+/// The category keeps its other members, so only its prototype moves.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConceptUncategorized {
+    /// The space the concept belongs to
+    pub space_id: ConceptualSpaceId,
+
+    /// The concept that left
+    pub concept_id: Uuid,
+
+    /// Region id of the category it left
+    pub region_id: Uuid,
+
+    /// The category's prototype without the concept
+    pub prototype: ConceptualPoint,
+}
@@ -4,11 +4,13 @@ mod space_created;
 mod concept_added;
 mod region_added;
 mod weights_updated;
+mod category_learning;
 
 pub use space_created::*;
 pub use concept_added::*;
 pub use region_added::*;
 pub use weights_updated::*;
+pub use category_learning::*;
 
 use cim_domain::DomainEvent;
 use crate::ConceptualSpaceId;
@@ -29,6 +31,10 @@ pub enum ConceptualSpaceDomainEvent {
     RegionAdded(RegionAdded),
     WeightsRemoved(DimensionWeightsRemoved),
     WeightsAdded(DimensionWeightsAdded),
+    CategoryFormed(CategoryFormed),
+    CategoryRemoved(CategoryRemoved),
+    ConceptCategorized(ConceptCategorized),
+    ConceptUncategorized(ConceptUncategorized),
 }
 
 impl DomainEvent for ConceptualSpaceDomainEvent {
@@ -39,6 +45,10 @@ impl DomainEvent for ConceptualSpaceDomainEvent {
             Self::RegionAdded(_) => "RegionAdded",
             Self::WeightsRemoved(_) => "DimensionWeightsRemoved",
             Self::WeightsAdded(_) => "DimensionWeightsAdded",
+            Self::CategoryFormed(_) => "CategoryFormed",
+            Self::CategoryRemoved(_) => "CategoryRemoved",
+            Self::ConceptCategorized(_) => "ConceptCategorized",
+            Self::ConceptUncategorized(_) => "ConceptUncategorized",
         }
     }
 
@@ -49,6 +59,10 @@ impl DomainEvent for ConceptualSpaceDomainEvent {
             Self::RegionAdded(e) => e.space_id.0,
             Self::WeightsRemoved(e) => e.space_id.0,
             Self::WeightsAdded(e) => e.space_id.0,
+            Self::CategoryFormed(e) => e.space_id.0,
+            Self::CategoryRemoved(e) => e.space_id.0,
+            Self::ConceptCategorized(e) => e.space_id.0,
+            Self::ConceptUncategorized(e) => e.space_id.0,
         }
     }
 
@@ -250,6 +264,11 @@ mod tests {
                 weights: vec![],
                 reason: "Test".to_string(),
             }),
+            ConceptualSpaceDomainEvent::CategoryRemoved(CategoryRemoved {
+                space_id,
+                region_id: Uuid::new_v4(),
+                reason: "Test".to_string(),
+            }),
         ];
 
         // All events should return the same aggregate ID
@@ -0,0 +1,327 @@
+//! Explanations of distances and similarities
+//!
+//! Breaks a distance down into the weighted term each dimension adds to the
+//! metric's sum, so an analyst can see which qualities drive a surprising
+//! similarity. Names and domains come from the dimension registry.
+
+use crate::{
+    ConceptualError, ConceptualMetric, ConceptualPoint, ConceptualResult, DimensionId,
+    DimensionRegistry, DistanceMetric, SimilarityEngine,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// One dimension's part in a distance
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DimensionContribution {
+    /// Coordinate index of the dimension
+    pub index: usize,
+
+    /// Dimension id, when the points carry a dimension map
+    pub dimension_id: Option<DimensionId>,
+
+    /// Registry name of the dimension
+    pub name: Option<String>,
+
+    /// Registry context (domain) the dimension belongs to
+    pub domain: Option<String>,
+
+    /// Coordinate difference `a - b`
+    pub difference: f64,
+
+    /// Weight applied to this dimension
+    pub weight: f64,
+
+    /// The dimension's term in the metric's sum
+    pub term: f64,
+
+    /// Fraction of the sum contributed by this dimension
+    pub share: f64,
+}
+
+/// A domain's part in a distance, summed over its dimensions
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DomainContribution {
+    /// Registry context naming the domain
+    pub domain: String,
+
+    /// Coordinate indexes of the domain's dimensions
+    pub dimensions: Vec<usize>,
+
+    /// Summed terms of the domain's dimensions
+    pub term: f64,
+
+    /// Fraction of the sum contributed by the domain
+    pub share: f64,
+}
+
+/// A distance broken down by dimension and domain
+///
+/// The distance is `total^(1/p)` where `total` sums every term; shares
+/// are fractions of `total`, so for Euclidean metrics they split the
+/// squared distance.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DistanceExplanation {
+    /// The explained distance
+    pub distance: f64,
+
+    /// Similarity derived from the distance, when explained by an engine
+    pub similarity: Option<f64>,
+
+    /// Exponent turning the summed terms into the distance
+    pub p: f64,
+
+    /// Sum of all dimension terms
+    pub total: f64,
+
+    /// Context whose weights were active
+    pub context: Option<String>,
+
+    /// Per-dimension contributions in coordinate order
+    pub contributions: Vec<DimensionContribution>,
+
+    /// Per-domain contributions, largest first
+    pub domains: Vec<DomainContribution>,
+}
+
+impl DistanceExplanation {
+    fn new(
+        a: &ConceptualPoint,
+        b: &ConceptualPoint,
+        weights: &[f64],
+        terms: Vec<f64>,
+        p: f64,
+        context: Option<String>,
+    ) -> Self {
+        let ids: HashMap<usize, DimensionId> = a.dimension_map.iter().map(|(id, i)| (*i, *id)).collect();
+        let total: f64 = terms.iter().sum();
+        let contributions = terms.into_iter()
+            .enumerate()
+            .map(|(index, term)| DimensionContribution {
+                index,
+                dimension_id: ids.get(&index).copied(),
+                name: None,
+                domain: None,
+                difference: a.coordinates[index] - b.coordinates[index],
+                weight: weights[index],
+                term,
+                share: if total > 0.0 { term / total } else { 0.0 },
+            })
+            .collect();
+
+        Self {
+            distance: total.powf(1.0 / p),
+            similarity: None,
+            p,
+            total,
+            context,
+            contributions,
+            domains: Vec::new(),
+        }
+    }
+
+    /// Fill in dimension names and domains from a registry
+    ///
+    /// Only dimensions whose ids the points carried can be named.
+    pub fn with_registry(mut self, registry: &DimensionRegistry) -> Self {
+        let mut domains: HashMap<String, DomainContribution> = HashMap::new();
+
+        for contribution in &mut self.contributions {
+            let Some(dimension) = contribution.dimension_id.and_then(|id| registry.get(&id)) else {
+                continue;
+            };
+            contribution.name = Some(dimension.name.clone());
+            contribution.domain = dimension.context.clone();
+
+            if let Some(domain) = &dimension.context {
+                let entry = domains.entry(domain.clone()).or_insert_with(|| DomainContribution {
+                    domain: domain.clone(),
+                    dimensions: Vec::new(),
+                    term: 0.0,
+                    share: 0.0,
+                });
+                entry.dimensions.push(contribution.index);
+                entry.term += contribution.term;
+                entry.share += contribution.share;
+            }
+        }
+
+        self.domains = domains.into_values().collect();
+        self.domains.sort_by(|a, b| {
+            b.term.partial_cmp(&a.term).unwrap_or(std::cmp::Ordering::Equal).then_with(|| a.domain.cmp(&b.domain))
+        });
+        self
+    }
+
+    /// The `n` dimensions contributing most, largest first
+    pub fn top_contributors(&self, n: usize) -> Vec<&DimensionContribution> {
+        let mut ranked: Vec<_> = self.contributions.iter().collect();
+        ranked.sort_by(|a, b| {
+            b.term.partial_cmp(&a.term).unwrap_or(std::cmp::Ordering::Equal).then(a.index.cmp(&b.index))
+        });
+        ranked.truncate(n);
+        ranked
+    }
+}
+
+impl DistanceMetric {
+    /// Break the distance between two points down by dimension
+    ///
+    /// Cosine distance is explained through unit vectors, where
+    /// `1 - cos = ½·Σ(âᵢ - b̂ᵢ)²`.
+    pub fn explain(&self, a: &ConceptualPoint, b: &ConceptualPoint) -> ConceptualResult<DistanceExplanation> {
+        let n = a.coordinates.len();
+        if b.coordinates.len() != n {
+            return Err(ConceptualError::InvalidPoint(
+                "Points have different dimensions".to_string()
+            ));
+        }
+
+        let (weights, p) = match self {
+            DistanceMetric::Euclidean => (vec![1.0; n], 2.0),
+            DistanceMetric::Manhattan => (vec![1.0; n], 1.0),
+            DistanceMetric::WeightedEuclidean { weights } => (weights.clone(), 2.0),
+            DistanceMetric::WeightedMinkowski { weights, p } => (weights.clone(), *p),
+            DistanceMetric::Cosine => {
+                let (norm_a, norm_b) = (a.coordinates.norm(), b.coordinates.norm());
+                if norm_a == 0.0 || norm_b == 0.0 {
+                    return Err(ConceptualError::InvalidPoint(
+                        "Cannot calculate cosine distance for zero vector".to_string()
+                    ));
+                }
+                let terms = a.coordinates.iter()
+                    .zip(b.coordinates.iter())
+                    .map(|(x, y)| 0.5 * (x / norm_a - y / norm_b).powi(2))
+                    .collect();
+                return Ok(DistanceExplanation::new(a, b, &vec![1.0; n], terms, 1.0, None));
+            }
+            DistanceMetric::Custom(name) => {
+                return Err(ConceptualError::InvalidDimension(
+                    format!("Custom metric '{name}' not implemented")
+                ));
+            }
+        };
+
+        if weights.len() != n {
+            return Err(ConceptualError::InvalidDimension(
+                "Weight vector has incorrect length".to_string()
+            ));
+        }
+
+        let terms = a.coordinates.iter()
+            .zip(b.coordinates.iter())
+            .zip(weights.iter())
+            .map(|((x, y), w)| w * (x - y).abs().powf(p))
+            .collect();
+        Ok(DistanceExplanation::new(a, b, &weights, terms, p, None))
+    }
+}
+
+impl ConceptualMetric {
+    /// Break the distance between two points down by dimension
+    ///
+    /// Weights are evaluated in `current_context`.
+    ///
+    /// ```mermaid
+    /// graph LR
+    ///     W[Context Weights] --> T[Per-Dimension Terms]
+    ///     A[Point A] --> T
+    ///     B[Point B] --> T
+    ///     T --> S[Shares of Total]
+    ///     S --> R[Registry Names and Domains]
+    /// ```
+    pub fn explain_distance(&self, a: &ConceptualPoint, b: &ConceptualPoint) -> ConceptualResult<DistanceExplanation> {
+        let mut explanation = self.as_distance_metric().explain(a, b)?;
+        explanation.context = self.current_context.clone();
+        Ok(explanation)
+    }
+}
+
+impl SimilarityEngine {
+    /// Break the distance behind a contextual similarity down by dimension
+    ///
+    /// Uses the same weights as `contextual_similarity` and records the
+    /// resulting similarity.
+    pub fn explain_distance(
+        &self,
+        a: &ConceptualPoint,
+        b: &ConceptualPoint,
+        context: Option<&str>,
+    ) -> ConceptualResult<DistanceExplanation> {
+        let (mut explanation, context) = match context.and_then(|c| self.context_weights.get(c).map(|w| (c, w))) {
+            Some((context, weights)) => {
+                let metric = DistanceMetric::WeightedMinkowski { weights: weights.clone(), p: 2.0 };
+                (metric.explain(a, b)?, Some(context.to_string()))
+            }
+            None => (self.base_metric.explain(a, b)?, None),
+        };
+
+        explanation.context = context;
+        explanation.similarity = Some(self.similarity_function.similarity(explanation.distance));
+        Ok(explanation)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dimensions::QualityDimension;
+    use crate::DimensionWeight;
+
+    #[test]
+    fn test_explanation_names_and_ranks_dimensions() {
+        let mut registry = DimensionRegistry::new();
+        let mut names = Vec::new();
+        for (name, domain) in [("hue", "color"), ("brightness", "color"), ("weight", "mass")] {
+            let mut dimension = QualityDimension::continuous(name.to_string(), 0.0, 10.0);
+            dimension.context = Some(domain.to_string());
+            names.push(dimension.id);
+            registry.register(dimension).unwrap();
+        }
+        let dimension_map: HashMap<DimensionId, usize> = names.iter().enumerate().map(|(i, id)| (*id, i)).collect();
+        let a = ConceptualPoint::new(vec![0.0, 0.0, 0.0], dimension_map.clone());
+        let b = ConceptualPoint::new(vec![1.0, 2.0, 2.0], dimension_map);
+
+        let mut metric = ConceptualMetric::uniform(3, 2.0);
+        metric.dimension_weights[2] = DimensionWeight::contextual(1.0).with_context("shipping".to_string(), 4.0);
+        metric.current_context = Some("shipping".to_string());
+
+        let explanation = metric.explain_distance(&a, &b).unwrap().with_registry(&registry);
+        assert!((explanation.distance - metric.distance(&a, &b).unwrap()).abs() < 1e-12);
+        assert_eq!(explanation.context.as_deref(), Some("shipping"));
+
+        let top = explanation.top_contributors(2);
+        assert_eq!(top[0].name.as_deref(), Some("weight"));
+        assert_eq!(top[0].weight, 4.0);
+        assert_eq!(top[0].share, 16.0 / 21.0);
+        assert_eq!(top[1].name.as_deref(), Some("brightness"));
+
+        assert_eq!(explanation.domains[0].domain, "mass");
+        assert_eq!(explanation.domains[1].dimensions, vec![0, 1]);
+        assert_eq!(explanation.domains[1].term, 5.0);
+
+        let json = serde_json::to_string(&explanation).unwrap();
+        let restored: DistanceExplanation = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.top_contributors(1)[0].name, top[0].name);
+        assert_eq!(restored.domains.len(), 2);
+    }
+
+    #[test]
+    fn test_engine_explanations_match_similarities() {
+        let a = ConceptualPoint::new(vec![1.0, 0.0], HashMap::new());
+        let b = ConceptualPoint::new(vec![1.0, 1.0], HashMap::new());
+
+        let mut engine = SimilarityEngine::new(DistanceMetric::Cosine);
+        engine.add_context_weights("x-only".to_string(), vec![1.0, 0.0]);
+
+        let cosine = engine.explain_distance(&a, &b, None).unwrap();
+        assert!((cosine.distance - DistanceMetric::Cosine.calculate(&a, &b).unwrap()).abs() < 1e-12);
+        assert_eq!(cosine.similarity, Some(engine.basic_similarity(&a, &b).unwrap()));
+
+        let contextual = engine.explain_distance(&a, &b, Some("x-only")).unwrap();
+        assert_eq!(contextual.distance, 0.0);
+        assert_eq!(contextual.similarity, Some(engine.contextual_similarity(&a, &b, Some("x-only")).unwrap()));
+        assert_eq!(contextual.context.as_deref(), Some("x-only"));
+        assert!(DistanceMetric::Custom("edit".to_string()).explain(&a, &b).is_err());
+    }
+}
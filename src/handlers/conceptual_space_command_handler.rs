@@ -2,7 +2,7 @@
 
 use cim_domain::{CommandHandler, CommandEnvelope, CommandAcknowledgment, CommandStatus};
 use crate::commands::{
-    CreateConceptualSpace, AddConcept, AddRegion, ReplaceDimensionWeights,
+    CreateConceptualSpace, AddConcept, AddRegion, RemoveRegion, ReplaceDimensionWeights,
 };
 use crate::aggregate::ConceptualSpaceAggregate;
 use crate::ConceptualSpaceId;
@@ -146,6 +146,39 @@ impl CommandHandler<AddRegion> for ConceptualSpaceCommandHandler {
     }
 }
 
+impl CommandHandler<RemoveRegion> for ConceptualSpaceCommandHandler {
+    fn handle(&mut self, envelope: CommandEnvelope<RemoveRegion>) -> CommandAcknowledgment {
+        let command = envelope.command;
+        let command_id = envelope.id;
+
+        // Load aggregate
+        let aggregate = match self.aggregates.get_mut(&command.space_id) {
+            Some(agg) => agg,
+            None => return CommandAcknowledgment {
+                command_id,
+                correlation_id: envelope.identity.correlation_id,
+                status: CommandStatus::Rejected,
+                reason: Some("Conceptual space not found".to_string()),
+            }
+        };
+
+        match aggregate.remove_region(&command.region_id) {
+            Ok(_) => CommandAcknowledgment {
+                command_id,
+                correlation_id: envelope.identity.correlation_id,
+                status: CommandStatus::Accepted,
+                reason: None,
+            },
+            Err(e) => CommandAcknowledgment {
+                command_id,
+                correlation_id: envelope.identity.correlation_id,
+                status: CommandStatus::Rejected,
+                reason: Some(e.to_string()),
+            }
+        }
+    }
+}
+
 impl CommandHandler<ReplaceDimensionWeights> for ConceptualSpaceCommandHandler {
     fn handle(&mut self, envelope: CommandEnvelope<ReplaceDimensionWeights>) -> CommandAcknowledgment {
         let command = envelope.command;
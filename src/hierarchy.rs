@@ -0,0 +1,361 @@
+//! Hierarchical category formation and taxonomies
+//!
+//! Natural categories nest: vehicle ⊃ car ⊃ sports car. Agglomerative
+//! clustering merges the closest pair of clusters until one remains,
+//! recording a dendrogram. Cutting the dendrogram at several levels gives a
+//! taxonomy of nested convex regions, which can be exported as hierarchy
+//! morphisms.
+
+use crate::morphisms::MorphismCollection;
+use crate::partitioning::{distance_matrix, point_rows};
+use crate::{
+    CategoryFormation, ConceptId, ConceptualError, ConceptualPoint, ConceptualResult,
+    ConceptualSpace, ContextId, ConvexRegion, CrossContextMorphism, MorphismType,
+};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// How the distance between two clusters is measured
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Linkage {
+    /// Closest pair of members
+    Single,
+
+    /// Farthest pair of members
+    Complete,
+
+    /// Mean distance over all member pairs (UPGMA)
+    Average,
+
+    /// Increase in within-cluster variance; meaningful for Euclidean metrics
+    Ward,
+}
+
+impl Linkage {
+    /// Lance–Williams update: distance from the union of i and j to k
+    fn update(self, d_ik: f64, d_jk: f64, d_ij: f64, n_i: usize, n_j: usize, n_k: usize) -> f64 {
+        let (n_i, n_j, n_k) = (n_i as f64, n_j as f64, n_k as f64);
+        match self {
+            Linkage::Single => d_ik.min(d_jk),
+            Linkage::Complete => d_ik.max(d_jk),
+            Linkage::Average => (n_i * d_ik + n_j * d_jk) / (n_i + n_j),
+            Linkage::Ward => {
+                let squared = ((n_i + n_k) * d_ik * d_ik + (n_j + n_k) * d_jk * d_jk - n_k * d_ij * d_ij)
+                    / (n_i + n_j + n_k);
+                squared.max(0.0).sqrt()
+            }
+        }
+    }
+}
+
+/// One merge of a dendrogram
+///
+/// Nodes below the number of points are leaves; node `n + i` is the
+/// cluster created by merge `i`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct DendrogramMerge {
+    /// First merged node
+    pub left: usize,
+
+    /// Second merged node
+    pub right: usize,
+
+    /// Linkage distance at which they merge
+    pub height: f64,
+
+    /// Points in the merged cluster
+    pub size: usize,
+}
+
+/// Full merge history of agglomerative clustering
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Dendrogram {
+    /// Point ids, indexed by leaf node
+    pub ids: Vec<Uuid>,
+
+    /// Merges in order of increasing height
+    pub merges: Vec<DendrogramMerge>,
+
+    /// Linkage the dendrogram was built with
+    pub linkage: Linkage,
+}
+
+impl Dendrogram {
+    /// Clusters after applying the first `count` merges, ordered by first leaf
+    fn clusters_after(&self, count: usize) -> Vec<Vec<Uuid>> {
+        let n = self.ids.len();
+        let mut parent: Vec<usize> = (0..n).collect();
+        fn find(parent: &mut [usize], mut x: usize) -> usize {
+            while parent[x] != x {
+                parent[x] = parent[parent[x]];
+                x = parent[x];
+            }
+            x
+        }
+
+        // Any leaf of a node identifies its union-find set
+        let mut representative: Vec<usize> = (0..n).collect();
+        for merge in &self.merges[..count.min(self.merges.len())] {
+            let (a, b) = (representative[merge.left], representative[merge.right]);
+            let (ra, rb) = (find(&mut parent, a), find(&mut parent, b));
+            parent[rb] = ra;
+            representative.push(ra);
+        }
+
+        let mut clusters: Vec<Vec<Uuid>> = Vec::new();
+        let mut slot = vec![usize::MAX; n];
+        for leaf in 0..n {
+            let root = find(&mut parent, leaf);
+            if slot[root] == usize::MAX {
+                slot[root] = clusters.len();
+                clusters.push(Vec::new());
+            }
+            clusters[slot[root]].push(self.ids[leaf]);
+        }
+        clusters
+    }
+
+    /// Clusters formed by every merge at or below `height`
+    pub fn cut_at(&self, height: f64) -> Vec<Vec<Uuid>> {
+        self.clusters_after(self.merges.iter().take_while(|m| m.height <= height).count())
+    }
+
+    /// Exactly `k` clusters (clamped to the number of points)
+    pub fn cut_into(&self, k: usize) -> Vec<Vec<Uuid>> {
+        let n = self.ids.len();
+        self.clusters_after(n.saturating_sub(k.clamp(1, n.max(1))))
+    }
+
+    /// Nested regions from cuts into each of the given cluster counts
+    ///
+    /// Counts are visited from coarsest to finest; a cluster that survives a
+    /// finer cut unchanged stays a single node instead of nesting in itself.
+    /// Regions are member convex hulls, so children lie inside their parent.
+    pub fn taxonomy(&self, space: &ConceptualSpace, counts: &[usize]) -> ConceptualResult<Taxonomy> {
+        let mut counts: Vec<usize> = counts.iter().map(|&k| k.clamp(1, self.ids.len().max(1))).collect();
+        counts.sort_unstable();
+        counts.dedup();
+
+        let mut nodes: Vec<TaxonomyNode> = Vec::new();
+        // Node index owning each point at the previous level
+        let mut owner: Vec<Option<usize>> = vec![None; self.ids.len()];
+        let leaf_of = |id: &Uuid| self.ids.iter().position(|other| other == id).expect("ids come from the dendrogram");
+
+        for (depth, &k) in counts.iter().enumerate() {
+            let mut next_owner = owner.clone();
+            for members in self.cut_into(k) {
+                let parent = owner[leaf_of(&members[0])];
+                if let Some(parent) = parent {
+                    if nodes[parent].members.len() == members.len() {
+                        continue;
+                    }
+                }
+
+                let region = hull_region(space, &members)?
+                    .with_name(format!("Level {depth} Category {}", nodes.len()))
+                    .with_description(format!("{:?}-linkage cluster of {} points", self.linkage, members.len()));
+                let index = nodes.len();
+                for id in &members {
+                    next_owner[leaf_of(id)] = Some(index);
+                }
+                if let Some(parent) = parent {
+                    nodes[parent].children.push(region.id);
+                }
+                nodes.push(TaxonomyNode {
+                    parent: parent.map(|p| nodes[p].region.id),
+                    children: Vec::new(),
+                    depth,
+                    members,
+                    region,
+                });
+            }
+            owner = next_owner;
+        }
+
+        Ok(Taxonomy { nodes })
+    }
+}
+
+/// A category in a taxonomy
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaxonomyNode {
+    /// The category's region
+    pub region: ConvexRegion,
+
+    /// Region id of the enclosing category
+    pub parent: Option<Uuid>,
+
+    /// Region ids of the subcategories
+    pub children: Vec<Uuid>,
+
+    /// Cut level the category first appears at, 0 being the coarsest
+    pub depth: usize,
+
+    /// Member point ids
+    pub members: Vec<Uuid>,
+}
+
+/// Categories nested by parent/child links
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Taxonomy {
+    /// All categories, parents before their children
+    pub nodes: Vec<TaxonomyNode>,
+}
+
+impl Taxonomy {
+    /// Categories without a parent
+    pub fn roots(&self) -> Vec<&TaxonomyNode> {
+        self.nodes.iter().filter(|n| n.parent.is_none()).collect()
+    }
+
+    /// Category by region id
+    pub fn get(&self, id: &Uuid) -> Option<&TaxonomyNode> {
+        self.nodes.iter().find(|n| n.region.id == *id)
+    }
+
+    /// Direct subcategories of a category
+    pub fn children(&self, id: &Uuid) -> Vec<&TaxonomyNode> {
+        self.get(id)
+            .map(|node| node.children.iter().filter_map(|c| self.get(c)).collect())
+            .unwrap_or_default()
+    }
+
+    /// All regions, parents before their children
+    pub fn regions(&self) -> Vec<ConvexRegion> {
+        self.nodes.iter().map(|n| n.region.clone()).collect()
+    }
+
+    /// Parent → child `Hierarchy` morphisms between region concepts
+    ///
+    /// Strength is the share of the parent's members that fall in the child.
+    pub fn to_morphisms(&self, context: ContextId) -> MorphismCollection {
+        let mut collection = MorphismCollection::new();
+        for node in &self.nodes {
+            let Some(parent) = node.parent.and_then(|p| self.get(&p)) else {
+                continue;
+            };
+            collection.add(CrossContextMorphism::new(
+                (context, ConceptId::from_uuid(parent.region.id)),
+                (context, ConceptId::from_uuid(node.region.id)),
+                MorphismType::Hierarchy {
+                    parent_role: "category".to_string(),
+                    child_role: "subcategory".to_string(),
+                },
+                node.members.len() as f64 / parent.members.len() as f64,
+            ));
+        }
+        collection
+    }
+}
+
+/// Convex hull of some points with their centroid as prototype
+fn hull_region(space: &ConceptualSpace, members: &[Uuid]) -> ConceptualResult<ConvexRegion> {
+    let points: Vec<ConceptualPoint> = members.iter()
+        .map(|id| space.points.get(id).ok_or_else(|| {
+            ConceptualError::InvalidPoint(format!("Point {id} is not in the space"))
+        }))
+        .collect::<ConceptualResult<_>>()?;
+    ConvexRegion::hull_around_centroid(&points)
+}
+
+/// Agglomerative clustering of the space's points under the engine's metric
+pub(crate) fn agglomerate(formation: &CategoryFormation, space: &ConceptualSpace, linkage: Linkage) -> ConceptualResult<Dendrogram> {
+    let (ids, rows) = point_rows(space);
+    let n = ids.len();
+    let mut distances = distance_matrix(formation.metric(), &rows)?;
+
+    let mut active: Vec<usize> = (0..n).collect();
+    let mut node = (0..n).collect::<Vec<usize>>();
+    let mut size = vec![1usize; n];
+    let mut merges = Vec::with_capacity(n.saturating_sub(1));
+
+    while active.len() > 1 {
+        let mut closest = (0, 1, f64::INFINITY);
+        for (a, &i) in active.iter().enumerate() {
+            for &j in &active[a + 1..] {
+                if distances[(i, j)] < closest.2 {
+                    closest = (i, j, distances[(i, j)]);
+                }
+            }
+        }
+        let (i, j, height) = closest;
+
+        for &k in active.iter().filter(|&&k| k != i && k != j) {
+            let d = linkage.update(distances[(i, k)], distances[(j, k)], height, size[i], size[j], size[k]);
+            distances[(i, k)] = d;
+            distances[(k, i)] = d;
+        }
+
+        merges.push(DendrogramMerge { left: node[i], right: node[j], height, size: size[i] + size[j] });
+        node[i] = n + merges.len() - 1;
+        size[i] += size[j];
+        active.retain(|&k| k != j);
+    }
+
+    Ok(Dendrogram { ids, merges, linkage })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ConceptualMetric, DimensionId, DistanceMetric};
+    use std::collections::HashMap;
+
+    /// Four tight groups of five: two near the origin, two far away
+    fn nested_space() -> ConceptualSpace {
+        let dims = vec![DimensionId::new(), DimensionId::new()];
+        let map: HashMap<_, _> = dims.iter().enumerate().map(|(i, d)| (*d, i)).collect();
+        let mut space = ConceptualSpace::new("nested".to_string(), dims, ConceptualMetric::uniform(2, 2.0));
+        for (cx, cy) in [(0.0, 0.0), (3.0, 0.0), (20.0, 0.0), (23.0, 0.0)] {
+            for (dx, dy) in [(0.0, 0.0), (0.2, 0.0), (0.0, 0.2), (0.2, 0.2), (0.1, 0.1)] {
+                space.add_point(ConceptualPoint::new(vec![cx + dx, cy + dy], map.clone())).unwrap();
+            }
+        }
+        space
+    }
+
+    #[test]
+    fn test_linkages_give_monotone_dendrograms() {
+        let space = nested_space();
+        let formation = CategoryFormation::new(DistanceMetric::Euclidean);
+        for linkage in [Linkage::Single, Linkage::Complete, Linkage::Average, Linkage::Ward] {
+            let dendrogram = formation.agglomerate(&space, linkage).unwrap();
+            assert_eq!(dendrogram.merges.len(), 19);
+            assert!(dendrogram.merges.windows(2).all(|w| w[0].height <= w[1].height + 1e-12));
+            assert_eq!(dendrogram.merges.last().unwrap().size, 20);
+
+            let groups = dendrogram.cut_into(4);
+            assert!(groups.iter().all(|g| g.len() == 5));
+            assert_eq!(dendrogram.cut_at(10.0).len(), 2);
+        }
+    }
+
+    #[test]
+    fn test_taxonomy_nests_regions_and_exports_morphisms() {
+        let space = nested_space();
+        let dendrogram = CategoryFormation::new(DistanceMetric::Euclidean)
+            .agglomerate(&space, Linkage::Ward)
+            .unwrap();
+        let taxonomy = dendrogram.taxonomy(&space, &[4, 2]).unwrap();
+
+        assert_eq!(taxonomy.nodes.len(), 6);
+        assert_eq!(taxonomy.roots().len(), 2);
+        for root in taxonomy.roots() {
+            let children = taxonomy.children(&root.region.id);
+            assert_eq!(children.len(), 2);
+            for child in children {
+                assert_eq!(child.parent, Some(root.region.id));
+                assert!(root.region.contains(&child.region.prototype));
+                for id in &child.members {
+                    assert!(root.members.contains(id));
+                }
+            }
+        }
+
+        let morphisms = taxonomy.to_morphisms(ContextId::new());
+        assert_eq!(morphisms.len(), 4);
+        assert!(morphisms.all().iter().all(|m| {
+            matches!(m.morphism_type, MorphismType::Hierarchy { .. }) && (m.strength - 0.5).abs() < 1e-12
+        }));
+    }
+}
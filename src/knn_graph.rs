@@ -0,0 +1,245 @@
+//! Nearest-neighbour graphs over a conceptual space
+//!
+//! Builds k-NN and mutual k-NN graphs from batched index queries and
+//! answers reverse k-NN queries ("which concepts count X among their
+//! nearest neighbours"), the basis for hub detection and graph clustering.
+
+use crate::{ConceptualPoint, ConceptualResult, ConceptualSpace};
+use petgraph::graph::{Graph, NodeIndex};
+use petgraph::visit::EdgeRef;
+use petgraph::{Directed, Direction, EdgeType, Undirected};
+use std::collections::{HashMap, HashSet};
+use uuid::Uuid;
+
+/// A neighbour graph with point ids as nodes and distances as edge weights
+#[derive(Debug, Clone)]
+pub struct NeighborGraph<Ty: EdgeType> {
+    /// The underlying graph
+    pub graph: Graph<Uuid, f64, Ty>,
+
+    /// Node lookup by point id
+    pub nodes: HashMap<Uuid, NodeIndex>,
+
+    /// Neighbourhood size the graph was built with
+    pub k: usize,
+}
+
+/// Directed k-NN graph: an edge `a -> b` means `b` is one of `a`'s k nearest
+pub type KnnGraph = NeighborGraph<Directed>;
+
+/// Undirected mutual k-NN graph: `a` and `b` are each in the other's k nearest
+pub type MutualKnnGraph = NeighborGraph<Undirected>;
+
+impl<Ty: EdgeType> NeighborGraph<Ty> {
+    fn with_nodes(ids: &[Uuid], k: usize) -> Self {
+        let mut graph = Graph::default();
+        let nodes = ids.iter().map(|id| (*id, graph.add_node(*id))).collect();
+        Self { graph, nodes, k }
+    }
+
+    /// Get the node index of a point
+    pub fn node(&self, id: &Uuid) -> Option<NodeIndex> {
+        self.nodes.get(id).copied()
+    }
+
+    /// Neighbours of a point with their distances
+    pub fn neighbors(&self, id: &Uuid) -> Vec<(Uuid, f64)> {
+        self.edges(id, Direction::Outgoing)
+    }
+
+    fn edges(&self, id: &Uuid, direction: Direction) -> Vec<(Uuid, f64)> {
+        let Some(node) = self.node(id) else {
+            return Vec::new();
+        };
+
+        let mut edges: Vec<_> = self.graph.edges_directed(node, direction)
+            .map(|edge| {
+                let other = if edge.source() == node { edge.target() } else { edge.source() };
+                (self.graph[other], *edge.weight())
+            })
+            .collect();
+        edges.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal));
+        edges
+    }
+}
+
+impl KnnGraph {
+    /// Points that count `id` among their k nearest neighbours
+    pub fn reverse_neighbors(&self, id: &Uuid) -> Vec<(Uuid, f64)> {
+        self.edges(id, Direction::Incoming)
+    }
+
+    /// Points ranked by how often they appear in other points' neighbourhoods
+    ///
+    /// A k-occurrence far above `k` marks a hub; zero marks an anti-hub.
+    pub fn hub_scores(&self) -> Vec<(Uuid, usize)> {
+        let mut scores: Vec<_> = self.graph.node_indices()
+            .map(|node| {
+                let occurrences = self.graph.edges_directed(node, Direction::Incoming).count();
+                (self.graph[node], occurrences)
+            })
+            .collect();
+        scores.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        scores
+    }
+
+    /// Distance from every point to its k-th nearest neighbour
+    ///
+    /// A point with fewer than `k` neighbours reaches infinitely far, since
+    /// anything would be among its k nearest.
+    pub fn k_distances(&self) -> HashMap<Uuid, f64> {
+        self.graph.node_indices()
+            .map(|node| {
+                let (count, farthest) = self.graph.edges_directed(node, Direction::Outgoing)
+                    .fold((0, 0.0_f64), |(count, farthest), edge| (count + 1, farthest.max(*edge.weight())));
+                let reach = if count < self.k { f64::INFINITY } else { farthest };
+                (self.graph[node], reach)
+            })
+            .collect()
+    }
+
+    /// Keep only the edges whose reverse edge also exists
+    pub fn to_mutual(&self) -> MutualKnnGraph {
+        let mut ids: Vec<Uuid> = self.nodes.keys().copied().collect();
+        ids.sort();
+        let mut mutual = MutualKnnGraph::with_nodes(&ids, self.k);
+
+        for edge in self.graph.edge_references() {
+            let (a, b) = (self.graph[edge.source()], self.graph[edge.target()]);
+            // Add each mutual pair once, from its smaller endpoint
+            if a < b && self.graph.contains_edge(edge.target(), edge.source()) {
+                mutual.graph.add_edge(mutual.nodes[&a], mutual.nodes[&b], *edge.weight());
+            }
+        }
+
+        mutual
+    }
+
+    /// Build the directed k-NN graph over all points of a space
+    pub fn build(space: &ConceptualSpace, k: usize) -> ConceptualResult<Self> {
+        let lists = neighbor_lists(space, k)?;
+        let ids: Vec<Uuid> = lists.iter().map(|(id, _)| *id).collect();
+        let mut knn = KnnGraph::with_nodes(&ids, k);
+
+        for (id, neighbors) in lists {
+            for (neighbor, distance) in neighbors {
+                knn.graph.add_edge(knn.nodes[&id], knn.nodes[&neighbor], distance);
+            }
+        }
+
+        Ok(knn)
+    }
+
+    /// The points of `space` that would count a query point among their k nearest
+    ///
+    /// Unlike `reverse_neighbors`, the query need not be a node of the graph,
+    /// which must have been built over the space's current points.
+    pub fn reverse_neighbors_of(
+        &self,
+        space: &ConceptualSpace,
+        query: &ConceptualPoint,
+    ) -> ConceptualResult<Vec<(Uuid, f64)>> {
+        if self.k == 0 {
+            return Ok(Vec::new());
+        }
+
+        let k_distances = self.k_distances();
+        let excluded: HashSet<Uuid> = query.id.into_iter().collect();
+        let radius = k_distances.values().copied().fold(0.0, f64::max);
+        let candidates: Vec<(Uuid, f64)> = if radius.is_finite() {
+            space.range_search(query, radius)?.into_iter().map(|(id, d)| (*id, d)).collect()
+        } else {
+            space.distances_from(query)?
+        };
+
+        let mut reverse: Vec<_> = candidates.into_iter()
+            .filter(|(id, d)| !excluded.contains(id) && k_distances.get(id).is_some_and(|reach| d <= reach))
+            .collect();
+        reverse.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal));
+        Ok(reverse)
+    }
+}
+
+/// Each point id paired with its neighbours and their distances
+type NeighborLists = Vec<(Uuid, Vec<(Uuid, f64)>)>;
+
+/// The k nearest neighbours of every point, excluding the point itself
+fn neighbor_lists(space: &ConceptualSpace, k: usize) -> ConceptualResult<NeighborLists> {
+    let mut ids: Vec<Uuid> = space.points.keys().copied().collect();
+    ids.sort();
+
+    let queries: Vec<ConceptualPoint> = ids.iter().filter_map(|id| space.points.get(id)).collect();
+    let results = space.k_nearest_neighbors_batch(&queries, k + 1)?;
+
+    Ok(ids.into_iter()
+        .zip(results)
+        .map(|(id, neighbors)| {
+            let mut neighbors: Vec<_> = neighbors.into_iter().filter(|(n, _)| *n != id).collect();
+            neighbors.truncate(k);
+            (id, neighbors)
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ConceptualMetric, DimensionId};
+
+    fn line_space(xs: &[f64]) -> (ConceptualSpace, Vec<Uuid>) {
+        let mut space = ConceptualSpace::new(
+            "Line".to_string(),
+            vec![DimensionId::new(), DimensionId::new()],
+            ConceptualMetric::uniform(2, 2.0),
+        );
+        let ids = xs.iter()
+            .map(|x| space.add_point(ConceptualPoint::new(vec![*x, 0.0], HashMap::new())).unwrap())
+            .collect();
+        (space, ids)
+    }
+
+    #[test]
+    fn test_knn_graph_edges_and_hubs() {
+        // Point 1 sits between two tight pairs and is everyone's neighbour
+        let (space, ids) = line_space(&[0.0, 1.0, 2.1, 5.0]);
+        let knn = space.knn_graph(1).unwrap();
+
+        assert_eq!(knn.graph.node_count(), 4);
+        assert_eq!(knn.graph.edge_count(), 4);
+        assert_eq!(knn.neighbors(&ids[0]), vec![(ids[1], 1.0)]);
+        assert_eq!(knn.neighbors(&ids[1])[0].0, ids[0]);
+
+        let reverse: HashSet<Uuid> = knn.reverse_neighbors(&ids[1]).into_iter().map(|(id, _)| id).collect();
+        assert_eq!(reverse, HashSet::from([ids[0], ids[2]]));
+        assert_eq!(knn.hub_scores()[0], (ids[1], 2));
+
+        let mutual = space.mutual_knn_graph(1).unwrap();
+        assert_eq!(mutual.graph.edge_count(), 1);
+        assert_eq!(mutual.neighbors(&ids[0]), vec![(ids[1], 1.0)]);
+        assert_eq!(mutual.neighbors(&ids[1]), vec![(ids[0], 1.0)]);
+    }
+
+    #[test]
+    fn test_reverse_knn_matches_graph() {
+        let (space, ids) = line_space(&[0.0, 1.0, 2.1, 5.0, 5.5, 9.0]);
+        let knn = space.knn_graph(2).unwrap();
+
+        for id in &ids {
+            let mut from_query: Vec<Uuid> = space.reverse_k_nearest_neighbors(&knn, &space.points.get(id).unwrap())
+                .unwrap()
+                .into_iter()
+                .map(|(id, _)| id)
+                .collect();
+            let mut from_graph: Vec<Uuid> = knn.reverse_neighbors(id).into_iter().map(|(id, _)| id).collect();
+            from_query.sort();
+            from_graph.sort();
+            assert_eq!(from_query, from_graph);
+        }
+
+        // An outside query close to the last point is its nearest neighbour
+        let probe = ConceptualPoint::new(vec![8.8, 0.0], HashMap::new());
+        let reverse = space.reverse_k_nearest_neighbors(&space.knn_graph(1).unwrap(), &probe).unwrap();
+        assert_eq!(reverse.len(), 1);
+        assert_eq!(reverse[0].0, ids[5]);
+    }
+}
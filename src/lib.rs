@@ -18,6 +18,8 @@ pub mod projections;
 
 // Original modules (domain logic)
 pub mod space;
+pub mod point_store;
+pub mod sparse;
 pub mod dimensions;
 pub mod concept_map;
 pub mod morphisms;
@@ -28,8 +30,28 @@ pub mod category_theory;
 // New modules for completion
 pub mod spatial_index;
 pub mod similarity;
+pub mod similarity_cache;
+pub mod similarity_function;
+pub mod tversky;
+pub mod explanation;
+pub mod metric_learning;
+pub mod set_distance;
+pub mod region_similarity;
+pub mod trajectory;
+pub(crate) mod sampling;
 pub mod category_formation;
+pub mod partitioning;
+pub mod density_clustering;
+pub mod mixture;
+pub mod hierarchy;
+pub mod online_learning;
+pub mod supervised;
+pub mod category_quality;
+pub mod category_refinement;
+pub mod contrast;
 pub mod reasoning;
+pub mod similarity_join;
+pub mod knn_graph;
 
 // ECS systems
 pub mod systems;
@@ -45,13 +67,14 @@ pub use value_objects::{
 
 // Re-export commands
 pub use commands::{
-    CreateConceptualSpace, AddConcept, AddRegion, ReplaceDimensionWeights
+    CreateConceptualSpace, AddConcept, AddRegion, RemoveRegion, ReplaceDimensionWeights
 };
 
 // Re-export events
 pub use events::{
     ConceptualSpaceCreated, ConceptAdded, 
-    RegionAdded, DimensionWeightsRemoved, DimensionWeightsAdded
+    RegionAdded, DimensionWeightsRemoved, DimensionWeightsAdded,
+    CategoryFormed, CategoryRemoved, ConceptCategorized, ConceptUncategorized
 };
 
 // Re-export handlers
@@ -63,8 +86,10 @@ pub use queries::{FindSimilarConcepts, SimilarConcepts};
 // Re-export core types from original modules
 pub use space::{
     ConceptualSpace, ConceptualPoint, ConceptualSpaceId, DimensionId,
-    ConceptualMetric, OpenBall
+    ConceptualMetric, OpenBall, DistanceMatrix, IndexRestore
 };
+pub use point_store::{PointStore, PointStorage};
+pub use sparse::SparsePoint;
 pub use dimensions::{DistanceMetric, DimensionRegistry};
 pub use concept_map::{ConceptMap, ConceptMapId, ConceptNode, ConceptEdge, ContextId};
 pub use morphisms::{CrossContextMorphism, MorphismType, ConceptId};
@@ -72,13 +97,52 @@ pub use projection::{ConceptualProjection, ConceptualChange};
 pub use traits::{ConceptualEntity, ConceptProducer};
 
 // Re-export new modules
-pub use spatial_index::{SpatialIndex, RTreeIndex, KdTreeIndex};
+pub use spatial_index::{
+    SpatialIndex, SpatialIndexConfig, SpaceIndex, ScanIndex, RTreeIndex, KdTreeIndex,
+    IndexSnapshot, INDEX_SNAPSHOT_FORMAT
+};
 pub use similarity::{SimilarityEngine, AdvancedSimilarity};
+pub use similarity_cache::{SimilarityCache, CacheStats, MutationClock, DEFAULT_CACHE_CAPACITY};
+pub use similarity_function::{
+    SimilarityFunction, SimilarityFamily, SimilarityCalibration, SimilarityFit,
+};
+pub use tversky::{
+    TverskyModel, TverskyForm, FeatureSet, FeatureThreshold, FeatureOverlap,
+};
+pub use explanation::{DistanceExplanation, DimensionContribution, DomainContribution};
+pub use metric_learning::{
+    MetricLearner, MetricLearningConfig, MetricConstraint, LearnedWeights, MahalanobisMetric,
+};
+pub use set_distance::SetDistance;
+pub use region_similarity::{RegionSimilarity, RegionSimilarityConfig};
+pub use trajectory::{ConceptTrajectory, WarpingWindow};
 pub use category_formation::{CategoryFormation, CategoryBoundaryDetection};
+pub use partitioning::{
+    Partition, KMeansConfig, MedoidMethod, PartitionAlgorithm, KSelection, KSelectionResult,
+};
+pub use density_clustering::{DensityCluster, DensityClustering};
+pub use mixture::{
+    GaussianMixture, GaussianComponent, CovarianceType, MixtureConfig, MixtureSelection,
+};
+pub use hierarchy::{Linkage, Dendrogram, DendrogramMerge, Taxonomy, TaxonomyNode};
+pub use online_learning::{
+    OnlineCategoryLearner, OnlineCategory, OnlineLearningConfig, PrototypeUpdate,
+};
+pub use supervised::{
+    SupervisedLearner, SupervisedModel, SupervisedMode, SvmConfig, LearnedCategory,
+    ConvexityViolation,
+};
+pub use category_quality::{CategoryQualityReport, RegionQuality, RegionOverlap};
+pub use category_refinement::{
+    CategoryRefinementAnalyzer, RefinementConfig, RefinementProposal, RefinementCommand, ProposalKind,
+};
+pub use contrast::{ContrastClass, ValueRange};
 pub use reasoning::{
     ConceptualReasoning, CategoryInference, ConceptualBlend,
     SemanticPath, SimilarityMatch, PathConstraints
 };
+pub use similarity_join::{SimilarityJoin, JoinPredicate, JoinPair};
+pub use knn_graph::{NeighborGraph, KnnGraph, MutualKnnGraph};
 
 use cim_domain::DomainError;
 use thiserror::Error;
@@ -102,6 +166,10 @@ pub enum ConceptualError {
     #[error("Projection error: {0}")]
     ProjectionError(String),
 
+    /// Index snapshot could not be encoded or decoded
+    #[error("Index snapshot error: {0}")]
+    SnapshotError(String),
+
     /// Domain error
     #[error("Domain error: {0}")]
     DomainError(#[from] DomainError),
@@ -0,0 +1,478 @@
+//! Learning the metric of a space from similarity constraints
+//!
+//! Judgements such as "A is closer to B than to C" or "A and B are
+//! dissimilar" constrain the geometry rather than individual scores. This
+//! module fits non-negative dimension weights, or a full positive
+//! semi-definite Mahalanobis matrix, to such constraints by projected
+//! gradient descent on a hinge loss. Learned weights are applied through a
+//! `ReplaceDimensionWeights` command so the change goes through the
+//! aggregate; that command only carries constants, so spaces with contextual
+//! or attentional weights take the rescaled weights directly instead.
+
+use crate::{
+    ConceptualError, ConceptualResult, ConceptualSpace, ConceptualSpaceId, DimensionWeight,
+    ReplaceDimensionWeights,
+};
+use nalgebra::{DMatrix, DVector};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// A judgement about the relative or absolute closeness of points
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MetricConstraint {
+    /// `anchor` is closer to `closer` than to `farther`
+    Triplet {
+        anchor: Uuid,
+        closer: Uuid,
+        farther: Uuid,
+    },
+
+    /// The two points should be near each other
+    Similar(Uuid, Uuid),
+
+    /// The two points should be far apart
+    Dissimilar(Uuid, Uuid),
+}
+
+/// Parameters of the projected gradient descent
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MetricLearningConfig {
+    /// Step size of each gradient step
+    pub learning_rate: f64,
+
+    /// Number of gradient steps
+    pub iterations: usize,
+
+    /// Required gap between the far and near distance of a triplet
+    pub margin: f64,
+
+    /// Largest acceptable distance between similar points
+    pub similar_bound: f64,
+
+    /// Smallest acceptable distance between dissimilar points
+    pub dissimilar_bound: f64,
+
+    /// Pull towards the starting weights, keeping the change minimal
+    pub regularization: f64,
+}
+
+impl Default for MetricLearningConfig {
+    fn default() -> Self {
+        Self {
+            learning_rate: 0.05,
+            iterations: 500,
+            margin: 0.5,
+            similar_bound: 1.0,
+            dissimilar_bound: 4.0,
+            regularization: 0.01,
+        }
+    }
+}
+
+/// Dimension weights fitted to constraints
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LearnedWeights {
+    /// Space the weights were learned for
+    pub space_id: ConceptualSpaceId,
+
+    /// Non-negative weight per dimension, under the space's current context
+    pub weights: Vec<f64>,
+
+    /// The space's own weights, each rescaled to its learned value
+    ///
+    /// Contextual and attentional weights keep their kind: modifiers and
+    /// bounds scale with the base. A weight that started at zero has no
+    /// scale to keep and becomes a constant.
+    pub rescaled_weights: Vec<DimensionWeight>,
+
+    /// Constraints violated under the starting weights
+    pub violations_before: usize,
+
+    /// Constraints violated under the learned weights
+    pub violations_after: usize,
+
+    /// Loss under the starting weights
+    pub initial_loss: f64,
+
+    /// Loss under the learned weights
+    pub final_loss: f64,
+}
+
+impl LearnedWeights {
+    /// The learned weights, keeping the kind of each of the space's weights
+    pub fn dimension_weights(&self) -> Vec<DimensionWeight> {
+        self.rescaled_weights.clone()
+    }
+
+    /// Command replacing the space's weights with the learned ones
+    ///
+    /// The command carries plain values, which the aggregate stores as
+    /// constant weights, so it is refused when any of the space's weights
+    /// was contextual or attentional; apply `dimension_weights` instead.
+    pub fn to_command(&self) -> ConceptualResult<ReplaceDimensionWeights> {
+        require_constant(self.rescaled_weights.iter().all(|w| matches!(w, DimensionWeight::Constant(_))))?;
+        Ok(ReplaceDimensionWeights {
+            space_id: self.space_id,
+            new_weights: self.weights.clone(),
+            reason: format!(
+                "Metric learned from constraints ({} -> {} violated)",
+                self.violations_before, self.violations_after
+            ),
+        })
+    }
+}
+
+/// A full Mahalanobis metric fitted to constraints
+///
+/// Distances are `sqrt((a - b)ᵀ M (a - b))`; `M` is kept positive
+/// semi-definite, so off-diagonal entries capture correlated dimensions
+/// that dimension weights cannot.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MahalanobisMetric {
+    /// Space the metric was learned for
+    pub space_id: ConceptualSpaceId,
+
+    /// The positive semi-definite matrix `M`
+    pub matrix: DMatrix<f64>,
+
+    /// Constraints violated under the starting matrix
+    pub violations_before: usize,
+
+    /// Constraints violated under the learned matrix
+    pub violations_after: usize,
+
+    /// Whether every starting weight of the space was constant
+    pub constant_weights: bool,
+}
+
+impl MahalanobisMetric {
+    /// Distance between two coordinate vectors
+    pub fn distance(&self, a: &DVector<f64>, b: &DVector<f64>) -> ConceptualResult<f64> {
+        if a.len() != self.matrix.nrows() || b.len() != self.matrix.nrows() {
+            return Err(ConceptualError::InvalidDimension(
+                "Points do not match the metric dimensions".to_string()
+            ));
+        }
+        let delta = a - b;
+        Ok(delta.dot(&(&self.matrix * &delta)).max(0.0).sqrt())
+    }
+
+    /// Linear map `L` with `LᵀL = M`, turning the metric into plain Euclidean
+    pub fn transform(&self) -> DMatrix<f64> {
+        let eigen = self.matrix.clone().symmetric_eigen();
+        let roots = eigen.eigenvalues.map(|v| v.max(0.0).sqrt());
+        DMatrix::from_diagonal(&roots) * eigen.eigenvectors.transpose()
+    }
+
+    /// Command applying the diagonal of `M` as dimension weights
+    ///
+    /// Dimension weights cannot express correlations, so off-diagonal
+    /// entries are dropped. Like `LearnedWeights::to_command`, it is refused
+    /// when the space had contextual or attentional weights, which the
+    /// command would flatten into constants.
+    pub fn to_command(&self) -> ConceptualResult<ReplaceDimensionWeights> {
+        require_constant(self.constant_weights)?;
+        Ok(ReplaceDimensionWeights {
+            space_id: self.space_id,
+            new_weights: self.matrix.diagonal().iter().copied().collect(),
+            reason: "Diagonal of a learned Mahalanobis metric".to_string(),
+        })
+    }
+}
+
+/// Refuse a weight command that would replace non-constant weights
+fn require_constant(constant: bool) -> ConceptualResult<()> {
+    if constant {
+        Ok(())
+    } else {
+        Err(ConceptualError::InvalidDimension(
+            "Space has contextual or attentional weights; apply the rescaled weights instead".to_string()
+        ))
+    }
+}
+
+/// A constraint as a linear hinge `max(0, θ·g + offset)` on the parameters
+struct Hinge {
+    gradient: DVector<f64>,
+    offset: f64,
+    triplet: bool,
+}
+
+impl Hinge {
+    fn loss(&self, theta: &DVector<f64>) -> f64 {
+        (theta.dot(&self.gradient) + self.offset).max(0.0)
+    }
+
+    /// Triplets count as violated when the order is wrong, ignoring the margin
+    fn violated(&self, theta: &DVector<f64>, margin: f64) -> bool {
+        let value = theta.dot(&self.gradient) + self.offset;
+        if self.triplet { value - margin >= 0.0 } else { value > 0.0 }
+    }
+}
+
+/// Learns space metrics from constraints
+#[derive(Debug, Clone, Default)]
+pub struct MetricLearner {
+    /// Optimisation parameters
+    pub config: MetricLearningConfig,
+}
+
+impl MetricLearner {
+    /// Create a learner with the given parameters
+    pub fn new(config: MetricLearningConfig) -> Self {
+        Self { config }
+    }
+
+    /// Fit non-negative dimension weights to the constraints
+    ///
+    /// Works on the metric's power sum `Σ wᵢ·|aᵢ - bᵢ|^p`, which is linear
+    /// in the weights, so distance bounds and margins are in those units.
+    ///
+    /// ```mermaid
+    /// graph LR
+    ///     C[Constraints] --> H[Hinge Losses]
+    ///     W[Current Weights] --> G[Gradient Step]
+    ///     H --> G
+    ///     G --> P[Clamp to w >= 0]
+    ///     P --> G
+    ///     P --> R[ReplaceDimensionWeights]
+    /// ```
+    pub fn learn_weights(
+        &self,
+        space: &ConceptualSpace,
+        constraints: &[MetricConstraint],
+    ) -> ConceptualResult<LearnedWeights> {
+        let p = space.metric.minkowski_p;
+        let hinges = self.hinges(space, constraints, |delta| delta.map(|d| d.abs().powf(p)))?;
+        let start = DVector::from_vec(space.metric.get_weights());
+
+        let (weights, before, after) = self.descend(&hinges, start.clone(), |theta| theta.apply(|w| *w = w.max(0.0)));
+
+        let rescaled_weights = space.metric.dimension_weights.iter()
+            .zip(start.iter().zip(weights.iter()))
+            .map(|(weight, (&before, &after))| if before > 0.0 {
+                weight.rescaled(after / before)
+            } else {
+                DimensionWeight::constant(after)
+            })
+            .collect();
+
+        Ok(LearnedWeights {
+            space_id: space.id,
+            weights: weights.iter().copied().collect(),
+            rescaled_weights,
+            violations_before: before.0,
+            violations_after: after.0,
+            initial_loss: before.1,
+            final_loss: after.1,
+        })
+    }
+
+    /// Fit a positive semi-definite Mahalanobis matrix to the constraints
+    ///
+    /// Starts from the diagonal of the space's weights; each step is
+    /// projected back onto the PSD cone by clamping negative eigenvalues.
+    /// Bounds and margins apply to squared distances.
+    pub fn learn_mahalanobis(
+        &self,
+        space: &ConceptualSpace,
+        constraints: &[MetricConstraint],
+    ) -> ConceptualResult<MahalanobisMetric> {
+        let n = space.dimension_ids.len();
+        let hinges = self.hinges(space, constraints, |delta| {
+            let outer = delta * delta.transpose();
+            DVector::from_column_slice(outer.as_slice())
+        })?;
+        let start = DMatrix::from_diagonal(&DVector::from_vec(space.metric.get_weights()));
+        let start = DVector::from_column_slice(start.as_slice());
+
+        let (theta, before, after) = self.descend(&hinges, start, |theta| {
+            let matrix = DMatrix::from_column_slice(n, n, theta.as_slice());
+            let symmetric = (&matrix + matrix.transpose()) * 0.5;
+            let eigen = symmetric.symmetric_eigen();
+            let clamped = eigen.eigenvalues.map(|v| v.max(0.0));
+            let projected = &eigen.eigenvectors * DMatrix::from_diagonal(&clamped) * eigen.eigenvectors.transpose();
+            theta.copy_from_slice(projected.as_slice());
+        });
+
+        Ok(MahalanobisMetric {
+            space_id: space.id,
+            matrix: DMatrix::from_column_slice(n, n, theta.as_slice()),
+            violations_before: before.0,
+            violations_after: after.0,
+            constant_weights: space.metric.dimension_weights.iter().all(|w| matches!(w, DimensionWeight::Constant(_))),
+        })
+    }
+
+    fn hinges(
+        &self,
+        space: &ConceptualSpace,
+        constraints: &[MetricConstraint],
+        features: impl Fn(&DVector<f64>) -> DVector<f64>,
+    ) -> ConceptualResult<Vec<Hinge>> {
+        let coordinates = |id: &Uuid| {
+            space.points.coordinates(id)
+                .ok_or_else(|| ConceptualError::InvalidPoint(format!("Point {id} not found")))
+        };
+        let pair = |a: &Uuid, b: &Uuid| -> ConceptualResult<DVector<f64>> {
+            Ok(features(&(coordinates(a)? - coordinates(b)?)))
+        };
+
+        constraints.iter()
+            .map(|constraint| Ok(match constraint {
+                MetricConstraint::Triplet { anchor, closer, farther } => Hinge {
+                    gradient: pair(anchor, closer)? - pair(anchor, farther)?,
+                    offset: self.config.margin,
+                    triplet: true,
+                },
+                MetricConstraint::Similar(a, b) => Hinge {
+                    gradient: pair(a, b)?,
+                    offset: -self.config.similar_bound,
+                    triplet: false,
+                },
+                MetricConstraint::Dissimilar(a, b) => Hinge {
+                    gradient: -pair(a, b)?,
+                    offset: self.config.dissimilar_bound,
+                    triplet: false,
+                },
+            }))
+            .collect()
+    }
+
+    /// Projected subgradient descent, keeping the best iterate
+    ///
+    /// Returns the parameters with `(violations, loss)` before and after.
+    fn descend(
+        &self,
+        hinges: &[Hinge],
+        start: DVector<f64>,
+        project: impl Fn(&mut DVector<f64>),
+    ) -> (DVector<f64>, (usize, f64), (usize, f64)) {
+        let config = &self.config;
+        let count = hinges.len().max(1) as f64;
+        let loss = |theta: &DVector<f64>| {
+            hinges.iter().map(|h| h.loss(theta)).sum::<f64>() / count
+                + config.regularization * (theta - &start).norm_squared()
+        };
+        let violations = |theta: &DVector<f64>| {
+            hinges.iter().filter(|h| h.violated(theta, config.margin)).count()
+        };
+
+        let mut theta = start.clone();
+        project(&mut theta);
+        let mut best = (theta.clone(), loss(&theta));
+
+        for _ in 0..config.iterations {
+            let mut gradient = (&theta - &start) * (2.0 * config.regularization);
+            for hinge in hinges.iter().filter(|h| h.loss(&theta) > 0.0) {
+                gradient += &hinge.gradient / count;
+            }
+
+            theta -= gradient * config.learning_rate;
+            project(&mut theta);
+
+            let value = loss(&theta);
+            if value < best.1 {
+                best = (theta.clone(), value);
+            }
+        }
+
+        let before = (violations(&start), loss(&start));
+        let after = (violations(&best.0), best.1);
+        (best.0, before, after)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ConceptualMetric, ConceptualPoint, DimensionId};
+    use std::collections::HashMap;
+
+    /// Points whose class follows dimension 0, with noise on dimension 1
+    fn labelled_space() -> (ConceptualSpace, Vec<Uuid>, Vec<Uuid>) {
+        let mut space = ConceptualSpace::new(
+            "Learn".to_string(),
+            vec![DimensionId::new(), DimensionId::new()],
+            ConceptualMetric::uniform(2, 2.0),
+        );
+        let mut add = |x: f64, y: f64| space.add_point(ConceptualPoint::new(vec![x, y], HashMap::new())).unwrap();
+        let left = vec![add(0.0, 0.0), add(0.2, 3.0), add(0.1, 6.0)];
+        let right = vec![add(1.5, 0.5), add(1.4, 3.5), add(1.6, 6.5)];
+        (space, left, right)
+    }
+
+    #[test]
+    fn test_learn_weights_satisfies_triplets() {
+        let (space, left, right) = labelled_space();
+        let constraints = vec![
+            MetricConstraint::Triplet { anchor: left[0], closer: left[1], farther: right[0] },
+            MetricConstraint::Triplet { anchor: left[2], closer: left[1], farther: right[2] },
+            MetricConstraint::Triplet { anchor: right[0], closer: right[1], farther: left[0] },
+            MetricConstraint::Similar(left[0], left[2]),
+            MetricConstraint::Dissimilar(left[0], right[0]),
+        ];
+
+        let learned = MetricLearner::default().learn_weights(&space, &constraints).unwrap();
+        assert!(learned.violations_before > 0);
+        assert_eq!(learned.violations_after, 0);
+        assert!(learned.final_loss < learned.initial_loss);
+        assert!(learned.weights.iter().all(|w| *w >= 0.0));
+        assert!(learned.weights[0] > learned.weights[1]);
+
+        let command = learned.to_command().unwrap();
+        assert_eq!(command.space_id, space.id);
+        assert_eq!(command.new_weights, learned.weights);
+
+        let missing = [MetricConstraint::Similar(left[0], Uuid::new_v4())];
+        assert!(MetricLearner::default().learn_weights(&space, &missing).is_err());
+    }
+
+    #[test]
+    fn test_learned_weights_keep_their_kind() {
+        let (mut space, left, right) = labelled_space();
+        space.metric.dimension_weights = vec![
+            DimensionWeight::contextual(1.0).with_context("focus".to_string(), 2.0),
+            DimensionWeight::attentional(1.0, 0.5, 4.0),
+        ];
+        let constraints = vec![
+            MetricConstraint::Triplet { anchor: left[0], closer: left[1], farther: right[0] },
+            MetricConstraint::Triplet { anchor: right[0], closer: right[1], farther: left[0] },
+        ];
+
+        let learned = MetricLearner::default().learn_weights(&space, &constraints).unwrap();
+        match &learned.dimension_weights()[..] {
+            [DimensionWeight::Contextual { base_weight, context_modifiers }, DimensionWeight::Attentional { current_weight, max_weight, .. }] => {
+                assert!((base_weight - learned.weights[0]).abs() < 1e-12);
+                assert!((context_modifiers["focus"] - 2.0 * learned.weights[0]).abs() < 1e-12);
+                assert!((current_weight - learned.weights[1]).abs() < 1e-12);
+                assert!((max_weight - 4.0 * learned.weights[1]).abs() < 1e-12);
+            }
+            other => panic!("weight kinds changed: {other:?}"),
+        }
+
+        // The command would flatten them into constants
+        assert!(learned.to_command().is_err());
+        let mahalanobis = MetricLearner::default().learn_mahalanobis(&space, &constraints).unwrap();
+        assert!(mahalanobis.to_command().is_err());
+    }
+
+    #[test]
+    fn test_learn_mahalanobis_stays_psd() {
+        let (space, left, right) = labelled_space();
+        let constraints: Vec<_> = left.iter()
+            .zip(right.iter())
+            .map(|(l, r)| MetricConstraint::Dissimilar(*l, *r))
+            .chain(left.windows(2).map(|w| MetricConstraint::Similar(w[0], w[1])))
+            .collect();
+
+        let learned = MetricLearner::default().learn_mahalanobis(&space, &constraints).unwrap();
+        assert!(learned.violations_after < learned.violations_before);
+        assert!(learned.matrix.clone().symmetric_eigen().eigenvalues.iter().all(|v| *v > -1e-9));
+
+        // Distances through the transform agree with the matrix form
+        let (a, b) = (space.points.coordinates(&left[0]).unwrap(), space.points.coordinates(&right[1]).unwrap());
+        let mapped = (learned.transform() * (&a - &b)).norm();
+        assert!((mapped - learned.distance(&a, &b).unwrap()).abs() < 1e-9);
+    }
+}
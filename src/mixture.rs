@@ -0,0 +1,555 @@
+//! Gaussian mixture models for graded categories
+//!
+//! Where partitioning gives every point exactly one category, a mixture
+//! fitted by expectation-maximisation gives each point a posterior
+//! probability per component. Components become convex regions through a
+//! probability contour of their Gaussian, circumscribed by hyperplanes.
+
+use crate::partitioning::point_rows;
+use crate::value_objects::DIAGONAL_FACE_DIMENSIONS;
+use crate::{
+    CategoryFormation, ConceptualError, ConceptualPoint, ConceptualResult, ConceptualSpace,
+    ConvexRegion, DistanceMetric, Hyperplane, KMeansConfig,
+};
+use nalgebra::{DMatrix, DVector};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::f64::consts::PI;
+use std::ops::RangeInclusive;
+use uuid::Uuid;
+
+/// Shape of the component covariance matrices
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CovarianceType {
+    /// Unrestricted symmetric covariance
+    Full,
+
+    /// Independent dimensions with their own variances
+    Diagonal,
+
+    /// One shared variance for all dimensions
+    Spherical,
+}
+
+/// Settings for fitting a mixture by EM
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct MixtureConfig {
+    /// Covariance shape of every component
+    pub covariance: CovarianceType,
+
+    /// Maximum EM iterations
+    pub max_iterations: usize,
+
+    /// Change in mean log-likelihood per point below which EM stops
+    pub tolerance: f64,
+
+    /// Added to covariance diagonals to keep them positive definite
+    pub regularization: f64,
+
+    /// Seed of the k-means initialisation
+    pub seed: u64,
+}
+
+impl Default for MixtureConfig {
+    fn default() -> Self {
+        Self {
+            covariance: CovarianceType::Full,
+            max_iterations: 200,
+            tolerance: 1e-6,
+            regularization: 1e-6,
+            seed: 0x5EED,
+        }
+    }
+}
+
+/// One Gaussian of a mixture
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GaussianComponent {
+    /// Id given to the component's region
+    pub id: Uuid,
+
+    /// Mixing weight
+    pub weight: f64,
+
+    /// Mean, used as the region prototype
+    pub mean: DVector<f64>,
+
+    /// Covariance matrix
+    pub covariance: DMatrix<f64>,
+}
+
+impl GaussianComponent {
+    /// Log of the Gaussian density at `x`
+    ///
+    /// Factors the covariance on every call; evaluating many points goes
+    /// through the mixture, which factors each component once.
+    pub fn log_density(&self, x: &DVector<f64>) -> ConceptualResult<f64> {
+        self.factor()?.log_density(x)
+    }
+
+    /// Cholesky factor of the covariance with the density's constant terms
+    fn factor(&self) -> ConceptualResult<FactoredComponent<'_>> {
+        let lower = self.covariance.clone().cholesky().ok_or_else(|| {
+            ConceptualError::InvalidDimension("Covariance is not positive definite".to_string())
+        })?.unpack();
+        let log_det: f64 = 2.0 * lower.diagonal().iter().map(|v| v.ln()).sum::<f64>();
+        Ok(FactoredComponent {
+            mean: &self.mean,
+            log_weight: self.weight.max(f64::MIN_POSITIVE).ln(),
+            log_normaliser: -0.5 * (self.mean.len() as f64 * (2.0 * PI).ln() + log_det),
+            lower,
+        })
+    }
+
+    /// Squared Mahalanobis radius of the ellipsoid holding `probability` of the mass
+    pub fn contour_radius_squared(dimensions: usize, probability: f64) -> f64 {
+        chi_squared_quantile(dimensions, probability)
+    }
+
+    /// Hyperplanes circumscribing the `probability` contour ellipsoid
+    ///
+    /// Each plane is tangent to the ellipsoid along a principal axis or the
+    /// diagonal between two principal axes, so the polytope contains it.
+    /// Past `DIAGONAL_FACE_DIMENSIONS` only the principal axes are kept, a
+    /// looser box around the ellipsoid with 2·d faces.
+    pub fn contour_boundaries(&self, probability: f64) -> Vec<Hyperplane> {
+        let d = self.mean.len();
+        let radius = Self::contour_radius_squared(d, probability).sqrt();
+        let axes = self.covariance.clone().symmetric_eigen().eigenvectors;
+
+        let diagonals = d <= DIAGONAL_FACE_DIMENSIONS;
+        let mut directions = Vec::new();
+        for i in 0..d {
+            let a = axes.column(i).into_owned();
+            for j in (i + 1..d).filter(|_| diagonals) {
+                let b = axes.column(j);
+                directions.push((&a + b).normalize());
+                directions.push((&a - b).normalize());
+            }
+            directions.push(a);
+        }
+
+        directions.into_iter()
+            .flat_map(|u| [u.clone(), -u])
+            .map(|u| {
+                // Support of the ellipsoid along u
+                let support = u.dot(&self.mean) + radius * (u.transpose() * &self.covariance * &u)[0].max(0.0).sqrt();
+                Hyperplane::new(-u, -support)
+            })
+            .collect()
+    }
+}
+
+/// A component with its covariance factored, for evaluation at many points
+struct FactoredComponent<'a> {
+    mean: &'a DVector<f64>,
+    log_weight: f64,
+    log_normaliser: f64,
+    lower: DMatrix<f64>,
+}
+
+impl FactoredComponent<'_> {
+    fn log_density(&self, x: &DVector<f64>) -> ConceptualResult<f64> {
+        let z = self.lower.solve_lower_triangular(&(x - self.mean)).ok_or_else(|| {
+            ConceptualError::InvalidDimension("Covariance is singular".to_string())
+        })?;
+        Ok(self.log_normaliser - 0.5 * z.norm_squared())
+    }
+}
+
+/// A Gaussian mixture fitted to the points of a space
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GaussianMixture {
+    /// Fitted components
+    pub components: Vec<GaussianComponent>,
+
+    /// Covariance shape used
+    pub covariance: CovarianceType,
+
+    /// Log-likelihood of the training points
+    pub log_likelihood: f64,
+
+    /// Number of training points
+    pub samples: usize,
+
+    /// EM iterations run
+    pub iterations: usize,
+
+    /// Whether EM met its tolerance before the iteration limit
+    pub converged: bool,
+}
+
+/// Outcome of choosing the number of mixture components
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MixtureSelection {
+    /// The mixture with the lowest BIC
+    pub mixture: GaussianMixture,
+
+    /// BIC per candidate component count
+    pub bic: Vec<(usize, f64)>,
+}
+
+impl GaussianMixture {
+    /// Fit `components` Gaussians to the space's points by EM
+    ///
+    /// Initialised from a k-means partition with the same seed.
+    ///
+    /// ```mermaid
+    /// graph TD
+    ///     A[Points] --> B[k-means Initialisation]
+    ///     B --> C[M-step: Weights, Means, Covariances]
+    ///     C --> D[E-step: Posteriors]
+    ///     D -->|likelihood improved| C
+    ///     D -->|converged| E[Gaussian Mixture]
+    /// ```
+    pub fn fit(space: &ConceptualSpace, components: usize, config: &MixtureConfig) -> ConceptualResult<Self> {
+        let (_, rows) = point_rows(space);
+        let n = rows.nrows();
+        let kmeans = KMeansConfig { seed: config.seed, ..KMeansConfig::default() };
+        let partition = CategoryFormation::new(DistanceMetric::Euclidean).k_means(space, components, &kmeans)?;
+
+        let mut responsibilities = DMatrix::zeros(n, components);
+        for (row, &label) in partition.labels.iter().enumerate() {
+            responsibilities[(row, label)] = 1.0;
+        }
+
+        let mut mixture = Self {
+            components: Vec::new(),
+            covariance: config.covariance,
+            log_likelihood: f64::NEG_INFINITY,
+            samples: n,
+            iterations: 0,
+            converged: false,
+        };
+        let mut previous = f64::NEG_INFINITY;
+        for iteration in 1..=config.max_iterations.max(1) {
+            mixture.maximize(&rows, &responsibilities, config);
+            let (posteriors, log_likelihood) = mixture.expect(&rows)?;
+            responsibilities = posteriors;
+            mixture.log_likelihood = log_likelihood;
+            mixture.iterations = iteration;
+
+            if (log_likelihood - previous) / n as f64 <= config.tolerance {
+                mixture.converged = true;
+                break;
+            }
+            previous = log_likelihood;
+        }
+        Ok(mixture)
+    }
+
+    /// Fit every component count in a range and keep the lowest BIC
+    pub fn select(
+        space: &ConceptualSpace,
+        candidates: RangeInclusive<usize>,
+        config: &MixtureConfig,
+    ) -> ConceptualResult<MixtureSelection> {
+        let mut best: Option<GaussianMixture> = None;
+        let mut bic = Vec::new();
+        for k in candidates.filter(|&k| k >= 1 && k <= space.points.len()) {
+            let mixture = Self::fit(space, k, config)?;
+            let score = mixture.bic();
+            bic.push((k, score));
+            if best.as_ref().is_none_or(|b| score < b.bic()) {
+                best = Some(mixture);
+            }
+        }
+
+        let mixture = best.ok_or_else(|| ConceptualError::InvalidPoint(format!(
+            "No candidate component count fits {} points",
+            space.points.len()
+        )))?;
+        Ok(MixtureSelection { mixture, bic })
+    }
+
+    /// Number of free parameters
+    pub fn parameter_count(&self) -> usize {
+        let k = self.components.len();
+        let d = self.components.first().map_or(0, |c| c.mean.len());
+        let covariance = match self.covariance {
+            CovarianceType::Full => d * (d + 1) / 2,
+            CovarianceType::Diagonal => d,
+            CovarianceType::Spherical => 1,
+        };
+        (k - 1) + k * d + k * covariance
+    }
+
+    /// Bayesian information criterion; lower is better
+    pub fn bic(&self) -> f64 {
+        -2.0 * self.log_likelihood + self.parameter_count() as f64 * (self.samples as f64).ln()
+    }
+
+    /// Posterior probability of each component for a point
+    pub fn posteriors(&self, point: &ConceptualPoint) -> ConceptualResult<Vec<f64>> {
+        Self::posteriors_under(&self.factors()?, &point.coordinates)
+    }
+
+    /// Posteriors of every point of a space, keyed by point id
+    pub fn memberships(&self, space: &ConceptualSpace) -> ConceptualResult<HashMap<Uuid, Vec<f64>>> {
+        let factors = self.factors()?;
+        space.points.iter()
+            .map(|(id, point)| Ok((*id, Self::posteriors_under(&factors, &point.coordinates)?)))
+            .collect()
+    }
+
+    fn posteriors_under(factors: &[FactoredComponent<'_>], x: &DVector<f64>) -> ConceptualResult<Vec<f64>> {
+        let logs = Self::joint_log_densities(factors, x)?;
+        let total = log_sum_exp(&logs);
+        Ok(logs.iter().map(|l| (l - total).exp()).collect())
+    }
+
+    /// One region per component bounded by its `probability` contour
+    ///
+    /// Prototypes are the means; members are the points whose most probable
+    /// component it is and that lie inside the contour.
+    pub fn regions(&self, space: &ConceptualSpace, probability: f64) -> ConceptualResult<Vec<ConvexRegion>> {
+        let mut members = vec![Vec::new(); self.components.len()];
+        for (id, posteriors) in self.memberships(space)? {
+            let best = (0..posteriors.len())
+                .max_by(|&a, &b| posteriors[a].total_cmp(&posteriors[b]))
+                .unwrap_or(0);
+            members[best].push(id);
+        }
+
+        let dimension_map = space.points.dimension_map().clone();
+        Ok(self.components.iter()
+            .zip(members)
+            .enumerate()
+            .map(|(index, (component, members))| {
+                let prototype = ConceptualPoint::new(component.mean.as_slice().to_vec(), dimension_map.clone());
+                let mut region = ConvexRegion {
+                    id: component.id,
+                    boundaries: component.contour_boundaries(probability),
+                    ..ConvexRegion::from_prototype(prototype)
+                };
+                region.member_points = members.into_iter()
+                    .filter(|id| space.points.get(id).is_some_and(|p| region.contains(&p)))
+                    .collect();
+                region
+                    .with_name(format!("Mixture Component {index}"))
+                    .with_description(format!(
+                        "{:.0}% contour of a Gaussian with weight {:.3}",
+                        probability * 100.0,
+                        component.weight
+                    ))
+            })
+            .collect())
+    }
+
+    /// Every component factored once, for evaluation at many points
+    fn factors(&self) -> ConceptualResult<Vec<FactoredComponent<'_>>> {
+        self.components.iter().map(GaussianComponent::factor).collect()
+    }
+
+    /// log(wₖ) + log N(x | μₖ, Σₖ) for every component
+    fn joint_log_densities(factors: &[FactoredComponent<'_>], x: &DVector<f64>) -> ConceptualResult<Vec<f64>> {
+        factors.iter()
+            .map(|f| Ok(f.log_weight + f.log_density(x)?))
+            .collect()
+    }
+
+    /// E-step: posteriors of every row and the total log-likelihood
+    ///
+    /// Each component's covariance is factored once for all rows.
+    fn expect(&self, rows: &DMatrix<f64>) -> ConceptualResult<(DMatrix<f64>, f64)> {
+        let factors = self.factors()?;
+        let mut posteriors = DMatrix::zeros(rows.nrows(), self.components.len());
+        let mut log_likelihood = 0.0;
+        for row in 0..rows.nrows() {
+            let logs = Self::joint_log_densities(&factors, &rows.row(row).transpose())?;
+            let total = log_sum_exp(&logs);
+            log_likelihood += total;
+            for (k, l) in logs.iter().enumerate() {
+                posteriors[(row, k)] = (l - total).exp();
+            }
+        }
+        Ok((posteriors, log_likelihood))
+    }
+
+    /// M-step: weighted weights, means and covariances
+    ///
+    /// A component that has lost all its mass keeps its previous parameters.
+    fn maximize(&mut self, rows: &DMatrix<f64>, responsibilities: &DMatrix<f64>, config: &MixtureConfig) {
+        let (n, d) = (rows.nrows(), rows.ncols());
+        let mut components = Vec::with_capacity(responsibilities.ncols());
+
+        for k in 0..responsibilities.ncols() {
+            let column = responsibilities.column(k);
+            let mass: f64 = column.sum();
+            if mass < 1e-10 {
+                if let Some(previous) = self.components.get(k) {
+                    components.push(GaussianComponent { weight: 0.0, ..previous.clone() });
+                    continue;
+                }
+            }
+            let mass = mass.max(1e-10);
+
+            let mean = rows.tr_mul(&column) / mass;
+            let mut scatter = DMatrix::zeros(d, d);
+            for row in 0..n {
+                let centred = rows.row(row).transpose() - &mean;
+                scatter += column[row] * &centred * centred.transpose();
+            }
+            scatter /= mass;
+
+            let mut covariance = match config.covariance {
+                CovarianceType::Full => scatter,
+                CovarianceType::Diagonal => DMatrix::from_diagonal(&scatter.diagonal()),
+                CovarianceType::Spherical => DMatrix::identity(d, d) * (scatter.trace() / d as f64),
+            };
+            for i in 0..d {
+                covariance[(i, i)] += config.regularization;
+            }
+
+            components.push(GaussianComponent {
+                id: self.components.get(k).map_or_else(Uuid::new_v4, |c| c.id),
+                weight: mass / n as f64,
+                mean,
+                covariance,
+            });
+        }
+        self.components = components;
+    }
+}
+
+fn log_sum_exp(values: &[f64]) -> f64 {
+    let max = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    if !max.is_finite() {
+        return max;
+    }
+    max + values.iter().map(|v| (v - max).exp()).sum::<f64>().ln()
+}
+
+/// Quantile of the standard normal distribution (Acklam's rational approximation)
+fn normal_quantile(p: f64) -> f64 {
+    const A: [f64; 6] = [-3.969683028665376e1, 2.209460984245205e2, -2.759285104469687e2,
+        1.38357751867269e2, -3.066479806614716e1, 2.506628277459239];
+    const B: [f64; 5] = [-5.447609879822406e1, 1.615858368580409e2, -1.556989798598866e2,
+        6.680131188771972e1, -1.328068155288572e1];
+    const C: [f64; 6] = [-7.784894002430293e-3, -3.223964580411365e-1, -2.400758277161838,
+        -2.549732539343734, 4.374664141464968, 2.938163982698783];
+    const D: [f64; 4] = [7.784695709041462e-3, 3.224671290700398e-1, 2.445134137142996,
+        3.754408661907416];
+    let p = p.clamp(1e-15, 1.0 - 1e-15);
+    let tail = |q: f64| {
+        (((((C[0] * q + C[1]) * q + C[2]) * q + C[3]) * q + C[4]) * q + C[5])
+            / ((((D[0] * q + D[1]) * q + D[2]) * q + D[3]) * q + 1.0)
+    };
+
+    if p < 0.02425 {
+        tail((-2.0 * p.ln()).sqrt())
+    } else if p > 1.0 - 0.02425 {
+        -tail((-2.0 * (1.0 - p).ln()).sqrt())
+    } else {
+        let q = p - 0.5;
+        let r = q * q;
+        (((((A[0] * r + A[1]) * r + A[2]) * r + A[3]) * r + A[4]) * r + A[5]) * q
+            / (((((B[0] * r + B[1]) * r + B[2]) * r + B[3]) * r + B[4]) * r + 1.0)
+    }
+}
+
+/// Quantile of the chi-squared distribution
+///
+/// Exact for one and two degrees of freedom, Wilson–Hilferty otherwise.
+fn chi_squared_quantile(degrees: usize, p: f64) -> f64 {
+    match degrees {
+        0 => 0.0,
+        1 => normal_quantile(0.5 + p / 2.0).powi(2),
+        2 => -2.0 * (1.0 - p.clamp(0.0, 1.0 - 1e-15)).ln(),
+        _ => {
+            let k = degrees as f64;
+            let h = 2.0 / (9.0 * k);
+            (k * (1.0 - h + normal_quantile(p) * h.sqrt()).powi(3)).max(0.0)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sampling::SplitMix64;
+    use crate::{ConceptualMetric, ConceptualReasoning, DimensionId};
+
+    /// Two elongated Gaussian blobs drawn with Box–Muller
+    fn two_blobs() -> ConceptualSpace {
+        let dims = vec![DimensionId::new(), DimensionId::new()];
+        let map: HashMap<_, _> = dims.iter().enumerate().map(|(i, d)| (*d, i)).collect();
+        let mut space = ConceptualSpace::new("blobs".to_string(), dims, ConceptualMetric::uniform(2, 2.0));
+        let mut rng = SplitMix64::new(42);
+        let mut gaussian = || {
+            let (u, v) = (rng.next_f64().max(1e-12), rng.next_f64());
+            (-2.0 * u.ln()).sqrt() * (2.0 * PI * v).cos()
+        };
+        for (centre, spread) in [((0.0, 0.0), (2.0, 0.3)), ((0.0, 6.0), (0.3, 1.0))] {
+            for _ in 0..80 {
+                let x = centre.0 + spread.0 * gaussian();
+                let y = centre.1 + spread.1 * gaussian();
+                space.add_point(ConceptualPoint::new(vec![x, y], map.clone())).unwrap();
+            }
+        }
+        space
+    }
+
+    #[test]
+    fn test_chi_squared_quantiles() {
+        assert!((chi_squared_quantile(1, 0.95) - 3.841).abs() < 1e-3);
+        assert!((chi_squared_quantile(2, 0.95) - 5.991).abs() < 1e-3);
+        assert!((chi_squared_quantile(5, 0.95) - 11.070).abs() < 0.05);
+    }
+
+    #[test]
+    fn test_em_recovers_components_and_bic_selects_two() {
+        let space = two_blobs();
+        let selection = GaussianMixture::select(&space, 1..=4, &MixtureConfig::default()).unwrap();
+        assert_eq!(selection.bic.len(), 4);
+
+        let mixture = selection.mixture;
+        assert_eq!(mixture.components.len(), 2);
+        assert!(mixture.converged);
+        let mut means: Vec<f64> = mixture.components.iter().map(|c| c.mean[1]).collect();
+        means.sort_by(f64::total_cmp);
+        assert!(means[0].abs() < 0.3 && (means[1] - 6.0).abs() < 0.3);
+
+        for posteriors in mixture.memberships(&space).unwrap().values() {
+            assert!((posteriors.iter().sum::<f64>() - 1.0).abs() < 1e-9);
+        }
+
+        let regions = mixture.regions(&space, 0.99).unwrap();
+        for region in &regions {
+            assert!(region.contains(&region.prototype));
+            assert!(region.member_points.len() >= 75);
+            assert!(region.member_points.iter().all(|id| region.contains(&space.points.get(id).unwrap())));
+        }
+    }
+
+    #[test]
+    fn test_covariance_shapes_change_parameter_count() {
+        let space = two_blobs();
+        let count = |covariance| {
+            let config = MixtureConfig { covariance, ..MixtureConfig::default() };
+            GaussianMixture::fit(&space, 2, &config).unwrap().parameter_count()
+        };
+        assert_eq!(count(CovarianceType::Full), 11);
+        assert_eq!(count(CovarianceType::Diagonal), 9);
+        assert_eq!(count(CovarianceType::Spherical), 7);
+    }
+
+    #[test]
+    fn test_reasoning_reports_mixture_posteriors() {
+        let mut space = two_blobs();
+        let mixture = GaussianMixture::fit(&space, 2, &MixtureConfig::default()).unwrap();
+        for region in mixture.regions(&space, 0.95).unwrap() {
+            space.add_region(region).unwrap();
+        }
+
+        let mut reasoning = ConceptualReasoning::new(DistanceMetric::Euclidean).with_mixture(mixture);
+        let probe = ConceptualPoint::new(vec![0.0, 5.5], space.points.dimension_map().clone());
+        let inference = reasoning.categorical_inference(&probe, &space).unwrap();
+
+        let total: f64 = inference.category_memberships.iter().map(|m| m.membership_strength).sum();
+        assert!((total - 1.0).abs() < 1e-9);
+        let strongest = &inference.category_memberships[0];
+        assert!(strongest.membership_strength > 0.99);
+        assert!(space.regions[&strongest.category_id].prototype.coordinates[1] > 5.0);
+    }
+}
@@ -0,0 +1,515 @@
+//! Online category learning from a stream of concepts
+//!
+//! Batch category formation has to see every point up front. The online
+//! learner instead takes concepts one at a time, as `ConceptAdded` events
+//! arrive: the nearest prototype wins the concept and moves towards it
+//! (online k-means / competitive learning), a concept far from every
+//! prototype founds a new category, a category whose spread grows too large
+//! splits in two, and categories whose prototypes converge merge. Every
+//! change is returned as domain events so read models can follow along.
+
+use crate::events::{
+    CategoryFormed, CategoryRemoved, ConceptCategorized, ConceptUncategorized,
+    ConceptualSpaceDomainEvent,
+};
+use crate::partitioning::{bisector_weights, voronoi_cell};
+use crate::{
+    ConceptualError, ConceptualPoint, ConceptualResult, ConceptualSpaceId, ConvexRegion,
+    DimensionId, DistanceMetric,
+};
+use nalgebra::{DMatrix, DVector};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// Lloyd iterations refining a split
+const SPLIT_ITERATIONS: usize = 10;
+
+/// How a winning prototype moves towards a new concept
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum PrototypeUpdate {
+    /// Step 1/n, keeping the prototype at the mean of its concepts (online k-means)
+    RunningMean,
+
+    /// Fixed step, so recent concepts weigh more (competitive learning)
+    Constant(f64),
+}
+
+/// Settings for the online learner
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct OnlineLearningConfig {
+    /// Prototype update rule
+    pub update: PrototypeUpdate,
+
+    /// Distance beyond which a concept founds a new category
+    pub novelty_distance: f64,
+
+    /// Mean squared distance to the category mean above which it splits
+    pub split_variance: f64,
+
+    /// Fewest members a category needs before it may split
+    pub min_split_size: usize,
+
+    /// Prototype distance below which two categories merge
+    pub merge_distance: f64,
+}
+
+impl Default for OnlineLearningConfig {
+    fn default() -> Self {
+        Self {
+            update: PrototypeUpdate::RunningMean,
+            novelty_distance: 2.0,
+            split_variance: 1.0,
+            min_split_size: 8,
+            merge_distance: 0.5,
+        }
+    }
+}
+
+/// A category maintained by the online learner
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OnlineCategory {
+    /// Region id of the category
+    pub id: Uuid,
+
+    /// Current prototype
+    pub prototype: DVector<f64>,
+
+    /// Member concepts and the coordinates they arrived with
+    pub members: Vec<(Uuid, DVector<f64>)>,
+}
+
+impl OnlineCategory {
+    fn founded_by(concept_id: Uuid, coordinates: DVector<f64>) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            prototype: coordinates.clone(),
+            members: vec![(concept_id, coordinates)],
+        }
+    }
+
+    fn from_members(members: Vec<(Uuid, DVector<f64>)>) -> Self {
+        let mean = members.iter()
+            .fold(DVector::zeros(members[0].1.len()), |sum, (_, c)| sum + c)
+            / members.len() as f64;
+        Self { id: Uuid::new_v4(), prototype: mean, members }
+    }
+
+    fn member_rows(&self) -> DMatrix<f64> {
+        DMatrix::from_fn(self.members.len(), self.prototype.len(), |i, j| self.members[i].1[j])
+    }
+}
+
+/// Incremental category learner over a stream of concepts
+#[derive(Debug, Clone)]
+pub struct OnlineCategoryLearner {
+    space_id: ConceptualSpaceId,
+    metric: DistanceMetric,
+    config: OnlineLearningConfig,
+    categories: Vec<OnlineCategory>,
+    dimension_map: HashMap<DimensionId, usize>,
+}
+
+impl OnlineCategoryLearner {
+    /// Create a learner for a space with default settings
+    pub fn new(space_id: ConceptualSpaceId, metric: DistanceMetric) -> Self {
+        Self {
+            space_id,
+            metric,
+            config: OnlineLearningConfig::default(),
+            categories: Vec::new(),
+            dimension_map: HashMap::new(),
+        }
+    }
+
+    /// Configure thresholds and the update rule
+    pub fn with_config(mut self, config: OnlineLearningConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Current categories
+    pub fn categories(&self) -> &[OnlineCategory] {
+        &self.categories
+    }
+
+    /// Regions of all categories, bounded by their prototypes' Voronoi cells
+    pub fn regions(&self) -> Vec<ConvexRegion> {
+        (0..self.categories.len()).map(|i| self.region(i)).collect()
+    }
+
+    /// Learn from a domain event; only `ConceptAdded` for this space counts
+    pub fn apply_event(&mut self, event: &ConceptualSpaceDomainEvent) -> ConceptualResult<Vec<ConceptualSpaceDomainEvent>> {
+        match event {
+            ConceptualSpaceDomainEvent::ConceptAdded(added) if added.space_id == self.space_id => {
+                self.observe(added.concept_id, &added.point)
+            }
+            _ => Ok(Vec::new()),
+        }
+    }
+
+    /// Learn from one concept, returning the category changes it caused
+    ///
+    /// A concept seen before is first taken out of its old category, which
+    /// is reported with its corrected prototype or removed if left empty.
+    ///
+    /// ```mermaid
+    /// graph TD
+    ///     A[Concept] --> B{Nearest Prototype Within Novelty Distance?}
+    ///     B -->|no| C[Form New Category]
+    ///     B -->|yes| D[Move Prototype Towards Concept]
+    ///     D --> E{Variance Above Threshold?}
+    ///     E -->|yes| F[Split Along Principal Axis]
+    ///     E -->|no| G{Prototype Near Another?}
+    ///     G -->|yes| H[Merge Categories]
+    /// ```
+    pub fn observe(&mut self, concept_id: Uuid, point: &ConceptualPoint) -> ConceptualResult<Vec<ConceptualSpaceDomainEvent>> {
+        let coordinates = point.coordinates.clone();
+        if let Some(first) = self.categories.first() {
+            if first.prototype.len() != coordinates.len() {
+                return Err(ConceptualError::InvalidPoint(
+                    "Points have different dimensions".to_string()
+                ));
+            }
+        } else if self.dimension_map.is_empty() {
+            self.dimension_map = point.dimension_map.clone();
+        }
+        let mut events = self.withdraw(concept_id);
+
+        let nearest = self.nearest(&coordinates, None)?;
+        let winner = match nearest {
+            Some((index, distance)) if distance <= self.config.novelty_distance => index,
+            _ => {
+                self.categories.push(OnlineCategory::founded_by(concept_id, coordinates));
+                events.push(self.formed(self.categories.len() - 1, Vec::new(), "Concept far from every prototype"));
+                return Ok(events);
+            }
+        };
+
+        let category = &mut self.categories[winner];
+        category.members.push((concept_id, coordinates.clone()));
+        let step = match self.config.update {
+            PrototypeUpdate::RunningMean => 1.0 / category.members.len() as f64,
+            PrototypeUpdate::Constant(rate) => rate,
+        };
+        category.prototype += (&coordinates - &category.prototype) * step;
+
+        events.push(ConceptualSpaceDomainEvent::ConceptCategorized(ConceptCategorized {
+            space_id: self.space_id,
+            concept_id,
+            region_id: category.id,
+            prototype: self.prototype_point(winner),
+        }));
+
+        if let Some(split) = self.try_split(winner)? {
+            events.extend(split);
+        } else if let Some(merge) = self.try_merge(winner)? {
+            events.extend(merge);
+        }
+        Ok(events)
+    }
+
+    /// Take a previously seen concept out of its category
+    ///
+    /// Under the running mean the concept's contribution is subtracted from
+    /// the prototype; a constant step has already blended it in for good.
+    /// The old category reports its new prototype in a
+    /// `ConceptUncategorized`, or its removal if the concept was its last
+    /// member.
+    fn withdraw(&mut self, concept_id: Uuid) -> Vec<ConceptualSpaceDomainEvent> {
+        let found = self.categories.iter().enumerate().find_map(|(index, category)| {
+            category.members.iter().position(|(id, _)| *id == concept_id).map(|position| (index, position))
+        });
+        let Some((index, position)) = found else {
+            return Vec::new();
+        };
+
+        let category = &mut self.categories[index];
+        let (_, coordinates) = category.members.remove(position);
+        if category.members.is_empty() {
+            let removed = self.categories.remove(index);
+            return vec![self.removed(removed.id, "Its only concept was observed again")];
+        }
+        if self.config.update == PrototypeUpdate::RunningMean {
+            let remaining = category.members.len() as f64;
+            category.prototype = (&category.prototype * (remaining + 1.0) - coordinates) / remaining;
+        }
+
+        vec![ConceptualSpaceDomainEvent::ConceptUncategorized(ConceptUncategorized {
+            space_id: self.space_id,
+            concept_id,
+            region_id: self.categories[index].id,
+            prototype: self.prototype_point(index),
+        })]
+    }
+
+    /// Split a category along its principal axis if it has grown too wide
+    fn try_split(&mut self, index: usize) -> ConceptualResult<Option<Vec<ConceptualSpaceDomainEvent>>> {
+        let category = &self.categories[index];
+        if category.members.len() < self.config.min_split_size.max(2) {
+            return Ok(None);
+        }
+
+        let rows = category.member_rows();
+        let mean = rows.row_mean().transpose();
+        let spread = self.metric.distances_to_rows(&mean, rows.rows(0, rows.nrows()))?;
+        if spread.map(|d| d * d).mean() <= self.config.split_variance {
+            return Ok(None);
+        }
+
+        // Seed the halves by side of the principal axis, then refine as 2-means
+        let centred = DMatrix::from_fn(rows.nrows(), rows.ncols(), |i, j| rows[(i, j)] - mean[j]);
+        let eigen = (centred.transpose() * &centred).symmetric_eigen();
+        let principal = eigen.eigenvalues.imax();
+        let axis = eigen.eigenvectors.column(principal);
+        let mut side: Vec<bool> = (0..rows.nrows()).map(|i| centred.row(i).dot(&axis.transpose()) >= 0.0).collect();
+
+        for _ in 0..SPLIT_ITERATIONS {
+            let centre = |half: bool| {
+                let chosen: Vec<usize> = (0..side.len()).filter(|&i| side[i] == half).collect();
+                chosen.iter().fold(DVector::zeros(rows.ncols()), |sum, &i| sum + rows.row(i).transpose())
+                    / chosen.len().max(1) as f64
+            };
+            let (a, b) = (centre(true), centre(false));
+            let to_a = self.metric.distances_to_rows(&a, rows.rows(0, rows.nrows()))?;
+            let to_b = self.metric.distances_to_rows(&b, rows.rows(0, rows.nrows()))?;
+            let next: Vec<bool> = (0..side.len()).map(|i| to_a[i] <= to_b[i]).collect();
+            if next == side {
+                break;
+            }
+            side = next;
+        }
+        if side.iter().all(|&s| s) || side.iter().all(|&s| !s) {
+            return Ok(None);
+        }
+
+        let removed = self.categories.remove(index);
+        let (left, right): (Vec<_>, Vec<_>) = removed.members.into_iter()
+            .zip(side)
+            .partition(|(_, s)| *s);
+        let halves = [left, right].map(|half| OnlineCategory::from_members(half.into_iter().map(|(m, _)| m).collect()));
+        self.categories.extend(halves);
+
+        let mut events = vec![self.removed(removed.id, "Split after its variance exceeded the threshold")];
+        for new in [self.categories.len() - 2, self.categories.len() - 1] {
+            events.push(self.formed(new, vec![removed.id], "Split along the principal axis"));
+        }
+        Ok(Some(events))
+    }
+
+    /// Merge a category with another whose prototype has come too close
+    fn try_merge(&mut self, index: usize) -> ConceptualResult<Option<Vec<ConceptualSpaceDomainEvent>>> {
+        let prototype = self.categories[index].prototype.clone();
+        let Some((other, distance)) = self.nearest(&prototype, Some(index))? else {
+            return Ok(None);
+        };
+        if distance > self.config.merge_distance {
+            return Ok(None);
+        }
+
+        let (first, second) = (index.max(other), index.min(other));
+        let a = self.categories.remove(first);
+        let b = self.categories.remove(second);
+        let (na, nb) = (a.members.len() as f64, b.members.len() as f64);
+        let mut merged = OnlineCategory {
+            id: Uuid::new_v4(),
+            prototype: (&a.prototype * na + &b.prototype * nb) / (na + nb).max(1.0),
+            members: a.members,
+        };
+        merged.members.extend(b.members);
+        self.categories.push(merged);
+
+        let reason = "Prototypes converged within the merge distance";
+        Ok(Some(vec![
+            self.removed(a.id, reason),
+            self.removed(b.id, reason),
+            self.formed(self.categories.len() - 1, vec![a.id, b.id], "Merged from two converged categories"),
+        ]))
+    }
+
+    /// Nearest prototype, optionally skipping one category
+    fn nearest(&self, coordinates: &DVector<f64>, skip: Option<usize>) -> ConceptualResult<Option<(usize, f64)>> {
+        if self.categories.is_empty() {
+            return Ok(None);
+        }
+        let prototypes = DMatrix::from_fn(self.categories.len(), coordinates.len(), |i, j| {
+            self.categories[i].prototype[j]
+        });
+        let distances = self.metric.distances_to_rows(coordinates, prototypes.rows(0, prototypes.nrows()))?;
+        Ok(distances.iter()
+            .enumerate()
+            .filter(|(i, _)| Some(*i) != skip)
+            .min_by(|a, b| a.1.total_cmp(b.1))
+            .map(|(i, d)| (i, *d)))
+    }
+
+    fn prototype_point(&self, index: usize) -> ConceptualPoint {
+        ConceptualPoint::new(self.categories[index].prototype.as_slice().to_vec(), self.dimension_map.clone())
+    }
+
+    fn region(&self, index: usize) -> ConvexRegion {
+        let prototypes: Vec<DVector<f64>> = self.categories.iter().map(|c| c.prototype.clone()).collect();
+        let weights = bisector_weights(&self.metric, prototypes[index].len());
+        let category = &self.categories[index];
+        ConvexRegion {
+            id: category.id,
+            boundaries: voronoi_cell(&prototypes, index, &weights),
+            member_points: category.members.iter().map(|(id, _)| *id).collect(),
+            ..ConvexRegion::from_prototype(self.prototype_point(index))
+        }
+        .with_name(format!("Learned Category {}", category.id))
+        .with_description("Voronoi cell of an incrementally learned prototype".to_string())
+    }
+
+    fn formed(&self, index: usize, derived_from: Vec<Uuid>, reason: &str) -> ConceptualSpaceDomainEvent {
+        ConceptualSpaceDomainEvent::CategoryFormed(CategoryFormed {
+            space_id: self.space_id,
+            region: self.region(index),
+            derived_from,
+            reason: reason.to_string(),
+        })
+    }
+
+    fn removed(&self, region_id: Uuid, reason: &str) -> ConceptualSpaceDomainEvent {
+        ConceptualSpaceDomainEvent::CategoryRemoved(CategoryRemoved {
+            space_id: self.space_id,
+            region_id,
+            reason: reason.to_string(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ConceptAdded;
+
+    fn added(space_id: ConceptualSpaceId, coordinates: Vec<f64>) -> ConceptualSpaceDomainEvent {
+        let concept_id = Uuid::new_v4();
+        ConceptualSpaceDomainEvent::ConceptAdded(ConceptAdded {
+            space_id,
+            concept_id,
+            point: ConceptualPoint { id: Some(concept_id), ..ConceptualPoint::new(coordinates, HashMap::new()) },
+        })
+    }
+
+    fn kinds(events: &[ConceptualSpaceDomainEvent]) -> Vec<&'static str> {
+        use cim_domain::DomainEvent;
+        events.iter().map(|e| e.event_type()).collect()
+    }
+
+    #[test]
+    fn test_stream_forms_one_category_per_cluster() {
+        let space_id = ConceptualSpaceId::new();
+        let mut learner = OnlineCategoryLearner::new(space_id, DistanceMetric::Euclidean);
+        let mut events = Vec::new();
+        for round in 0..5 {
+            let jitter = 0.05 * round as f64;
+            for centre in [0.0, 10.0, 20.0] {
+                events.extend(learner.apply_event(&added(space_id, vec![centre + jitter, -jitter])).unwrap());
+            }
+        }
+        // Events for other spaces are ignored
+        assert!(learner.apply_event(&added(ConceptualSpaceId::new(), vec![5.0, 5.0])).unwrap().is_empty());
+
+        assert_eq!(learner.categories().len(), 3);
+        let kinds = kinds(&events);
+        assert_eq!(kinds.iter().filter(|k| **k == "CategoryFormed").count(), 3);
+        assert_eq!(kinds.iter().filter(|k| **k == "ConceptCategorized").count(), 12);
+
+        let first = &learner.categories()[0];
+        assert!((first.prototype[0] - 0.1).abs() < 1e-12);
+        for region in learner.regions() {
+            assert_eq!(region.member_points.len(), 5);
+            assert!(region.contains(&region.prototype));
+        }
+    }
+
+    #[test]
+    fn test_wide_category_splits() {
+        let space_id = ConceptualSpaceId::new();
+        let config = OnlineLearningConfig { novelty_distance: 100.0, ..OnlineLearningConfig::default() };
+        let mut learner = OnlineCategoryLearner::new(space_id, DistanceMetric::Euclidean).with_config(config);
+
+        let mut events = Vec::new();
+        for i in 0..6 {
+            let offset = 0.01 * i as f64;
+            events.extend(learner.apply_event(&added(space_id, vec![offset, 0.0])).unwrap());
+            events.extend(learner.apply_event(&added(space_id, vec![6.0 + offset, 0.0])).unwrap());
+        }
+
+        assert_eq!(learner.categories().len(), 2);
+        let split_from = events.iter().find_map(|e| match e {
+            ConceptualSpaceDomainEvent::CategoryRemoved(removed) => Some(removed.region_id),
+            _ => None,
+        });
+        let derived = events.iter()
+            .filter(|e| matches!(e, ConceptualSpaceDomainEvent::CategoryFormed(f) if f.derived_from == vec![split_from.unwrap()]))
+            .count();
+        assert_eq!(derived, 2);
+        let mut prototypes: Vec<f64> = learner.categories().iter().map(|c| c.prototype[0]).collect();
+        prototypes.sort_by(f64::total_cmp);
+        assert!(prototypes[0] < 0.1 && prototypes[1] > 5.9);
+    }
+
+    #[test]
+    fn test_converging_prototypes_merge() {
+        let space_id = ConceptualSpaceId::new();
+        let config = OnlineLearningConfig {
+            update: PrototypeUpdate::Constant(0.5),
+            novelty_distance: 1.5,
+            merge_distance: 1.0,
+            ..OnlineLearningConfig::default()
+        };
+        let mut learner = OnlineCategoryLearner::new(space_id, DistanceMetric::Euclidean).with_config(config);
+
+        learner.apply_event(&added(space_id, vec![0.0])).unwrap();
+        learner.apply_event(&added(space_id, vec![2.0])).unwrap();
+        assert_eq!(learner.categories().len(), 2);
+
+        // Concepts between the two pull the second prototype towards the first
+        let mut events = Vec::new();
+        for x in [1.1, 1.2, 0.9, 0.8] {
+            assert_eq!(learner.categories().len(), 2);
+            events = learner.apply_event(&added(space_id, vec![x])).unwrap();
+        }
+
+        assert_eq!(learner.categories().len(), 1);
+        assert_eq!(learner.categories()[0].members.len(), 6);
+        assert_eq!(kinds(&events), ["ConceptCategorized", "CategoryRemoved", "CategoryRemoved", "CategoryFormed"]);
+    }
+
+    #[test]
+    fn test_reobserved_concept_leaves_its_old_category() {
+        let space_id = ConceptualSpaceId::new();
+        let mut learner = OnlineCategoryLearner::new(space_id, DistanceMetric::Euclidean);
+        let point = |x: f64| ConceptualPoint::new(vec![x], HashMap::new());
+        let (first, second) = (Uuid::new_v4(), Uuid::new_v4());
+
+        learner.observe(first, &point(0.0)).unwrap();
+        learner.observe(second, &point(1.0)).unwrap();
+        let old = learner.categories()[0].id;
+        assert!((learner.categories()[0].prototype[0] - 0.5).abs() < 1e-12);
+
+        // The old prototype falls back to the mean of the remaining member
+        let events = learner.observe(first, &point(10.0)).unwrap();
+        assert_eq!(kinds(&events), ["ConceptUncategorized", "CategoryFormed"]);
+        match &events[0] {
+            ConceptualSpaceDomainEvent::ConceptUncategorized(left) => {
+                assert_eq!((left.concept_id, left.region_id), (first, old));
+                assert!((left.prototype.coordinates[0] - 1.0).abs() < 1e-12);
+            }
+            other => panic!("unexpected event {other:?}"),
+        }
+        assert_eq!(learner.categories().len(), 2);
+        assert_eq!(learner.categories()[0].members.len(), 1);
+
+        // Moving the last member away removes the emptied category
+        let events = learner.observe(second, &point(10.5)).unwrap();
+        assert_eq!(kinds(&events), ["CategoryRemoved", "ConceptCategorized"]);
+        assert!(matches!(&events[0], ConceptualSpaceDomainEvent::CategoryRemoved(r) if r.region_id == old));
+        assert_eq!(learner.categories().len(), 1);
+        assert_eq!(learner.categories()[0].members.len(), 2);
+        assert!((learner.categories()[0].prototype[0] - 10.25).abs() < 1e-12);
+    }
+}
@@ -0,0 +1,782 @@
+//! Partitioning category formation: k-means and k-medoids
+//!
+//! Classic centroid- and medoid-based clustering over the points of a
+//! space. Every partition is returned as convex regions: each cluster's
+//! prototype is its centroid or medoid, and its boundaries are the
+//! bisectors against every other prototype, i.e. the induced Voronoi cell.
+//!
+//! Bisectors are only hyperplanes for (weighted) Euclidean metrics. Under
+//! Manhattan, other Minkowski exponents, cosine or custom metrics the
+//! Euclidean cell would disagree with the metric's own labels, so each
+//! cluster is bounded by the convex hull of its members instead. Those hulls
+//! contain every member but need not tile the space.
+
+use crate::sampling::SplitMix64;
+use crate::{
+    CategoryFormation, ConceptualError, ConceptualPoint, ConceptualResult, ConceptualSpace,
+    ConvexRegion, DistanceMetric, Hyperplane,
+};
+use nalgebra::{DMatrix, DVector};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::ops::RangeInclusive;
+use std::sync::OnceLock;
+use uuid::Uuid;
+
+/// Maximum PAM swap rounds before giving up on further improvement
+const PAM_MAX_SWAPS: usize = 100;
+
+/// Settings for Lloyd's k-means
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct KMeansConfig {
+    /// Maximum Lloyd iterations per restart
+    pub max_iterations: usize,
+
+    /// Largest centroid movement at which iteration stops
+    pub tolerance: f64,
+
+    /// Independent k-means++ seedings; the lowest inertia wins
+    pub restarts: usize,
+
+    /// Seed of the random generator
+    pub seed: u64,
+}
+
+impl Default for KMeansConfig {
+    fn default() -> Self {
+        Self {
+            max_iterations: 300,
+            tolerance: 1e-9,
+            restarts: 4,
+            seed: 0x5EED,
+        }
+    }
+}
+
+/// How medoids are searched for
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub enum MedoidMethod {
+    /// Partitioning Around Medoids over the full distance matrix
+    #[default]
+    Pam,
+
+    /// PAM on random subsamples, keeping the medoids best on the full set
+    Clara {
+        /// Number of subsamples drawn
+        samples: usize,
+        /// Points per subsample
+        sample_size: usize,
+        /// Seed of the random generator
+        seed: u64,
+    },
+}
+
+/// Partitioning algorithm used when selecting k
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum PartitionAlgorithm {
+    /// Centroids minimising squared distance
+    KMeans(KMeansConfig),
+
+    /// Medoids minimising distance, for any metric
+    KMedoids(MedoidMethod),
+}
+
+/// Criterion for choosing the number of clusters
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum KSelection {
+    /// Knee of the cost curve: the point farthest from the chord joining its ends
+    Elbow,
+
+    /// Highest mean silhouette width
+    Silhouette,
+}
+
+/// A partition of a space's points into k clusters
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Partition {
+    /// Point ids, in the order of `labels`
+    pub ids: Vec<Uuid>,
+
+    /// Cluster index of each point
+    pub labels: Vec<usize>,
+
+    /// Centroid or medoid of each cluster
+    pub prototypes: Vec<ConceptualPoint>,
+
+    /// Inertia (sum of squared distances) for k-means, total distance for k-medoids
+    pub cost: f64,
+
+    /// One region per cluster, a Voronoi cell or member hull (see module docs)
+    pub regions: Vec<ConvexRegion>,
+
+    /// Position of each id in `ids`, built on first lookup
+    #[serde(skip)]
+    positions: OnceLock<HashMap<Uuid, usize>>,
+}
+
+impl Partition {
+    /// Number of clusters
+    pub fn k(&self) -> usize {
+        self.prototypes.len()
+    }
+
+    /// Cluster of a point
+    pub fn cluster_of(&self, id: &Uuid) -> Option<usize> {
+        let positions = self.positions.get_or_init(|| {
+            self.ids.iter().enumerate().map(|(i, id)| (*id, i)).collect()
+        });
+        positions.get(id).map(|&i| self.labels[i])
+    }
+
+    /// Ids of the points in a cluster
+    pub fn members(&self, cluster: usize) -> Vec<Uuid> {
+        self.ids.iter()
+            .zip(&self.labels)
+            .filter(|(_, &label)| label == cluster)
+            .map(|(id, _)| *id)
+            .collect()
+    }
+}
+
+/// Outcome of choosing k
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KSelectionResult {
+    /// The partition at the chosen k
+    pub partition: Partition,
+
+    /// Criterion value per candidate k: cost for the elbow, mean width for silhouette
+    pub scores: Vec<(usize, f64)>,
+}
+
+/// Row-ordered ids and coordinate matrix of a space's points
+pub(crate) fn point_rows(space: &ConceptualSpace) -> (Vec<Uuid>, DMatrix<f64>) {
+    let ids = space.points.ids().to_vec();
+    let rows = match space.points.matrix() {
+        Some(matrix) => matrix.into_owned(),
+        None => {
+            let dimensions = space.points.dimensions();
+            let mut rows = DMatrix::zeros(ids.len(), dimensions);
+            for (row, point) in space.points.values().enumerate() {
+                rows.row_mut(row).tr_copy_from(&point.coordinates);
+            }
+            rows
+        }
+    };
+    (ids, rows)
+}
+
+/// Distances from `query` to every row
+fn distances(metric: &DistanceMetric, query: &DVector<f64>, rows: &DMatrix<f64>) -> ConceptualResult<DVector<f64>> {
+    metric.distances_to_rows(query, rows.rows(0, rows.nrows()))
+}
+
+/// Full pairwise distance matrix of a set of rows
+pub(crate) fn distance_matrix(metric: &DistanceMetric, rows: &DMatrix<f64>) -> ConceptualResult<DMatrix<f64>> {
+    let n = rows.nrows();
+    let mut matrix = DMatrix::zeros(n, n);
+    for i in 0..n {
+        let column = distances(metric, &rows.row(i).transpose(), rows)?;
+        matrix.set_column(i, &column);
+    }
+    Ok(matrix)
+}
+
+fn check_k(k: usize, n: usize) -> ConceptualResult<()> {
+    if k == 0 || k > n {
+        return Err(ConceptualError::InvalidPoint(format!(
+            "Cannot partition {n} points into {k} clusters"
+        )));
+    }
+    Ok(())
+}
+
+/// Whether points equidistant from two prototypes lie on a hyperplane
+pub(crate) fn flat_bisectors(metric: &DistanceMetric, dimensions: usize) -> bool {
+    match metric {
+        DistanceMetric::Euclidean => true,
+        DistanceMetric::WeightedEuclidean { weights } => weights.len() == dimensions,
+        DistanceMetric::WeightedMinkowski { weights, p } => *p == 2.0 && weights.len() == dimensions,
+        _ => false,
+    }
+}
+
+/// Per-dimension weights under which bisectors of the metric are hyperplanes
+///
+/// Exact when `flat_bisectors` holds; other metrics fall back to the
+/// Euclidean bisector.
+pub(crate) fn bisector_weights(metric: &DistanceMetric, dimensions: usize) -> DVector<f64> {
+    match metric {
+        DistanceMetric::WeightedEuclidean { weights } if weights.len() == dimensions => {
+            DVector::from_column_slice(weights)
+        }
+        DistanceMetric::WeightedMinkowski { weights, p }
+            if *p == 2.0 && weights.len() == dimensions =>
+        {
+            DVector::from_column_slice(weights)
+        }
+        _ => DVector::from_element(dimensions, 1.0),
+    }
+}
+
+/// Voronoi cell of prototype `own` as half-spaces facing it
+///
+/// x is at least as close to a as to b under Σ wᵢ(xᵢ − ·)² exactly when
+/// (w∘(a − b))·x ≥ ½ Σ wᵢ(aᵢ² − bᵢ²).
+pub(crate) fn voronoi_cell(prototypes: &[DVector<f64>], own: usize, weights: &DVector<f64>) -> Vec<Hyperplane> {
+    let a = &prototypes[own];
+    prototypes.iter()
+        .enumerate()
+        .filter(|&(other, b)| other != own && b != a)
+        .filter_map(|(_, b)| {
+            let normal = weights.component_mul(&(a - b));
+            let offset = 0.5 * weights.dot(&(a.component_mul(a) - b.component_mul(b)));
+            let norm = normal.norm();
+            (norm > 0.0).then(|| Hyperplane::new(normal / norm, offset / norm))
+        })
+        .collect()
+}
+
+/// Index and value of the nearest entry of a distance vector
+fn nearest(distances: impl Iterator<Item = f64>) -> (usize, f64) {
+    distances.enumerate().fold((0, f64::INFINITY), |best, (i, d)| {
+        if d < best.1 { (i, d) } else { best }
+    })
+}
+
+/// Greedy BUILD phase followed by best-improvement SWAP over a distance matrix
+///
+/// Returns medoid indices into the matrix and their total distance.
+fn pam(matrix: &DMatrix<f64>, k: usize) -> (Vec<usize>, f64) {
+    let n = matrix.nrows();
+
+    // BUILD: start from the most central point, then add whichever point
+    // most reduces the total distance
+    let first = nearest((0..n).map(|i| matrix.column(i).sum())).0;
+    let mut medoids = vec![first];
+    let mut closest: Vec<f64> = (0..n).map(|j| matrix[(j, first)]).collect();
+    while medoids.len() < k {
+        let gain = |candidate: usize| -> f64 {
+            (0..n).map(|j| (closest[j] - matrix[(j, candidate)]).max(0.0)).sum()
+        };
+        let candidate = (0..n)
+            .filter(|i| !medoids.contains(i))
+            .max_by(|&a, &b| gain(a).total_cmp(&gain(b)))
+            .expect("k does not exceed the number of points");
+        medoids.push(candidate);
+        for (j, best) in closest.iter_mut().enumerate() {
+            *best = best.min(matrix[(j, candidate)]);
+        }
+    }
+
+    // SWAP: with the nearest and second-nearest medoid of every point, the
+    // cost of replacing medoid m by h is a single pass over the points
+    for _ in 0..PAM_MAX_SWAPS {
+        let mut first_second = Vec::with_capacity(n);
+        for j in 0..n {
+            let mut best = (usize::MAX, f64::INFINITY);
+            let mut second = f64::INFINITY;
+            for (slot, &m) in medoids.iter().enumerate() {
+                let d = matrix[(j, m)];
+                if d < best.1 {
+                    second = best.1;
+                    best = (slot, d);
+                } else if d < second {
+                    second = d;
+                }
+            }
+            first_second.push((best.0, best.1, second));
+        }
+        let current: f64 = first_second.iter().map(|&(_, d, _)| d).sum();
+
+        let mut best_swap = None;
+        let mut best_cost = current;
+        for slot in 0..k {
+            for h in (0..n).filter(|h| !medoids.contains(h)) {
+                let cost: f64 = first_second.iter()
+                    .enumerate()
+                    .map(|(j, &(owner, d, second))| {
+                        let to_h = matrix[(j, h)];
+                        if owner == slot { second.min(to_h) } else { d.min(to_h) }
+                    })
+                    .sum();
+                if cost < best_cost - 1e-12 {
+                    best_cost = cost;
+                    best_swap = Some((slot, h));
+                }
+            }
+        }
+
+        match best_swap {
+            Some((slot, h)) => medoids[slot] = h,
+            None => return (medoids, current),
+        }
+    }
+
+    let cost = (0..n)
+        .map(|j| medoids.iter().map(|&m| matrix[(j, m)]).fold(f64::INFINITY, f64::min))
+        .sum();
+    (medoids, cost)
+}
+
+/// Pick `count` distinct indices out of `0..n`, keeping `keep` first
+fn subsample(rng: &mut SplitMix64, n: usize, count: usize, keep: &[usize]) -> Vec<usize> {
+    let mut pool: Vec<usize> = (0..n).filter(|i| !keep.contains(i)).collect();
+    let mut chosen = keep.to_vec();
+    while chosen.len() < count && !pool.is_empty() {
+        let pick = rng.below(pool.len());
+        chosen.push(pool.swap_remove(pick));
+    }
+    chosen
+}
+
+/// Index of the point farthest from the chord joining the ends of a curve
+///
+/// Both axes are rescaled to [0, 1] so the knee does not depend on units.
+fn knee(curve: &[(usize, f64)]) -> usize {
+    if curve.len() < 3 {
+        return 0;
+    }
+    let (first, last) = (curve[0], curve[curve.len() - 1]);
+    let x_span = (last.0 - first.0) as f64;
+    let (low, high) = curve.iter()
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), &(_, c)| (lo.min(c), hi.max(c)));
+    let y_span = if high > low { high - low } else { 1.0 };
+    let scaled = |&(k, c): &(usize, f64)| ((k - first.0) as f64 / x_span, (c - low) / y_span);
+
+    let (x0, y0) = scaled(&first);
+    let (x1, y1) = scaled(&last);
+    let (dx, dy) = (x1 - x0, y1 - y0);
+    let length = (dx * dx + dy * dy).sqrt().max(f64::EPSILON);
+    let offset = |point: &(usize, f64)| {
+        let (x, y) = scaled(point);
+        (dy * (x - x0) - dx * (y - y0)).abs() / length
+    };
+
+    (0..curve.len())
+        .max_by(|&a, &b| offset(&curve[a]).total_cmp(&offset(&curve[b])))
+        .unwrap_or(0)
+}
+
+/// Mean silhouette width of a labelling over a distance matrix
+///
+/// Points alone in their cluster score zero; a single cluster scores zero.
+fn silhouette(matrix: &DMatrix<f64>, labels: &[usize], k: usize) -> f64 {
+    let values = silhouette_values(matrix, labels, k);
+    if values.is_empty() {
+        return 0.0;
+    }
+    values.iter().sum::<f64>() / values.len() as f64
+}
+
+/// Silhouette of every point; all zero when there are fewer than two clusters
+pub(crate) fn silhouette_values(matrix: &DMatrix<f64>, labels: &[usize], k: usize) -> Vec<f64> {
+    let n = labels.len();
+    if k < 2 {
+        return vec![0.0; n];
+    }
+    let mut sizes = vec![0usize; k];
+    for &label in labels {
+        sizes[label] += 1;
+    }
+
+    (0..n)
+        .map(|i| {
+            let own = labels[i];
+            if sizes[own] < 2 {
+                return 0.0;
+            }
+            let mut sums = vec![0.0; k];
+            for j in 0..n {
+                sums[labels[j]] += matrix[(i, j)];
+            }
+            let a = sums[own] / (sizes[own] - 1) as f64;
+            let b = (0..k)
+                .filter(|&c| c != own && sizes[c] > 0)
+                .map(|c| sums[c] / sizes[c] as f64)
+                .fold(f64::INFINITY, f64::min);
+            if !b.is_finite() || a.max(b) == 0.0 {
+                0.0
+            } else {
+                (b - a) / a.max(b)
+            }
+        })
+        .collect()
+}
+
+/// Partition the space's points with k-means++ seeded Lloyd iterations
+pub(crate) fn k_means(formation: &CategoryFormation, space: &ConceptualSpace, k: usize, config: &KMeansConfig) -> ConceptualResult<Partition> {
+    let (ids, rows) = point_rows(space);
+    check_k(k, ids.len())?;
+    let metric = formation.metric();
+    let mut rng = SplitMix64::new(config.seed);
+
+    let mut best: Option<(Vec<DVector<f64>>, Vec<usize>, f64)> = None;
+    for _ in 0..config.restarts.max(1) {
+        let mut centroids = seed_centroids(formation, &rows, k, &mut rng)?;
+        let mut labels = vec![0; ids.len()];
+        let mut inertia = 0.0;
+
+        for _ in 0..config.max_iterations.max(1) {
+            let to_centroids = centroids.iter()
+                .map(|c| distances(metric, c, &rows))
+                .collect::<ConceptualResult<Vec<_>>>()?;
+            inertia = 0.0;
+            for (row, label) in labels.iter_mut().enumerate() {
+                let (cluster, d) = nearest(to_centroids.iter().map(|column| column[row]));
+                *label = cluster;
+                inertia += d * d;
+            }
+
+            let mut sums = vec![DVector::zeros(rows.ncols()); k];
+            let mut counts = vec![0usize; k];
+            for (row, &label) in labels.iter().enumerate() {
+                sums[label] += rows.row(row).transpose();
+                counts[label] += 1;
+            }
+
+            let mut shift: f64 = 0.0;
+            for cluster in 0..k {
+                let moved = if counts[cluster] > 0 {
+                    &sums[cluster] / counts[cluster] as f64
+                } else {
+                    // Re-seed an emptied cluster at the worst-served point
+                    let worst = (0..ids.len())
+                        .max_by(|&a, &b| {
+                            to_centroids[labels[a]][a].total_cmp(&to_centroids[labels[b]][b])
+                        })
+                        .unwrap_or(0);
+                    rows.row(worst).transpose()
+                };
+                shift = shift.max((&moved - &centroids[cluster]).norm());
+                centroids[cluster] = moved;
+            }
+
+            if shift <= config.tolerance {
+                break;
+            }
+        }
+
+        if best.as_ref().is_none_or(|(_, _, cost)| inertia < *cost) {
+            best = Some((centroids, labels, inertia));
+        }
+    }
+
+    let (centroids, labels, cost) = best.expect("at least one restart runs");
+    let prototypes = centroids.iter()
+        .map(|c| ConceptualPoint::new(c.as_slice().to_vec(), space.points.dimension_map().clone()))
+        .collect();
+    Ok(partition(formation, ids, &rows, labels, prototypes, cost, "k-means centroid"))
+}
+
+/// Partition the space's points around k medoids
+pub(crate) fn k_medoids(formation: &CategoryFormation, space: &ConceptualSpace, k: usize, method: MedoidMethod) -> ConceptualResult<Partition> {
+    let (ids, rows) = point_rows(space);
+    check_k(k, ids.len())?;
+    let metric = formation.metric();
+
+    let medoids = match method {
+        MedoidMethod::Pam => pam(&distance_matrix(metric, &rows)?, k).0,
+        MedoidMethod::Clara { samples, sample_size, seed } => {
+            let mut rng = SplitMix64::new(seed);
+            let sample_size = sample_size.clamp(k, ids.len());
+            let mut best: Option<(Vec<usize>, f64)> = None;
+
+            for _ in 0..samples.max(1) {
+                // Carry the best medoids so far into every later sample
+                let keep = best.as_ref().map(|(m, _)| m.clone()).unwrap_or_default();
+                let sample = subsample(&mut rng, ids.len(), sample_size, &keep);
+                let sub_rows = rows.select_rows(sample.iter());
+                let (local, _) = pam(&distance_matrix(metric, &sub_rows)?, k);
+                let medoids: Vec<usize> = local.iter().map(|&i| sample[i]).collect();
+
+                let centres: Vec<_> = medoids.iter().map(|&m| rows.row(m).transpose()).collect();
+                let cost = assign(formation, &rows, &centres)?.1;
+                if best.as_ref().is_none_or(|(_, c)| cost < *c) {
+                    best = Some((medoids, cost));
+                }
+            }
+            best.expect("at least one sample runs").0
+        }
+    };
+
+    let centres: Vec<DVector<f64>> = medoids.iter().map(|&m| rows.row(m).transpose()).collect();
+    let (labels, cost) = assign(formation, &rows, &centres)?;
+    let prototypes = medoids.iter()
+        .map(|&m| space.points.get(&ids[m]).expect("medoids are stored points"))
+        .collect();
+    Ok(partition(formation, ids, &rows, labels, prototypes, cost, "k-medoids medoid"))
+}
+
+/// Run a partitioning algorithm for every k in a range and keep the best
+pub(crate) fn select_k(
+    formation: &CategoryFormation,
+    space: &ConceptualSpace,
+    candidates: RangeInclusive<usize>,
+    algorithm: PartitionAlgorithm,
+    criterion: KSelection,
+) -> ConceptualResult<KSelectionResult> {
+    let n = space.points.len();
+    let candidates: Vec<usize> = candidates.filter(|&k| k >= 1 && k <= n).collect();
+    if candidates.is_empty() {
+        return Err(ConceptualError::InvalidPoint(format!(
+            "No candidate k fits {n} points"
+        )));
+    }
+
+    let matrix = match criterion {
+        KSelection::Silhouette => Some(distance_matrix(formation.metric(), &point_rows(space).1)?),
+        KSelection::Elbow => None,
+    };
+
+    let mut partitions = Vec::with_capacity(candidates.len());
+    let mut scores = Vec::with_capacity(candidates.len());
+    for &k in &candidates {
+        let partition = match algorithm {
+            PartitionAlgorithm::KMeans(config) => k_means(formation, space, k, &config)?,
+            PartitionAlgorithm::KMedoids(method) => k_medoids(formation, space, k, method)?,
+        };
+        let score = match &matrix {
+            Some(matrix) => silhouette(matrix, &partition.labels, k),
+            None => partition.cost,
+        };
+        scores.push((k, score));
+        partitions.push(partition);
+    }
+
+    let chosen = match criterion {
+        KSelection::Elbow => knee(&scores),
+        KSelection::Silhouette => (0..scores.len())
+            .max_by(|&a, &b| scores[a].1.total_cmp(&scores[b].1))
+            .unwrap_or(0),
+    };
+
+    Ok(KSelectionResult {
+        partition: partitions.swap_remove(chosen),
+        scores,
+    })
+}
+
+/// Mean silhouette width of a partition under the engine's metric
+pub(crate) fn silhouette_score(formation: &CategoryFormation, space: &ConceptualSpace, partition: &Partition) -> ConceptualResult<f64> {
+    let (ids, rows) = point_rows(space);
+    let labels = ids.iter()
+        .map(|id| partition.cluster_of(id).ok_or_else(|| {
+            ConceptualError::InvalidPoint(format!("Point {id} is not in the partition"))
+        }))
+        .collect::<ConceptualResult<Vec<_>>>()?;
+    Ok(silhouette(&distance_matrix(formation.metric(), &rows)?, &labels, partition.k()))
+}
+
+/// k-means++ seeding: each new centre is drawn with probability
+/// proportional to its squared distance from the nearest centre so far
+fn seed_centroids(formation: &CategoryFormation, rows: &DMatrix<f64>, k: usize, rng: &mut SplitMix64) -> ConceptualResult<Vec<DVector<f64>>> {
+    let n = rows.nrows();
+    let mut centroids = vec![rows.row(rng.below(n)).transpose()];
+    let mut closest = distances(formation.metric(), &centroids[0], rows)?.map(|d| d * d);
+
+    while centroids.len() < k {
+        let total = closest.sum();
+        let next = if total > 0.0 {
+            let mut target = rng.next_f64() * total;
+            let mut pick = n - 1;
+            for (i, &weight) in closest.iter().enumerate() {
+                if target < weight {
+                    pick = i;
+                    break;
+                }
+                target -= weight;
+            }
+            pick
+        } else {
+            rng.below(n)
+        };
+
+        let centre = rows.row(next).transpose();
+        let to_centre = distances(formation.metric(), &centre, rows)?;
+        for (best, d) in closest.iter_mut().zip(to_centre.iter()) {
+            *best = best.min(d * d);
+        }
+        centroids.push(centre);
+    }
+    Ok(centroids)
+}
+
+/// Label every row with its nearest centre and sum the distances
+fn assign(formation: &CategoryFormation, rows: &DMatrix<f64>, centres: &[DVector<f64>]) -> ConceptualResult<(Vec<usize>, f64)> {
+    let columns = centres.iter()
+        .map(|c| distances(formation.metric(), c, rows))
+        .collect::<ConceptualResult<Vec<_>>>()?;
+    let mut cost = 0.0;
+    let labels = (0..rows.nrows())
+        .map(|row| {
+            let (cluster, d) = nearest(columns.iter().map(|column| column[row]));
+            cost += d;
+            cluster
+        })
+        .collect();
+    Ok((labels, cost))
+}
+
+/// Assemble a partition and its regions
+///
+/// Voronoi cells when the engine's metric has flat bisectors, member
+/// hulls otherwise.
+fn partition(
+    formation: &CategoryFormation,
+    ids: Vec<Uuid>,
+    rows: &DMatrix<f64>,
+    labels: Vec<usize>,
+    prototypes: Vec<ConceptualPoint>,
+    cost: f64,
+    kind: &str,
+) -> Partition {
+    let centres: Vec<DVector<f64>> = prototypes.iter().map(|p| p.coordinates.clone()).collect();
+    let dimensions = rows.ncols();
+    let cells = flat_bisectors(formation.metric(), dimensions);
+    let weights = bisector_weights(formation.metric(), dimensions);
+    let regions = prototypes.iter()
+        .enumerate()
+        .map(|(cluster, prototype)| {
+            let rows_in: Vec<usize> = (0..ids.len()).filter(|&row| labels[row] == cluster).collect();
+            if cells {
+                return ConvexRegion {
+                    boundaries: voronoi_cell(&centres, cluster, &weights),
+                    member_points: rows_in.iter().map(|&row| ids[row]).collect(),
+                    ..ConvexRegion::from_prototype(prototype.clone())
+                }
+                .with_name(format!("Cluster {cluster}"))
+                .with_description(format!("Voronoi cell of {kind} {cluster}"));
+            }
+
+            let members: Vec<ConceptualPoint> = rows_in.iter()
+                .map(|&row| ConceptualPoint {
+                    id: Some(ids[row]),
+                    ..ConceptualPoint::new(rows.row(row).iter().copied().collect(), prototype.dimension_map.clone())
+                })
+                .collect();
+            ConvexRegion::from_hull(prototype.clone(), &members)
+                .with_name(format!("Cluster {cluster}"))
+                .with_description(format!("Member hull around {kind} {cluster}"))
+        })
+        .collect();
+
+    Partition { ids, labels, prototypes, cost, regions, positions: OnceLock::new() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ConceptualMetric, DimensionId};
+    use std::collections::HashMap;
+
+    fn blobs() -> ConceptualSpace {
+        let dims = vec![DimensionId::new(), DimensionId::new()];
+        let mut space = ConceptualSpace::new(
+            "blobs".to_string(),
+            dims.clone(),
+            ConceptualMetric::uniform(2, 2.0),
+        );
+        let dimension_map: HashMap<_, _> = dims.iter().enumerate().map(|(i, d)| (*d, i)).collect();
+        let mut rng = SplitMix64::new(7);
+        for centre in [(0.0, 0.0), (6.0, 0.0), (3.0, 6.0)] {
+            for _ in 0..15 {
+                let x = centre.0 + rng.next_f64() - 0.5;
+                let y = centre.1 + rng.next_f64() - 0.5;
+                space.add_point(ConceptualPoint::new(vec![x, y], dimension_map.clone())).unwrap();
+            }
+        }
+        space
+    }
+
+    fn pure(partition: &Partition) -> bool {
+        // Points were inserted fifteen per blob
+        partition.labels.chunks(15).all(|chunk| chunk.iter().all(|&l| l == chunk[0]))
+            && partition.labels.chunks(15).map(|c| c[0]).collect::<HashSet<_>>().len() == 3
+    }
+
+    #[test]
+    fn test_k_means_regions_are_voronoi_cells() {
+        let space = blobs();
+        let formation = CategoryFormation::new(DistanceMetric::Euclidean);
+        let partition = formation.k_means(&space, 3, &KMeansConfig::default()).unwrap();
+
+        assert!(pure(&partition));
+        for (region, prototype) in partition.regions.iter().zip(&partition.prototypes) {
+            assert_eq!(region.boundaries.len(), 2);
+            assert!(region.contains(prototype));
+            for id in &region.member_points {
+                assert!(region.contains(&space.points.get(id).unwrap()));
+            }
+        }
+    }
+
+    #[test]
+    fn test_k_medoids_pam_and_clara_agree() {
+        let space = blobs();
+        let formation = CategoryFormation::new(DistanceMetric::Manhattan);
+        let pam = formation.k_medoids(&space, 3, MedoidMethod::Pam).unwrap();
+        let clara = formation
+            .k_medoids(&space, 3, MedoidMethod::Clara { samples: 5, sample_size: 20, seed: 3 })
+            .unwrap();
+
+        assert!(pure(&pam) && pure(&clara));
+        assert!(pam.prototypes.iter().all(|p| p.id.is_some_and(|id| space.points.contains_key(&id))));
+        assert!(clara.cost >= pam.cost - 1e-9);
+    }
+
+    #[test]
+    fn test_manhattan_regions_contain_their_members() {
+        let mut space = ConceptualSpace::new(
+            "taxicab".to_string(),
+            vec![DimensionId::new(), DimensionId::new()],
+            ConceptualMetric::uniform(2, 2.0),
+        );
+        let mut add = |x: f64, y: f64| space.add_point(ConceptualPoint::new(vec![x, y], HashMap::new())).unwrap();
+        let origin = add(0.0, 0.0);
+        for (cx, cy) in [(0.0, 0.0), (4.0, 1.0)] {
+            for (dx, dy) in [(0.1, 0.0), (-0.1, 0.0), (0.0, 0.1), (0.0, -0.1)] {
+                add(cx + dx, cy + dy);
+            }
+        }
+        add(4.0, 1.0);
+        // Nearer the origin in Manhattan distance, nearer (4, 1) in Euclidean
+        let between = add(2.3, 0.0);
+
+        let manhattan = CategoryFormation::new(DistanceMetric::Manhattan)
+            .k_medoids(&space, 2, MedoidMethod::Pam)
+            .unwrap();
+        let cluster = manhattan.cluster_of(&between).unwrap();
+        assert_eq!(manhattan.cluster_of(&origin), Some(cluster));
+        for region in &manhattan.regions {
+            for id in &region.member_points {
+                assert!(region.contains(&space.points.get(id).unwrap()));
+            }
+        }
+        assert!(manhattan.regions[cluster].contains(&space.points.get(&between).unwrap()));
+
+        let euclidean = CategoryFormation::new(DistanceMetric::Euclidean)
+            .k_medoids(&space, 2, MedoidMethod::Pam)
+            .unwrap();
+        assert_ne!(euclidean.cluster_of(&between), euclidean.cluster_of(&origin));
+        assert_eq!(euclidean.cluster_of(&Uuid::new_v4()), None);
+    }
+
+    #[test]
+    fn test_select_k_finds_three_blobs() {
+        let space = blobs();
+        let formation = CategoryFormation::new(DistanceMetric::Euclidean);
+
+        let by_silhouette = formation
+            .select_k(&space, 1..=6, PartitionAlgorithm::KMedoids(MedoidMethod::Pam), KSelection::Silhouette)
+            .unwrap();
+        assert_eq!(by_silhouette.partition.k(), 3);
+        assert_eq!(by_silhouette.scores.len(), 6);
+
+        let by_elbow = formation
+            .select_k(&space, 1..=6, PartitionAlgorithm::KMeans(KMeansConfig::default()), KSelection::Elbow)
+            .unwrap();
+        assert_eq!(by_elbow.partition.k(), 3);
+        let score = formation.silhouette_score(&space, &by_elbow.partition).unwrap();
+        assert!(score > 0.7);
+    }
+}
@@ -1,6 +1,6 @@
 //! Query to find similar concepts in a conceptual space
 
-use crate::{ConceptualSpaceId, ConceptualPoint};
+use crate::{ConceptualSpace, ConceptualSpaceId, ConceptualPoint, ConceptualResult};
 use cim_domain::Query;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
@@ -28,4 +28,23 @@ pub struct SimilarConcepts {
     pub points: Vec<(Uuid, ConceptualPoint, f64)>,
 }
 
+impl FindSimilarConcepts {
+    /// Run the query against a space using its spatial index
+    pub fn execute(&self, space: &ConceptualSpace) -> ConceptualResult<SimilarConcepts> {
+        let mut neighbors = match self.max_distance {
+            Some(radius) => space.range_search(&self.reference, radius)?,
+            None => space.k_nearest_neighbors(&self.reference, self.limit)?,
+        };
+        neighbors.truncate(self.limit);
+
+        let points = neighbors.into_iter()
+            .filter_map(|(id, distance)| {
+                space.points.get(id).map(|point| (*id, point, distance))
+            })
+            .collect();
+
+        Ok(SimilarConcepts { points })
+    }
+}
+
 impl Query for FindSimilarConcepts {}
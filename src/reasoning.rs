@@ -5,8 +5,7 @@
 
 use crate::{
     ConceptualSpace, ConceptualPoint, ConceptualError, ConceptualResult,
-    SimilarityEngine, CategoryFormation,
-    DistanceMetric, RTreeIndex, SpatialIndex
+    SimilarityEngine, CategoryFormation, DistanceMetric, GaussianMixture
 };
 use nalgebra::DVector;
 use serde::{Deserialize, Serialize};
@@ -22,11 +21,11 @@ pub struct ConceptualReasoning {
     /// Category formation engine
     _category_formation: CategoryFormation,
 
-    /// Spatial index for efficient reasoning
-    spatial_index: RTreeIndex,
-
     /// Learning rate for adaptive reasoning
     learning_rate: f64,
+
+    /// Mixture whose posteriors replace distance decay in categorical inference
+    mixture: Option<GaussianMixture>,
 }
 
 impl ConceptualReasoning {
@@ -34,9 +33,9 @@ impl ConceptualReasoning {
     pub fn new(metric: DistanceMetric) -> Self {
         Self {
             similarity_engine: SimilarityEngine::new(metric.clone()),
-            _category_formation: CategoryFormation::new(metric.clone()),
-            spatial_index: RTreeIndex::new(metric),
+            _category_formation: CategoryFormation::new(metric),
             learning_rate: 0.1,
+            mixture: None,
         }
     }
 
@@ -46,6 +45,14 @@ impl ConceptualReasoning {
         self
     }
 
+    /// Infer categories from the posteriors of a fitted Gaussian mixture
+    ///
+    /// Component ids match the regions from `GaussianMixture::regions`.
+    pub fn with_mixture(mut self, mixture: GaussianMixture) -> Self {
+        self.mixture = Some(mixture);
+        self
+    }
+
     /// Perform analogical reasoning: A is to B as C is to ?
     ///
     /// ```mermaid
@@ -103,7 +110,9 @@ impl ConceptualReasoning {
 
     /// Perform categorical inference
     ///
-    /// Given a point, infer its likely category membership and properties
+    /// Given a point, infer its likely category membership and properties.
+    /// With a mixture configured, memberships are its component posteriors;
+    /// otherwise containing regions score by exponential distance decay.
     pub fn categorical_inference(
         &mut self,
         point: &ConceptualPoint,
@@ -116,8 +125,21 @@ impl ConceptualReasoning {
         
         // Calculate membership probabilities
         let mut category_memberships = Vec::new();
-        
-        for region in &containing_regions {
+
+        if let Some(mixture) = &self.mixture {
+            let posteriors = mixture.posteriors(point)?;
+            for (component, posterior) in mixture.components.iter().zip(posteriors) {
+                let mean = ConceptualPoint::new(component.mean.as_slice().to_vec(), point.dimension_map.clone());
+                category_memberships.push(CategoryMembership {
+                    category_id: component.id,
+                    category_name: space.regions.get(&component.id).and_then(|r| r.name.clone()),
+                    membership_strength: posterior,
+                    prototype_distance: space.metric.distance(point, &mean)?,
+                });
+            }
+        }
+
+        for region in containing_regions.iter().filter(|_| self.mixture.is_none()) {
             let distance_to_prototype = space.metric.distance(point, &region.prototype)?;
             let membership_strength = (-distance_to_prototype).exp(); // Exponential decay
             
@@ -285,15 +307,39 @@ impl ConceptualReasoning {
     ) -> ConceptualResult<Vec<SimilarityMatch>> {
         debug!("Retrieving {} similar concepts", k);
 
+        // The index ranks by the space metric, so it can only shortlist the
+        // candidates when the engine measures distance the same way. Context
+        // weights or a different engine metric reorder the whole space, and
+        // then every point is scored in one batched pass.
+        let engine = &self.similarity_engine;
+        let weighted = context.is_some_and(|c| engine.context_weights.contains_key(c));
+        let indexed = !weighted && engine.base_metric.is_equivalent(
+            &space.metric.as_distance_metric(),
+            space.dimension_ids.len(),
+        );
+        let scored: Vec<(ConceptualPoint, f64)> = if indexed {
+            space.k_nearest_neighbors(query, k)?
+                .into_iter()
+                .filter_map(|(id, _)| space.points.get(id))
+                .map(|point| {
+                    let similarity = engine.contextual_similarity(query, &point, context)?;
+                    Ok((point, similarity))
+                })
+                .collect::<ConceptualResult<_>>()?
+        } else {
+            let mut all = engine.similarities_in(query, space, context)?;
+            all.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+            all.truncate(k);
+            all.into_iter()
+                .filter_map(|(id, similarity)| space.points.get(&id).map(|p| (p, similarity)))
+                .collect()
+        };
+
         let mut matches = Vec::new();
         
-        for point in space.points.values() {
-            let similarity = self.similarity_engine.contextual_similarity(
-                query, point, context
-            )?;
-
+        for (point, similarity) in scored {
             matches.push(SimilarityMatch {
-                concept: point.clone(),
+                concept: point,
                 similarity_score: similarity,
                 match_type: self.classify_match_type(similarity),
             });
@@ -335,18 +381,15 @@ impl ConceptualReasoning {
     // Helper methods
 
     fn find_nearest_concept(
-        &mut self,
+        &self,
         point: &ConceptualPoint,
         space: &ConceptualSpace,
         k: usize,
     ) -> ConceptualResult<Vec<(ConceptualPoint, f64)>> {
-        self.spatial_index.clear();
-        
-        for p in space.points.values() {
-            self.spatial_index.insert(p.clone())?;
-        }
-
-        self.spatial_index.find_k_nearest(point, k)
+        Ok(space.k_nearest_neighbors(point, k)?
+            .into_iter()
+            .filter_map(|(id, d)| space.points.get(id).map(|p| (p, d)))
+            .collect())
     }
 
     fn infer_properties_from_category(
@@ -541,19 +584,13 @@ impl ConceptualReasoning {
         space: &ConceptualSpace,
         constraints: &PathConstraints,
     ) -> ConceptualResult<Vec<(ConceptualPoint, f64)>> {
-        let mut neighbors = Vec::new();
+        let neighbors = space.range_search(point, constraints.max_step_size)?
+            .into_iter()
+            .filter(|(_, distance)| *distance > 0.0)
+            .filter_map(|(id, distance)| space.points.get(id).map(|p| (p, distance)))
+            .take(constraints.beam_width)
+            .collect();
 
-        for candidate in space.points.values() {
-            let distance = space.metric.distance(point, candidate)?;
-            
-            if distance <= constraints.max_step_size && distance > 0.0 {
-                neighbors.push((candidate.clone(), distance));
-            }
-        }
-
-        neighbors.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap());
-        neighbors.truncate(constraints.beam_width);
-        
         Ok(neighbors)
     }
 
@@ -758,6 +795,38 @@ mod tests {
         assert!((d.coordinates[1] - 0.9).abs() < 0.001);
     }
 
+    #[test]
+    fn test_retrieval_ranks_by_engine_metric() {
+        use crate::ConceptualMetric;
+
+        // The space stretches the second axis, the engine does not
+        let mut metric = ConceptualMetric::uniform(2, 2.0);
+        metric.dimension_weights[1] = crate::DimensionWeight::constant(100.0);
+        let mut space = ConceptualSpace::new(
+            "Stretched".to_string(),
+            vec![DimensionId::new(), DimensionId::new()],
+            metric,
+        );
+        let across = space.add_point(ConceptualPoint::new(vec![0.0, 0.5], HashMap::new())).unwrap();
+        for x in 1..=3 {
+            space.add_point(ConceptualPoint::new(vec![x as f64, 0.0], HashMap::new())).unwrap();
+        }
+
+        let mut reasoning = ConceptualReasoning::new(DistanceMetric::Euclidean);
+        let query = ConceptualPoint::new(vec![0.0, 0.0], HashMap::new());
+        let matches = reasoning.similarity_retrieval(&query, &space, 1, None).unwrap();
+        assert_eq!(matches[0].concept.id, Some(across));
+
+        // A uniform space metric lets the index shortlist the candidates
+        let uniform = ConceptualSpace::new(
+            "Uniform".to_string(),
+            space.dimension_ids.clone(),
+            ConceptualMetric::uniform(2, 2.0),
+        );
+        assert!(DistanceMetric::Euclidean.is_equivalent(&uniform.metric.as_distance_metric(), 2));
+        assert!(!DistanceMetric::Euclidean.is_equivalent(&space.metric.as_distance_metric(), 2));
+    }
+
     #[test]
     fn test_conceptual_blending() {
         let space = create_test_space();
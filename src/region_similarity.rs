@@ -0,0 +1,383 @@
+//! Similarity between convex regions
+//!
+//! Compares whole categories rather than points: how far apart their
+//! prototypes are, how much of their volume they share, and how wide the
+//! gap between their boundaries is. A combined score flags categories that
+//! are nearly redundant.
+
+use crate::sampling::SplitMix64;
+use crate::{
+    ConceptualError, ConceptualPoint, ConceptualResult, ConceptualSpace, ConvexRegion, Hyperplane,
+};
+use nalgebra::DVector;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// How region similarity is estimated and combined
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RegionSimilarityConfig {
+    /// Monte Carlo samples for overlap of oblique regions
+    pub samples: usize,
+
+    /// Seed for the overlap sampler
+    pub seed: u64,
+
+    /// Box within which volumes are measured, as `(lower, upper)`
+    ///
+    /// Defaults to the bounding box of the space's points, both prototypes
+    /// and any finite axis-aligned sides, padded by a tenth of its extent.
+    pub bounds: Option<(DVector<f64>, DVector<f64>)>,
+
+    /// Weight of prototype similarity in the combined score
+    pub prototype_weight: f64,
+
+    /// Weight of volume overlap in the combined score
+    pub overlap_weight: f64,
+
+    /// Weight of boundary proximity in the combined score
+    pub boundary_weight: f64,
+}
+
+impl Default for RegionSimilarityConfig {
+    fn default() -> Self {
+        Self {
+            samples: 20_000,
+            seed: 0x5EED,
+            bounds: None,
+            prototype_weight: 0.4,
+            overlap_weight: 0.4,
+            boundary_weight: 0.2,
+        }
+    }
+}
+
+/// Measures comparing two regions
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RegionSimilarity {
+    /// Distance between the prototypes under the space metric
+    pub prototype_distance: f64,
+
+    /// Jaccard ratio of the regions' volumes within the measuring box
+    pub overlap: f64,
+
+    /// Whether `overlap` was computed exactly rather than sampled
+    pub overlap_exact: bool,
+
+    /// Gap between the regions, zero when they touch or overlap
+    ///
+    /// Exact under weighted Euclidean metrics with positive weights. Under
+    /// other metrics the closest pair is found in plain coordinates, so the
+    /// gap is an upper bound on the true one.
+    pub boundary_distance: f64,
+
+    /// Weighted combination of the three measures in `[0, 1]`
+    pub score: f64,
+}
+
+/// Volume overlap of two regions within a box, exact for axis-aligned regions
+fn jaccard(
+    a: &ConvexRegion,
+    b: &ConvexRegion,
+    bounds: &(DVector<f64>, DVector<f64>),
+    config: &RegionSimilarityConfig,
+) -> (f64, bool) {
+    let (lower, upper) = bounds;
+    let dimensions = lower.len();
+
+    if let (Some(box_a), Some(box_b)) = (a.axis_aligned_bounds(dimensions), b.axis_aligned_bounds(dimensions)) {
+        let volume = |lo: &DVector<f64>, hi: &DVector<f64>| -> f64 {
+            (0..dimensions)
+                .map(|i| (hi[i].min(upper[i]) - lo[i].max(lower[i])).max(0.0))
+                .product()
+        };
+        let shared = volume(&box_a.0.sup(&box_b.0), &box_a.1.inf(&box_b.1));
+        let union = volume(&box_a.0, &box_a.1) + volume(&box_b.0, &box_b.1) - shared;
+        return (if union > 0.0 { shared / union } else { 0.0 }, true);
+    }
+
+    let mut rng = SplitMix64::new(config.seed);
+    let (mut in_either, mut in_both) = (0usize, 0usize);
+    for _ in 0..config.samples {
+        let point = ConceptualPoint::new(rng.in_box(lower, upper).iter().copied().collect(), Default::default());
+        let (in_a, in_b) = (a.contains(&point), b.contains(&point));
+        in_either += usize::from(in_a || in_b);
+        in_both += usize::from(in_a && in_b);
+    }
+    (if in_either > 0 { in_both as f64 / in_either as f64 } else { 0.0 }, false)
+}
+
+/// Per-axis `√wᵢ` turning the space metric into plain Euclidean distance
+///
+/// Only weighted Euclidean metrics with positive weights have one.
+fn euclidean_scale(space: &ConceptualSpace) -> Option<DVector<f64>> {
+    let weights = space.metric.get_weights();
+    (space.metric.minkowski_p == 2.0 && weights.iter().all(|w| *w > 0.0 && w.is_finite()))
+        .then(|| DVector::from_iterator(weights.len(), weights.iter().map(|w| w.sqrt())))
+}
+
+/// The region in coordinates multiplied by `scale` per axis
+fn scaled_region(region: &ConvexRegion, scale: &DVector<f64>) -> ConvexRegion {
+    ConvexRegion {
+        boundaries: region.boundaries.iter()
+            .map(|plane| Hyperplane::new(plane.normal.component_div(scale), plane.offset))
+            .collect(),
+        ..ConvexRegion::from_prototype(ConceptualPoint::new(
+            region.prototype.coordinates.component_mul(scale).iter().copied().collect(),
+            Default::default(),
+        ))
+    }
+}
+
+/// Closest pair of points between two regions under the space metric
+///
+/// Projections are Euclidean, so the regions are first scaled by `√wᵢ`
+/// where that makes the space metric Euclidean.
+fn closest_pair(space: &ConceptualSpace, a: &ConvexRegion, b: &ConvexRegion) -> (DVector<f64>, DVector<f64>) {
+    match euclidean_scale(space) {
+        Some(scale) if scale.len() == a.prototype.coordinates.len() && scale.len() == b.prototype.coordinates.len() => {
+            let (x, y) = alternating_projections(&scaled_region(a, &scale), &scaled_region(b, &scale));
+            (x.component_div(&scale), y.component_div(&scale))
+        }
+        _ => alternating_projections(a, b),
+    }
+}
+
+/// Euclidean closest pair of points between two regions by alternating projections
+fn alternating_projections(a: &ConvexRegion, b: &ConvexRegion) -> (DVector<f64>, DVector<f64>) {
+    let mut y = b.project(&b.prototype.coordinates);
+    let mut x = a.project(&y);
+    for _ in 0..200 {
+        let next_y = b.project(&x);
+        let next_x = a.project(&next_y);
+        let moved = (&next_x - &x).norm() + (&next_y - &y).norm();
+        x = next_x;
+        y = next_y;
+        if moved <= 1e-12 {
+            break;
+        }
+    }
+    (x, y)
+}
+
+/// A region of the space by id
+fn region<'a>(space: &'a ConceptualSpace, id: &Uuid) -> ConceptualResult<&'a ConvexRegion> {
+    space.regions.get(id)
+        .ok_or_else(|| ConceptualError::InvalidPoint(format!("Region {id} not found")))
+}
+
+/// Box covering the points, the regions' prototypes and their finite
+/// axis-aligned sides, padded on every side
+fn measuring_box(space: &ConceptualSpace, regions: &[&ConvexRegion]) -> (DVector<f64>, DVector<f64>) {
+    let dimensions = space.dimension_ids.len();
+    let mut lower = DVector::from_element(dimensions, f64::INFINITY);
+    let mut upper = DVector::from_element(dimensions, f64::NEG_INFINITY);
+
+    let mut include = |c: &DVector<f64>| {
+        for i in 0..dimensions.min(c.len()) {
+            if c[i].is_finite() {
+                lower[i] = lower[i].min(c[i]);
+                upper[i] = upper[i].max(c[i]);
+            }
+        }
+    };
+    for point in space.points.values() {
+        include(&point.coordinates);
+    }
+    for region in regions {
+        include(&region.prototype.coordinates);
+        if let Some((lo, hi)) = region.axis_aligned_bounds(dimensions) {
+            include(&lo);
+            include(&hi);
+        }
+    }
+
+    for i in 0..dimensions {
+        if lower[i] > upper[i] {
+            (lower[i], upper[i]) = (0.0, 0.0);
+        }
+        let extent = upper[i] - lower[i];
+        let pad = if extent > 0.0 { extent * 0.1 } else { 1.0 };
+        lower[i] -= pad;
+        upper[i] += pad;
+    }
+    (lower, upper)
+}
+
+impl RegionSimilarity {
+    /// Compare two regions of a space
+    ///
+    /// ```mermaid
+    /// graph TD
+    ///     A[Region A] --> P[Prototype Distance]
+    ///     B[Region B] --> P
+    ///     A --> O{Axis-Aligned?}
+    ///     B --> O
+    ///     O -->|Yes| E[Exact Box Jaccard]
+    ///     O -->|No| M[Monte Carlo Jaccard]
+    ///     A --> D[Alternating Projections]
+    ///     B --> D
+    ///     P --> S[Combined Score]
+    ///     E --> S
+    ///     M --> S
+    ///     D --> S
+    /// ```
+    pub fn between(
+        space: &ConceptualSpace,
+        a: &Uuid,
+        b: &Uuid,
+        config: &RegionSimilarityConfig,
+    ) -> ConceptualResult<Self> {
+        let (region_a, region_b) = (region(space, a)?, region(space, b)?);
+        let prototype_distance = space.metric.distance(&region_a.prototype, &region_b.prototype)?;
+
+        let bounds = config.bounds.clone()
+            .unwrap_or_else(|| measuring_box(space, &[region_a, region_b]));
+        if bounds.0.len() != space.dimension_ids.len() || bounds.1.len() != space.dimension_ids.len() {
+            return Err(ConceptualError::InvalidDimension(
+                "Measuring box does not match the space dimensions".to_string()
+            ));
+        }
+        let (overlap, overlap_exact) = jaccard(region_a, region_b, &bounds, config);
+
+        let (x, y) = closest_pair(space, region_a, region_b);
+        let boundary_distance = space.metric.distance(
+            &ConceptualPoint::new(x.iter().copied().collect(), Default::default()),
+            &ConceptualPoint::new(y.iter().copied().collect(), Default::default()),
+        )?;
+
+        let function = space.similarity.function;
+        let total_weight = config.prototype_weight + config.overlap_weight + config.boundary_weight;
+        let score = if total_weight > 0.0 {
+            (config.prototype_weight * function.similarity(prototype_distance)
+                + config.overlap_weight * overlap
+                + config.boundary_weight * function.similarity(boundary_distance))
+                / total_weight
+        } else {
+            0.0
+        };
+
+        Ok(Self {
+            prototype_distance,
+            overlap,
+            overlap_exact,
+            boundary_distance,
+            score,
+        })
+    }
+
+    /// Region pairs of a space scoring at least `threshold`, most similar first
+    pub fn redundant(
+        space: &ConceptualSpace,
+        threshold: f64,
+        config: &RegionSimilarityConfig,
+    ) -> ConceptualResult<Vec<(Uuid, Uuid, Self)>> {
+        let mut ids: Vec<Uuid> = space.regions.keys().copied().collect();
+        ids.sort();
+
+        let mut pairs = Vec::new();
+        for (i, a) in ids.iter().enumerate() {
+            for b in &ids[i + 1..] {
+                let similarity = Self::between(space, a, b, config)?;
+                if similarity.score >= threshold {
+                    pairs.push((*a, *b, similarity));
+                }
+            }
+        }
+        pairs.sort_by(|x, y| y.2.score.partial_cmp(&x.2.score).unwrap_or(std::cmp::Ordering::Equal));
+        Ok(pairs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ConceptualMetric, DimensionId, DimensionWeight};
+    use std::collections::HashMap;
+
+    fn square_region(x0: f64, y0: f64, size: f64) -> ConvexRegion {
+        let mut region = ConvexRegion::from_prototype(ConceptualPoint::new(
+            vec![x0 + size / 2.0, y0 + size / 2.0],
+            HashMap::new(),
+        ));
+        region.boundaries = vec![
+            Hyperplane::new(DVector::from_vec(vec![1.0, 0.0]), x0),
+            Hyperplane::new(DVector::from_vec(vec![-1.0, 0.0]), -(x0 + size)),
+            Hyperplane::new(DVector::from_vec(vec![0.0, 1.0]), y0),
+            Hyperplane::new(DVector::from_vec(vec![0.0, -1.0]), -(y0 + size)),
+        ];
+        region
+    }
+
+    fn space_with(regions: Vec<ConvexRegion>) -> (ConceptualSpace, Vec<Uuid>) {
+        let mut space = ConceptualSpace::new(
+            "Regions".to_string(),
+            vec![DimensionId::new(), DimensionId::new()],
+            ConceptualMetric::uniform(2, 2.0),
+        );
+        let ids = regions.into_iter()
+            .map(|region| {
+                let id = region.id;
+                space.add_region(region).unwrap();
+                id
+            })
+            .collect();
+        (space, ids)
+    }
+
+    #[test]
+    fn test_axis_aligned_regions_are_exact() {
+        // Unit squares offset by half a side share a third of their union
+        let (space, ids) = space_with(vec![square_region(0.0, 0.0, 1.0), square_region(0.5, 0.0, 1.0)]);
+        let similarity = space.region_similarity(&ids[0], &ids[1], &RegionSimilarityConfig::default()).unwrap();
+        assert!(similarity.overlap_exact);
+        assert!((similarity.overlap - 1.0 / 3.0).abs() < 1e-12);
+        assert!(similarity.boundary_distance < 1e-9);
+        assert_eq!(similarity.prototype_distance, 0.5);
+
+        let (space, ids) = space_with(vec![square_region(0.0, 0.0, 1.0), square_region(3.0, 0.0, 1.0)]);
+        let apart = space.region_similarity(&ids[0], &ids[1], &RegionSimilarityConfig::default()).unwrap();
+        assert_eq!(apart.overlap, 0.0);
+        assert!((apart.boundary_distance - 2.0).abs() < 1e-9);
+        assert!(apart.score < similarity.score);
+    }
+
+    #[test]
+    fn test_oblique_regions_are_sampled() {
+        // Half-planes x + y >= 1 and x + y >= 3, measured within [0, 2]²
+        let mut a = ConvexRegion::from_prototype(ConceptualPoint::new(vec![1.5, 1.5], HashMap::new()));
+        a.boundaries = vec![Hyperplane::new(DVector::from_vec(vec![1.0, 1.0]), 1.0)];
+        let mut b = ConvexRegion::from_prototype(ConceptualPoint::new(vec![1.5, 1.5], HashMap::new()));
+        b.boundaries = vec![Hyperplane::new(DVector::from_vec(vec![1.0, 1.0]), 3.0)];
+        let (space, ids) = space_with(vec![a, b]);
+
+        let config = RegionSimilarityConfig {
+            bounds: Some((DVector::from_vec(vec![0.0, 0.0]), DVector::from_vec(vec![2.0, 2.0]))),
+            ..RegionSimilarityConfig::default()
+        };
+        let similarity = space.region_similarity(&ids[0], &ids[1], &config).unwrap();
+        assert!(!similarity.overlap_exact);
+        // Areas inside the box: 4 - 0.5 = 3.5 and 0.5; b lies inside a
+        assert!((similarity.overlap - 0.5 / 3.5).abs() < 0.02);
+        assert!(similarity.boundary_distance < 1e-9);
+
+        let redundant = space.redundant_regions(0.0, &config).unwrap();
+        assert_eq!(redundant.len(), 1);
+        assert!(space.region_similarity(&ids[0], &Uuid::new_v4(), &config).is_err());
+    }
+
+    #[test]
+    fn test_boundary_distance_follows_the_weights() {
+        // Half-planes x + y <= 0 and x + y >= 2 with dimension 1 weighted 4
+        let mut a = ConvexRegion::from_prototype(ConceptualPoint::new(vec![-1.0, -1.0], HashMap::new()));
+        a.boundaries = vec![Hyperplane::new(DVector::from_vec(vec![-1.0, -1.0]), 0.0)];
+        let mut b = ConvexRegion::from_prototype(ConceptualPoint::new(vec![2.0, 2.0], HashMap::new()));
+        b.boundaries = vec![Hyperplane::new(DVector::from_vec(vec![1.0, 1.0]), 2.0)];
+        let (mut space, ids) = space_with(vec![a, b]);
+        space.metric.dimension_weights = vec![DimensionWeight::constant(1.0), DimensionWeight::constant(4.0)];
+
+        // Minimise dx² + 4dy² subject to dx + dy = 2; a plain Euclidean
+        // projection would stop at dx = dy = 1, i.e. √5
+        let similarity = space.region_similarity(&ids[0], &ids[1], &RegionSimilarityConfig::default()).unwrap();
+        assert!((similarity.boundary_distance - 2.0 / 1.25_f64.sqrt()).abs() < 1e-6);
+    }
+}
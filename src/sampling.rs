@@ -0,0 +1,41 @@
+//! Deterministic random sampling for Monte Carlo estimates
+//!
+//! A small SplitMix64 generator: seeded runs are reproducible across
+//! platforms without pulling in a random number crate.
+
+use nalgebra::DVector;
+
+/// SplitMix64 pseudo-random generator
+#[derive(Debug, Clone)]
+pub(crate) struct SplitMix64 {
+    state: u64,
+}
+
+impl SplitMix64 {
+    pub(crate) fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    pub(crate) fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform sample in `[0, 1)` from the top 53 bits
+    pub(crate) fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Uniform index in `0..n`; `n` must be non-zero
+    pub(crate) fn below(&mut self, n: usize) -> usize {
+        ((self.next_f64() * n as f64) as usize).min(n - 1)
+    }
+
+    /// Uniform sample in the box `[lower, upper)`
+    pub(crate) fn in_box(&mut self, lower: &DVector<f64>, upper: &DVector<f64>) -> DVector<f64> {
+        DVector::from_fn(lower.len(), |i, _| lower[i] + (upper[i] - lower[i]) * self.next_f64())
+    }
+}
@@ -0,0 +1,342 @@
+//! Distances between sets of concepts
+//!
+//! Compares categories and bags of concepts rather than single points:
+//! Hausdorff distance (worst-case mismatch), average linkage (mean pairwise
+//! distance) and the Earth Mover's distance (cheapest transport of one
+//! set's mass onto the other). All are measured under the space metric.
+
+use crate::{
+    ConceptualError, ConceptualPoint, ConceptualResult, ConceptualSpace, DistanceMetric,
+    KdTreeIndex, SpatialIndex,
+};
+use nalgebra::DMatrix;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use uuid::Uuid;
+
+/// Which set distance to compute
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum SetDistance {
+    /// Largest distance from a point of either set to the other set
+    Hausdorff,
+
+    /// Mean distance over all cross-set pairs
+    AverageLinkage,
+
+    /// Earth Mover's distance with uniform mass per point
+    EarthMovers,
+}
+
+fn require_points(points: &[ConceptualPoint]) -> ConceptualResult<()> {
+    if points.is_empty() {
+        return Err(ConceptualError::InvalidPoint("Set distances need non-empty sets".to_string()));
+    }
+    Ok(())
+}
+
+fn require_dimensions(points: &[ConceptualPoint], dimensions: usize) -> ConceptualResult<()> {
+    if let Some(point) = points.iter().find(|p| p.coordinates.len() != dimensions) {
+        return Err(ConceptualError::InvalidDimension(format!(
+            "Point has {} coordinates but the space has {dimensions} dimensions",
+            point.coordinates.len()
+        )));
+    }
+    Ok(())
+}
+
+/// Rows of a matrix, one point per row
+fn point_rows(points: &[ConceptualPoint], dimensions: usize) -> ConceptualResult<DMatrix<f64>> {
+    require_dimensions(points, dimensions)?;
+    Ok(DMatrix::from_fn(points.len(), dimensions, |i, j| points[i].coordinates[j]))
+}
+
+/// Cross-set distances, one row per point of `a`
+fn cost_matrix(metric: &DistanceMetric, a: &[ConceptualPoint], b: &[ConceptualPoint]) -> ConceptualResult<DMatrix<f64>> {
+    let dimensions = b[0].coordinates.len();
+    let rows = point_rows(b, dimensions)?;
+    let mut costs = DMatrix::zeros(a.len(), b.len());
+    for (i, point) in a.iter().enumerate() {
+        if point.coordinates.len() != dimensions {
+            return Err(ConceptualError::InvalidPoint("Points have different dimensions".to_string()));
+        }
+        let distances = metric.distances_to_rows(&point.coordinates, rows.rows(0, b.len()))?;
+        if distances.iter().any(|d| d.is_nan()) {
+            return Err(ConceptualError::InvalidPoint("Metric undefined between set points".to_string()));
+        }
+        costs.set_row(i, &distances.transpose());
+    }
+    Ok(costs)
+}
+
+/// Min-cost flow by successive shortest paths
+///
+/// Dijkstra runs on reduced costs, which stay non-negative thanks to the
+/// node potentials updated after every augmentation.
+struct MinCostFlow {
+    /// `(to, capacity, cost, reverse edge index)` per node
+    edges: Vec<Vec<(usize, f64, f64, usize)>>,
+}
+
+/// Residual capacities below this count as exhausted
+const FLOW_EPSILON: f64 = 1e-12;
+
+impl MinCostFlow {
+    fn new(nodes: usize) -> Self {
+        Self { edges: vec![Vec::new(); nodes] }
+    }
+
+    fn add_edge(&mut self, from: usize, to: usize, capacity: f64, cost: f64) {
+        let (forward, backward) = (self.edges[from].len(), self.edges[to].len());
+        self.edges[from].push((to, capacity, cost, backward));
+        self.edges[to].push((from, 0.0, -cost, forward));
+    }
+
+    /// Push up to `demand` from `source` to `sink`, returning `(flow, cost)`
+    fn run(&mut self, source: usize, sink: usize, demand: f64) -> (f64, f64) {
+        let n = self.edges.len();
+        let mut potential = vec![0.0; n];
+        let (mut flow, mut cost) = (0.0, 0.0);
+
+        while demand - flow > FLOW_EPSILON {
+            let mut distance = vec![f64::INFINITY; n];
+            let mut previous: Vec<Option<(usize, usize)>> = vec![None; n];
+            let mut heap = BinaryHeap::new();
+            distance[source] = 0.0;
+            heap.push(HeapEntry(0.0, source));
+
+            while let Some(HeapEntry(d, node)) = heap.pop() {
+                if d > distance[node] {
+                    continue;
+                }
+                for (e, &(to, capacity, edge_cost, _)) in self.edges[node].iter().enumerate() {
+                    if capacity <= FLOW_EPSILON {
+                        continue;
+                    }
+                    let reduced = (edge_cost + potential[node] - potential[to]).max(0.0);
+                    if d + reduced < distance[to] {
+                        distance[to] = d + reduced;
+                        previous[to] = Some((node, e));
+                        heap.push(HeapEntry(distance[to], to));
+                    }
+                }
+            }
+
+            if distance[sink].is_infinite() {
+                break;
+            }
+            for (p, d) in potential.iter_mut().zip(distance.iter()) {
+                if d.is_finite() {
+                    *p += d;
+                }
+            }
+
+            let mut push = demand - flow;
+            let mut node = sink;
+            while let Some((from, e)) = previous[node] {
+                push = push.min(self.edges[from][e].1);
+                node = from;
+            }
+
+            let mut node = sink;
+            while let Some((from, e)) = previous[node] {
+                let (to, _, edge_cost, reverse) = self.edges[from][e];
+                self.edges[from][e].1 -= push;
+                self.edges[to][reverse].1 += push;
+                cost += push * edge_cost;
+                node = from;
+            }
+            flow += push;
+        }
+
+        (flow, cost)
+    }
+}
+
+/// Min-heap entry ordered by distance
+#[derive(PartialEq)]
+struct HeapEntry(f64, usize);
+
+impl Eq for HeapEntry {}
+
+impl PartialOrd for HeapEntry {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for HeapEntry {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.0.partial_cmp(&self.0).unwrap_or(Ordering::Equal).then(other.1.cmp(&self.1))
+    }
+}
+
+fn normalized_masses(masses: &[f64]) -> ConceptualResult<Vec<f64>> {
+    if masses.iter().any(|m| !m.is_finite() || *m < 0.0) {
+        return Err(ConceptualError::InvalidPoint("Masses must be finite and non-negative".to_string()));
+    }
+    let total: f64 = masses.iter().sum();
+    if total <= 0.0 {
+        return Err(ConceptualError::InvalidPoint("Masses must not all be zero".to_string()));
+    }
+    Ok(masses.iter().map(|m| m / total).collect())
+}
+
+impl SetDistance {
+    /// Distance of this kind between two sets, under a space's metric
+    pub fn between(
+        self,
+        space: &ConceptualSpace,
+        a: &[ConceptualPoint],
+        b: &[ConceptualPoint],
+    ) -> ConceptualResult<f64> {
+        match self {
+            SetDistance::Hausdorff => Ok(directed_hausdorff(space, a, b)?.max(directed_hausdorff(space, b, a)?)),
+            SetDistance::AverageLinkage => average_linkage(space, a, b),
+            SetDistance::EarthMovers => earth_movers(space, a, &vec![1.0; a.len()], b, &vec![1.0; b.len()]),
+        }
+    }
+}
+
+/// Largest distance from a point of `a` to its nearest point of `b`
+///
+/// `b` is indexed once so every nearest-point lookup is a tree query.
+pub(crate) fn directed_hausdorff(
+    space: &ConceptualSpace,
+    a: &[ConceptualPoint],
+    b: &[ConceptualPoint],
+) -> ConceptualResult<f64> {
+    require_points(a)?;
+    require_points(b)?;
+
+    let dimensions = space.dimension_ids.len();
+    require_dimensions(a, dimensions)?;
+    require_dimensions(b, dimensions)?;
+    let mut index = KdTreeIndex::new(dimensions, space.metric.as_distance_metric());
+    // Set members may lack ids or repeat them; index them by position
+    index.build_from_points(b.iter()
+        .enumerate()
+        .map(|(i, p)| ConceptualPoint { id: Some(Uuid::from_u128(i as u128)), ..p.clone() })
+        .collect())?;
+
+    let nearest = index.k_nearest_neighbors_batch(a, 1)?;
+    Ok(nearest.iter()
+        .filter_map(|found| found.first().map(|(_, d)| *d))
+        .fold(0.0, f64::max))
+}
+
+/// Mean distance over all pairs drawn one from each set
+pub(crate) fn average_linkage(space: &ConceptualSpace, a: &[ConceptualPoint], b: &[ConceptualPoint]) -> ConceptualResult<f64> {
+    require_points(a)?;
+    require_points(b)?;
+    let costs = cost_matrix(&space.metric.as_distance_metric(), a, b)?;
+    Ok(costs.mean())
+}
+
+/// Earth Mover's distance between weighted sets by min-cost flow
+pub(crate) fn earth_movers(
+    space: &ConceptualSpace,
+    a: &[ConceptualPoint],
+    a_masses: &[f64],
+    b: &[ConceptualPoint],
+    b_masses: &[f64],
+) -> ConceptualResult<f64> {
+    require_points(a)?;
+    require_points(b)?;
+    if a_masses.len() != a.len() || b_masses.len() != b.len() {
+        return Err(ConceptualError::InvalidPoint("Need one mass per point".to_string()));
+    }
+
+    let (a_masses, b_masses) = (normalized_masses(a_masses)?, normalized_masses(b_masses)?);
+    let costs = cost_matrix(&space.metric.as_distance_metric(), a, b)?;
+
+    // Source, then the points of a, then the points of b, then the sink
+    let (source, sink) = (0, a.len() + b.len() + 1);
+    let mut network = MinCostFlow::new(sink + 1);
+    for (i, mass) in a_masses.iter().enumerate() {
+        network.add_edge(source, 1 + i, *mass, 0.0);
+        for j in 0..b.len() {
+            network.add_edge(1 + i, 1 + a.len() + j, f64::INFINITY, costs[(i, j)]);
+        }
+    }
+    for (j, mass) in b_masses.iter().enumerate() {
+        network.add_edge(1 + a.len() + j, sink, *mass, 0.0);
+    }
+
+    let (_, cost) = network.run(source, sink, 1.0);
+    Ok(cost)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ConceptualMetric, ConvexRegion, DimensionId};
+    use std::collections::HashMap;
+
+    fn plane() -> ConceptualSpace {
+        ConceptualSpace::new(
+            "Plane".to_string(),
+            vec![DimensionId::new(), DimensionId::new()],
+            ConceptualMetric::uniform(2, 2.0),
+        )
+    }
+
+    fn points(coords: &[(f64, f64)]) -> Vec<ConceptualPoint> {
+        coords.iter().map(|(x, y)| ConceptualPoint::new(vec![*x, *y], HashMap::new())).collect()
+    }
+
+    #[test]
+    fn test_hausdorff_and_average_linkage() {
+        let space = plane();
+        let a = points(&[(0.0, 0.0), (1.0, 0.0)]);
+        let b = points(&[(0.0, 0.0), (1.0, 0.0), (4.0, 0.0)]);
+
+        assert_eq!(space.directed_hausdorff_distance(&a, &b).unwrap(), 0.0);
+        assert_eq!(space.directed_hausdorff_distance(&b, &a).unwrap(), 3.0);
+        assert_eq!(space.hausdorff_distance(&a, &b).unwrap(), 3.0);
+
+        // Pairs: 0,1,4 from the first point and 1,0,3 from the second
+        assert!((space.average_linkage_distance(&a, &b).unwrap() - 9.0 / 6.0).abs() < 1e-12);
+        assert!(space.hausdorff_distance(&a, &[]).is_err());
+    }
+
+    #[test]
+    fn test_earth_movers_transport() {
+        let space = plane();
+        let a = points(&[(0.0, 0.0), (0.0, 1.0)]);
+        let shifted = points(&[(3.0, 1.0), (3.0, 0.0)]);
+
+        // Each point moves straight across, whatever the listing order
+        assert!((space.earth_movers_distance(&a, &shifted).unwrap() - 3.0).abs() < 1e-9);
+        assert!(space.earth_movers_distance(&a, &a).unwrap().abs() < 1e-12);
+
+        // A single target takes all the mass: half travels 3, half travels √10
+        let single = points(&[(3.0, 0.0)]);
+        let expected = 0.5 * 3.0 + 0.5 * 10.0_f64.sqrt();
+        assert!((space.earth_movers_distance(&a, &single).unwrap() - expected).abs() < 1e-9);
+
+        // Unequal masses: 3/4 of the mass sits where the target is
+        let weighted = space.weighted_earth_movers_distance(&a, &[3.0, 1.0], &points(&[(0.0, 0.0)]), &[1.0]).unwrap();
+        assert!((weighted - 0.25).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_region_member_sets() {
+        let mut space = plane();
+        let mut regions = Vec::new();
+        for offset in [0.0, 5.0] {
+            let mut region = ConvexRegion::from_prototype(ConceptualPoint::new(vec![offset, 0.0], HashMap::new()));
+            for p in points(&[(offset, 0.0), (offset, 1.0)]) {
+                region.member_points.insert(space.add_point(p).unwrap());
+            }
+            regions.push(region.id);
+            space.add_region(region).unwrap();
+        }
+
+        for kind in [SetDistance::Hausdorff, SetDistance::EarthMovers] {
+            let distance = space.region_set_distance(&regions[0], &regions[1], kind).unwrap();
+            assert!((distance - 5.0).abs() < 1e-9, "{kind:?}: {distance}");
+        }
+        assert!(space.region_set_distance(&regions[0], &Uuid::new_v4(), SetDistance::AverageLinkage).is_err());
+    }
+}
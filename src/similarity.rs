@@ -3,9 +3,15 @@
 //! This module implements sophisticated similarity measures beyond basic distance metrics,
 //! including semantic similarity, contextual similarity, and domain-specific measures.
 
-use crate::{ConceptualPoint, ConceptualError, ConceptualResult, DistanceMetric, ConceptualSpace};
+use crate::events::ConceptualSpaceDomainEvent;
+use crate::{
+    CacheStats, ConceptTrajectory, ConceptualPoint, ConceptualError, ConceptualResult,
+    DistanceMetric, ConceptualSpace, MutationClock, SimilarityCache, SimilarityFunction,
+    WarpingWindow,
+};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::sync::{Mutex, MutexGuard};
 use uuid::Uuid;
 
 /// Advanced similarity computation engine
@@ -14,10 +20,24 @@ pub struct SimilarityEngine {
     pub base_metric: DistanceMetric,
 
     /// Context-dependent similarity weights
+    ///
+    /// Prefer `add_context_weights`, which also invalidates cached scores.
     pub context_weights: HashMap<String, Vec<f64>>,
 
-    /// Semantic similarity cache
-    similarity_cache: HashMap<(Uuid, Uuid), f64>,
+    /// Map from distance to similarity
+    pub similarity_function: SimilarityFunction,
+
+    /// Semantic similarity cache, shared by `&self` lookups
+    similarity_cache: Mutex<SimilarityCache>,
+
+    /// Learned similarities per point pair, kept out of the evictable cache
+    feedback: HashMap<(Uuid, Uuid), f64>,
+
+    /// Bumped whenever the geometry changes, orphaning cached scores
+    metric_version: u64,
+
+    /// Mutation clock of the space whose points are being compared
+    space_clock: Option<MutationClock>,
 }
 
 impl SimilarityEngine {
@@ -26,19 +46,144 @@ impl SimilarityEngine {
         Self {
             base_metric,
             context_weights: HashMap::new(),
-            similarity_cache: HashMap::new(),
+            similarity_function: SimilarityFunction::default(),
+            similarity_cache: Mutex::new(SimilarityCache::default()),
+            feedback: HashMap::new(),
+            metric_version: 0,
+            space_clock: None,
         }
     }
 
+    /// Bound the similarity cache to `capacity` entries
+    pub fn with_cache_capacity(mut self, capacity: usize) -> Self {
+        self.similarity_cache = Mutex::new(SimilarityCache::new(capacity));
+        self
+    }
+
+    /// Follow a space's mutations
+    ///
+    /// Cached scores are only served while the space is unchanged since
+    /// they were computed: adding, moving or removing a point, or replacing
+    /// its metric, orphans them. An aggregate exposes its space through
+    /// `ConceptualSpaceAggregate::space`.
+    pub fn tracking(mut self, space: &ConceptualSpace) -> Self {
+        self.track_space(space);
+        self
+    }
+
+    /// Follow a space's mutations, see `tracking`
+    pub fn track_space(&mut self, space: &ConceptualSpace) {
+        self.space_clock = Some(space.mutation_clock().watch());
+    }
+
+    /// Use a different distance-to-similarity function
+    pub fn with_similarity_function(mut self, function: SimilarityFunction) -> Self {
+        self.similarity_function = function;
+        self
+    }
+
     /// Add context-specific weights
     pub fn add_context_weights(&mut self, context: String, weights: Vec<f64>) {
         self.context_weights.insert(context, weights);
+        self.invalidate_metric();
+    }
+
+    /// Replace the base metric, invalidating cached scores
+    pub fn set_base_metric(&mut self, metric: DistanceMetric) {
+        self.base_metric = metric;
+        self.invalidate_metric();
+    }
+
+    /// Version of the geometry cached scores were computed under
+    pub fn metric_version(&self) -> u64 {
+        self.metric_version
+    }
+
+    /// Orphan every cached score after a change of geometry
+    pub fn invalidate_metric(&mut self) {
+        self.metric_version += 1;
+    }
+
+    /// Drop cached scores involving a point that moved or was removed
+    pub fn invalidate_point(&self, id: &Uuid) {
+        self.cache().invalidate_point(id);
+    }
+
+    /// Version cached scores are stored and looked up under
+    ///
+    /// Both parts only grow, so their sum changes whenever either does.
+    fn cache_version(&self) -> u64 {
+        self.metric_version + self.space_clock.as_ref().map_or(0, MutationClock::now)
+    }
+
+    /// Keep the cache consistent with a domain event
+    ///
+    /// Added or removed dimension weights orphan every score; an added (or
+    /// re-added) concept drops the scores involving it. Events are only
+    /// needed for spaces the engine does not track.
+    pub fn apply_event(&mut self, event: &ConceptualSpaceDomainEvent) {
+        match event {
+            ConceptualSpaceDomainEvent::WeightsAdded(_)
+            | ConceptualSpaceDomainEvent::WeightsRemoved(_) => self.invalidate_metric(),
+            ConceptualSpaceDomainEvent::ConceptAdded(added) => self.invalidate_point(&added.concept_id),
+            ConceptualSpaceDomainEvent::SpaceCreated(_)
+            | ConceptualSpaceDomainEvent::RegionAdded(_)
+            | ConceptualSpaceDomainEvent::CategoryFormed(_)
+            | ConceptualSpaceDomainEvent::CategoryRemoved(_)
+            | ConceptualSpaceDomainEvent::ConceptCategorized(_)
+            | ConceptualSpaceDomainEvent::ConceptUncategorized(_) => {}
+        }
+    }
+
+    fn cache(&self) -> MutexGuard<'_, SimilarityCache> {
+        // A panic mid-update leaves at worst a stale score, so keep going
+        self.similarity_cache.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
     }
 
-    /// Calculate basic similarity (inverse of distance)
+    /// Calculate basic similarity as a decreasing function of distance
     pub fn basic_similarity(&self, a: &ConceptualPoint, b: &ConceptualPoint) -> ConceptualResult<f64> {
         let distance = self.base_metric.calculate(a, b)?;
-        Ok(1.0 / (1.0 + distance))
+        Ok(self.similarity_function.similarity(distance))
+    }
+
+    /// Find the `k` points of a space most similar to a query
+    ///
+    /// Candidates come from the space's spatial index, so similarity here
+    /// follows the space metric rather than `base_metric`.
+    pub fn most_similar(
+        &self,
+        query: &ConceptualPoint,
+        space: &ConceptualSpace,
+        k: usize,
+    ) -> ConceptualResult<Vec<(Uuid, f64)>> {
+        Ok(space.k_nearest_neighbors(query, k)?
+            .into_iter()
+            .map(|(id, distance)| (*id, self.similarity_function.similarity(distance)))
+            .collect())
+    }
+
+    /// Context-aware similarity from a query to every point of a space
+    ///
+    /// Scores the whole point matrix with one batched kernel pass, using the
+    /// same weights as `contextual_similarity`. Points the metric cannot
+    /// score are left out.
+    pub fn similarities_in(
+        &self,
+        query: &ConceptualPoint,
+        space: &ConceptualSpace,
+        context: Option<&str>,
+    ) -> ConceptualResult<Vec<(Uuid, f64)>> {
+        let metric = match context.and_then(|c| self.context_weights.get(c)) {
+            Some(weights) => DistanceMetric::WeightedMinkowski { weights: weights.clone(), p: 2.0 },
+            None => self.base_metric.clone(),
+        };
+
+        let distances = space.points.distances(query, &metric)?;
+        Ok(space.points.ids().iter().copied()
+            .zip(distances.iter().copied())
+            .filter(|(_, d)| !d.is_nan())
+            .map(|(id, d)| (id, self.similarity_function.similarity(d)))
+            .collect())
     }
 
     /// Calculate context-aware similarity
@@ -51,18 +196,25 @@ impl SimilarityEngine {
         match context.and_then(|c| self.context_weights.get(c)) {
             Some(weights) => {
                 let weighted_distance = a.weighted_distance(b, weights, 2.0)?;
-                Ok(1.0 / (1.0 + weighted_distance))
+                Ok(self.similarity_function.similarity(weighted_distance))
             },
             None => self.basic_similarity(a, b)
         }
     }
 
     /// Calculate semantic similarity using embedding-like approach
+    ///
+    /// A similarity learned through `adaptive_similarity` takes precedence.
     pub fn semantic_similarity(&self, a: &ConceptualPoint, b: &ConceptualPoint) -> ConceptualResult<f64> {
+        let ids = a.id.zip(b.id);
+        if let Some(learned) = ids.and_then(|(id_a, id_b)| self.feedback.get(&pair_key(id_a, id_b))) {
+            return Ok(*learned);
+        }
+
         // Check cache first
-        if let (Some(id_a), Some(id_b)) = (a.id, b.id) {
-            let cache_key = if id_a < id_b { (id_a, id_b) } else { (id_b, id_a) };
-            if let Some(&cached_similarity) = self.similarity_cache.get(&cache_key) {
+        let version = self.cache_version();
+        if let Some((id_a, id_b)) = ids {
+            if let Some(cached_similarity) = self.cache().get(id_a, id_b, version) {
                 return Ok(cached_similarity);
             }
         }
@@ -81,10 +233,16 @@ impl SimilarityEngine {
         // Convert from [-1, 1] to [0, 1] range
         let similarity = (cosine_similarity + 1.0) / 2.0;
 
+        if let Some((id_a, id_b)) = ids {
+            self.cache().insert(id_a, id_b, version, similarity);
+        }
         Ok(similarity)
     }
 
     /// Calculate adaptive similarity that learns from feedback
+    ///
+    /// Learned similarities are kept apart from the cache, so they survive
+    /// eviction and metric changes until `clear_feedback`.
     pub fn adaptive_similarity(
         &mut self,
         a: &ConceptualPoint,
@@ -93,16 +251,14 @@ impl SimilarityEngine {
     ) -> ConceptualResult<f64> {
         let current_similarity = self.semantic_similarity(a, b)?;
 
-        // If feedback is provided, update the cache with weighted average
+        // If feedback is provided, learn the weighted average
         if let (Some(id_a), Some(id_b), Some(feedback_score)) = (a.id, b.id, feedback) {
-            let cache_key = if id_a < id_b { (id_a, id_b) } else { (id_b, id_a) };
-            
             // Learning rate for adaptation
             let learning_rate = 0.1;
             let updated_similarity = current_similarity * (1.0 - learning_rate) + 
                                    feedback_score * learning_rate;
             
-            self.similarity_cache.insert(cache_key, updated_similarity);
+            self.feedback.insert(pair_key(id_a, id_b), updated_similarity);
             Ok(updated_similarity)
         } else {
             Ok(current_similarity)
@@ -111,13 +267,29 @@ impl SimilarityEngine {
 
     /// Clear the similarity cache
     pub fn clear_cache(&mut self) {
-        self.similarity_cache.clear();
+        self.cache().clear();
+    }
+
+    /// Forget every similarity learned from feedback
+    pub fn clear_feedback(&mut self) {
+        self.feedback.clear();
     }
 
     /// Get cache statistics
     pub fn cache_stats(&self) -> (usize, usize) {
-        (self.similarity_cache.len(), self.similarity_cache.capacity())
+        let stats = self.cache_statistics();
+        (stats.entries, stats.capacity)
     }
+
+    /// Get cache size and hit, miss and eviction counts
+    pub fn cache_statistics(&self) -> CacheStats {
+        self.cache().stats()
+    }
+}
+
+/// Unordered point pair
+fn pair_key(a: Uuid, b: Uuid) -> (Uuid, Uuid) {
+    if a < b { (a, b) } else { (b, a) }
 }
 
 /// Advanced similarity algorithms implementation
@@ -150,14 +322,15 @@ impl AdvancedSimilarity {
         let shared_regions = regions_a.iter()
             .any(|region_a| regions_b.iter().any(|region_b| region_a.id == region_b.id));
 
+        let calibration = &space.similarity;
+        let similarity = space.point_similarity(point_a, point_b)?;
         if shared_regions {
             // Points in same category are highly similar
-            let distance = space.metric.distance(point_a, point_b)?;
-            Ok(0.9 + 0.1 / (1.0 + distance)) // High base similarity + distance adjustment
+            let floor = calibration.shared_category_floor;
+            Ok(floor + (1.0 - floor) * similarity)
         } else {
             // Standard distance-based similarity
-            let distance = space.metric.distance(point_a, point_b)?;
-            Ok(1.0 / (1.0 + distance))
+            Ok(similarity)
         }
     }
 
@@ -170,9 +343,9 @@ impl AdvancedSimilarity {
         space: &ConceptualSpace,
     ) -> ConceptualResult<f64> {
         let distance_to_prototype = space.metric.distance(point, prototype)?;
-        
-        // Exponential decay from prototype
-        Ok((-distance_to_prototype).exp())
+
+        // Decay from prototype, exponential unless the space says otherwise
+        Ok(space.similarity.prototype_function.similarity(distance_to_prototype))
     }
 
     /// Calculate salience-weighted similarity
@@ -209,7 +382,9 @@ impl AdvancedSimilarity {
 
     /// Calculate feature-based similarity
     ///
-    /// Similarity based on shared features rather than geometric distance
+    /// Similarity based on shared features rather than geometric distance.
+    /// This is a symmetric overlap score; see [`crate::TverskyModel`] for
+    /// directional judgements.
     pub fn feature_similarity(
         features_a: &HashMap<String, f64>,
         features_b: &HashMap<String, f64>,
@@ -229,7 +404,7 @@ impl AdvancedSimilarity {
             let value_a = features_a.get(*feature).unwrap_or(&0.0);
             let value_b = features_b.get(*feature).unwrap_or(&0.0);
             
-            // Tversky-like feature similarity
+            // Ratio of the smaller to the larger feature value
             let min_val = value_a.min(*value_b);
             let max_val = value_a.max(*value_b);
             
@@ -243,7 +418,8 @@ impl AdvancedSimilarity {
 
     /// Calculate temporal similarity for dynamic concepts
     ///
-    /// Concepts that change over time may have different similarity patterns
+    /// Concepts that change over time may have different similarity patterns.
+    /// See [`ConceptTrajectory`] for constrained warping and other distances.
     pub fn temporal_similarity(
         trajectory_a: &[ConceptualPoint],
         trajectory_b: &[ConceptualPoint],
@@ -253,33 +429,10 @@ impl AdvancedSimilarity {
             return Ok(0.0);
         }
 
-        // Dynamic Time Warping-like approach
-        let len_a = trajectory_a.len();
-        let len_b = trajectory_b.len();
-        let mut dtw_matrix = vec![vec![f64::INFINITY; len_b]; len_a];
-
-        // Initialize first cell
-        dtw_matrix[0][0] = metric.calculate(&trajectory_a[0], &trajectory_b[0])?;
-
-        // Initialize first row and column
-        for i in 1..len_a {
-            dtw_matrix[i][0] = dtw_matrix[i-1][0] + metric.calculate(&trajectory_a[i], &trajectory_b[0])?;
-        }
-        for j in 1..len_b {
-            dtw_matrix[0][j] = dtw_matrix[0][j-1] + metric.calculate(&trajectory_a[0], &trajectory_b[j])?;
-        }
-
-        // Fill the DTW matrix
-        for i in 1..len_a {
-            for j in 1..len_b {
-                let cost = metric.calculate(&trajectory_a[i], &trajectory_b[j])?;
-                dtw_matrix[i][j] = cost + dtw_matrix[i-1][j]
-                    .min(dtw_matrix[i][j-1])
-                    .min(dtw_matrix[i-1][j-1]);
-            }
-        }
-
-        let dtw_distance = dtw_matrix[len_a-1][len_b-1];
+        // Unconstrained dynamic time warping over the two paths
+        let a = ConceptTrajectory::from_points(Uuid::nil(), trajectory_a.to_vec())?;
+        let b = ConceptTrajectory::from_points(Uuid::nil(), trajectory_b.to_vec())?;
+        let dtw_distance = a.dtw_distance(&b, metric, WarpingWindow::Unconstrained)?;
         Ok(1.0 / (1.0 + dtw_distance))
     }
 
@@ -301,10 +454,7 @@ impl AdvancedSimilarity {
 
         // Level 0: Basic geometric similarity
         if !levels.is_empty() {
-            let geometric_sim = {
-                let distance = space.metric.distance(point_a, point_b)?;
-                1.0 / (1.0 + distance)
-            };
+            let geometric_sim = space.point_similarity(point_a, point_b)?;
             total_similarity += levels[0] * geometric_sim;
             total_weight += levels[0];
         }
@@ -0,0 +1,290 @@
+//! Bounded cache of pairwise similarities
+//!
+//! A least-recently-used cache keyed by the unordered pair of point ids and
+//! the version of the metric the similarity was computed under. Bumping the
+//! version makes every older entry unreachable; stale entries then age out
+//! through normal eviction.
+//!
+//! A space counts its own mutations on a [`MutationClock`]. A cache user
+//! watching that clock folds its reading into the version, so moving,
+//! adding or removing points orphans cached scores without any event.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use uuid::Uuid;
+
+/// Default number of similarities kept
+pub const DEFAULT_CACHE_CAPACITY: usize = 4096;
+
+/// Cache key: ordered point pair plus metric version
+type CacheKey = (Uuid, Uuid, u64);
+
+/// Usage counters of a similarity cache
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CacheStats {
+    /// Entries currently held
+    pub entries: usize,
+
+    /// Maximum number of entries
+    pub capacity: usize,
+
+    /// Lookups answered from the cache
+    pub hits: u64,
+
+    /// Lookups that had to be computed
+    pub misses: u64,
+
+    /// Entries dropped to make room
+    pub evictions: u64,
+}
+
+/// Mutation counter of a conceptual space
+///
+/// `watch` hands out a handle that observes the same counter. Cloning a
+/// clock, as happens when a space is cloned, starts an independent counter
+/// at the current reading, so watchers only follow the original space.
+#[derive(Debug, Default)]
+pub struct MutationClock(Arc<AtomicU64>);
+
+impl MutationClock {
+    /// Record one mutation
+    pub fn tick(&self) {
+        self.0.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Number of mutations recorded so far
+    pub fn now(&self) -> u64 {
+        self.0.load(Ordering::Relaxed)
+    }
+
+    /// A handle observing this clock
+    pub fn watch(&self) -> Self {
+        Self(Arc::clone(&self.0))
+    }
+}
+
+impl Clone for MutationClock {
+    fn clone(&self) -> Self {
+        Self(Arc::new(AtomicU64::new(self.now())))
+    }
+}
+
+/// Capacity-bounded LRU cache of pairwise similarities
+#[derive(Debug, Clone)]
+pub struct SimilarityCache {
+    /// Value and last-use tick per key
+    entries: HashMap<CacheKey, (f64, u64)>,
+
+    /// Keys by last-use tick, oldest first
+    recency: BTreeMap<u64, CacheKey>,
+
+    tick: u64,
+    stats: CacheStats,
+}
+
+impl SimilarityCache {
+    /// Create a cache holding at most `capacity` similarities
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            entries: HashMap::new(),
+            recency: BTreeMap::new(),
+            tick: 0,
+            stats: CacheStats { capacity, ..CacheStats::default() },
+        }
+    }
+
+    fn key(a: Uuid, b: Uuid, version: u64) -> CacheKey {
+        if a < b { (a, b, version) } else { (b, a, version) }
+    }
+
+    fn touch(&mut self, key: CacheKey) -> u64 {
+        self.tick += 1;
+        self.recency.insert(self.tick, key);
+        self.tick
+    }
+
+    /// Look up a similarity, counting the hit or miss
+    pub fn get(&mut self, a: Uuid, b: Uuid, version: u64) -> Option<f64> {
+        let key = Self::key(a, b, version);
+        let Some(&(value, last_used)) = self.entries.get(&key) else {
+            self.stats.misses += 1;
+            return None;
+        };
+
+        self.stats.hits += 1;
+        self.recency.remove(&last_used);
+        let tick = self.touch(key);
+        self.entries.insert(key, (value, tick));
+        Some(value)
+    }
+
+    /// Store a similarity, evicting the least recently used entry if full
+    pub fn insert(&mut self, a: Uuid, b: Uuid, version: u64, value: f64) {
+        if self.stats.capacity == 0 {
+            return;
+        }
+
+        let key = Self::key(a, b, version);
+        if let Some((_, last_used)) = self.entries.remove(&key) {
+            self.recency.remove(&last_used);
+        } else if self.entries.len() >= self.stats.capacity {
+            if let Some((_, oldest)) = self.recency.pop_first() {
+                self.entries.remove(&oldest);
+                self.stats.evictions += 1;
+            }
+        }
+
+        let tick = self.touch(key);
+        self.entries.insert(key, (value, tick));
+    }
+
+    /// Drop every entry involving a point
+    pub fn invalidate_point(&mut self, id: &Uuid) {
+        let stale: Vec<CacheKey> = self.entries.keys()
+            .filter(|(a, b, _)| a == id || b == id)
+            .copied()
+            .collect();
+        for key in stale {
+            if let Some((_, last_used)) = self.entries.remove(&key) {
+                self.recency.remove(&last_used);
+            }
+        }
+    }
+
+    /// Drop every entry, keeping the counters
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.recency.clear();
+    }
+
+    /// Current size and usage counters
+    pub fn stats(&self) -> CacheStats {
+        CacheStats { entries: self.entries.len(), ..self.stats }
+    }
+}
+
+impl Default for SimilarityCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_CACHE_CAPACITY)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::ConceptualSpaceDomainEvent;
+    use crate::{
+        ConceptAdded, ConceptualMetric, ConceptualPoint, ConceptualSpace, ConceptualSpaceId,
+        DimensionId, DimensionWeightsAdded, DimensionWeightsRemoved, DistanceMetric,
+        SimilarityEngine,
+    };
+
+    #[test]
+    fn test_lru_eviction_and_versions() {
+        let ids: Vec<Uuid> = (0..4).map(|_| Uuid::new_v4()).collect();
+        let mut cache = SimilarityCache::new(2);
+
+        cache.insert(ids[0], ids[1], 0, 0.5);
+        cache.insert(ids[1], ids[2], 0, 0.6);
+        // Pair order does not matter, and the hit makes (0, 1) most recent
+        assert_eq!(cache.get(ids[1], ids[0], 0), Some(0.5));
+        cache.insert(ids[2], ids[3], 0, 0.7);
+
+        assert_eq!(cache.get(ids[1], ids[2], 0), None);
+        assert_eq!(cache.get(ids[0], ids[1], 0), Some(0.5));
+        assert_eq!(cache.get(ids[0], ids[1], 1), None);
+
+        cache.invalidate_point(&ids[3]);
+        let stats = cache.stats();
+        assert_eq!(stats, CacheStats { entries: 1, capacity: 2, hits: 2, misses: 2, evictions: 1 });
+    }
+
+    #[test]
+    fn test_engine_invalidates_on_events() {
+        let mut engine = SimilarityEngine::new(DistanceMetric::Euclidean).with_cache_capacity(8);
+        let point = |coordinates| ConceptualPoint {
+            id: Some(Uuid::new_v4()),
+            ..ConceptualPoint::new(coordinates, HashMap::new())
+        };
+        let (mut a, b) = (point(vec![1.0, 0.0]), point(vec![1.0, 1.0]));
+
+        engine.semantic_similarity(&a, &b).unwrap();
+        engine.semantic_similarity(&a, &b).unwrap();
+        assert_eq!((engine.cache_statistics().hits, engine.cache_statistics().misses), (1, 1));
+
+        // A moved point must not be served its old score
+        a.coordinates[1] = 1.0;
+        engine.apply_event(&ConceptualSpaceDomainEvent::ConceptAdded(ConceptAdded {
+            space_id: ConceptualSpaceId::new(),
+            concept_id: a.id.unwrap(),
+            point: a.clone(),
+        }));
+        assert!((engine.semantic_similarity(&a, &b).unwrap() - 1.0).abs() < 1e-12);
+
+        engine.apply_event(&ConceptualSpaceDomainEvent::WeightsAdded(DimensionWeightsAdded {
+            space_id: ConceptualSpaceId::new(),
+            weights: vec![1.0, 2.0],
+            reason: "test".to_string(),
+        }));
+        assert_eq!(engine.metric_version(), 1);
+        engine.semantic_similarity(&a, &b).unwrap();
+
+        let stats = engine.cache_statistics();
+        assert_eq!((stats.hits, stats.misses, stats.entries), (1, 3, 2));
+        assert_eq!(engine.cache_stats(), (2, 8));
+
+        engine.apply_event(&ConceptualSpaceDomainEvent::WeightsRemoved(DimensionWeightsRemoved {
+            space_id: ConceptualSpaceId::new(),
+            removed_weights: vec![1.0, 2.0],
+            reason: "test".to_string(),
+        }));
+        assert_eq!(engine.metric_version(), 2);
+    }
+
+    #[test]
+    fn test_tracked_space_mutations_orphan_scores() {
+        let mut space = ConceptualSpace::new(
+            "Tracked".to_string(),
+            vec![DimensionId::new(), DimensionId::new()],
+            ConceptualMetric::uniform(2, 2.0),
+        );
+        let a = space.add_point(ConceptualPoint::new(vec![1.0, 0.0], HashMap::new())).unwrap();
+        let b = space.add_point(ConceptualPoint::new(vec![1.0, 1.0], HashMap::new())).unwrap();
+        let engine = SimilarityEngine::new(DistanceMetric::Euclidean).tracking(&space);
+
+        let score = |space: &ConceptualSpace| {
+            engine.semantic_similarity(&space.points.get(&a).unwrap(), &space.points.get(&b).unwrap()).unwrap()
+        };
+        let before = score(&space);
+        assert_eq!(score(&space), before);
+        assert_eq!(engine.cache_statistics().hits, 1);
+
+        // Moving a point through the space is enough, no event needed
+        space.move_point(&a, vec![2.0, 2.0]).unwrap();
+        assert!((score(&space) - 1.0).abs() < 1e-12);
+
+        let removed = space.remove_point(&b).unwrap().unwrap();
+        assert!(engine.semantic_similarity(&space.points.get(&a).unwrap(), &removed).is_ok());
+        assert_eq!(engine.cache_statistics().hits, 1);
+    }
+
+    #[test]
+    fn test_feedback_survives_eviction_and_metric_changes() {
+        let mut engine = SimilarityEngine::new(DistanceMetric::Euclidean).with_cache_capacity(1);
+        let point = |coordinates| ConceptualPoint::new(coordinates, HashMap::new());
+        let (a, b, c) = (point(vec![1.0, 0.0]), point(vec![0.0, 1.0]), point(vec![1.0, 1.0]));
+
+        let learned = engine.adaptive_similarity(&a, &b, Some(1.0)).unwrap();
+        assert!((learned - 0.55).abs() < 1e-12);
+
+        // Fill the single cache slot with another pair, then change the metric
+        engine.semantic_similarity(&a, &c).unwrap();
+        engine.set_base_metric(DistanceMetric::Manhattan);
+        assert_eq!(engine.semantic_similarity(&b, &a).unwrap(), learned);
+
+        engine.clear_feedback();
+        assert!((engine.semantic_similarity(&a, &b).unwrap() - 0.5).abs() < 1e-12);
+    }
+}
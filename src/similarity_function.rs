@@ -0,0 +1,323 @@
+//! Similarity as a function of distance
+//!
+//! Shepard's universal law of generalization says similarity decays with
+//! psychological distance, typically exponentially. This module provides a
+//! small family of decay functions, a per-space calibration choosing among
+//! them, and least-squares fitting of their parameters to human similarity
+//! ratings.
+
+use crate::{ConceptualError, ConceptualResult};
+use serde::{Deserialize, Serialize};
+
+/// A monotone map from distance to similarity in `(0, 1]`
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum SimilarityFunction {
+    /// Shepard's law: `exp(-c·d)`
+    Exponential {
+        /// Sensitivity to distance
+        c: f64,
+    },
+
+    /// Gaussian decay for confusable stimuli: `exp(-c·d²)`
+    Gaussian {
+        /// Sensitivity to distance
+        c: f64,
+    },
+
+    /// Inverse power: `1 / (1 + c·d)^p`
+    InversePower {
+        /// Sensitivity to distance
+        c: f64,
+        /// Decay exponent
+        p: f64,
+    },
+}
+
+impl Default for SimilarityFunction {
+    /// `1 / (1 + d)`, the crate's historical similarity
+    fn default() -> Self {
+        SimilarityFunction::InversePower { c: 1.0, p: 1.0 }
+    }
+}
+
+impl SimilarityFunction {
+    /// Similarity at a given distance
+    pub fn similarity(&self, distance: f64) -> f64 {
+        match *self {
+            SimilarityFunction::Exponential { c } => (-c * distance).exp(),
+            SimilarityFunction::Gaussian { c } => (-c * distance * distance).exp(),
+            SimilarityFunction::InversePower { c, p } => (1.0 + c * distance).powf(-p),
+        }
+    }
+
+    /// The family this function belongs to
+    pub fn family(&self) -> SimilarityFamily {
+        match self {
+            SimilarityFunction::Exponential { .. } => SimilarityFamily::Exponential,
+            SimilarityFunction::Gaussian { .. } => SimilarityFamily::Gaussian,
+            SimilarityFunction::InversePower { .. } => SimilarityFamily::InversePower,
+        }
+    }
+
+    /// Fit every family to `(distance, rating)` observations and keep the best
+    ///
+    /// Ratings are expected on the same `[0, 1]` scale as similarities.
+    pub fn fit(observations: &[(f64, f64)]) -> ConceptualResult<SimilarityFit> {
+        let mut best: Option<SimilarityFit> = None;
+        for family in [SimilarityFamily::Exponential, SimilarityFamily::Gaussian, SimilarityFamily::InversePower] {
+            let fit = Self::fit_family(family, observations)?;
+            if best.as_ref().is_none_or(|b| fit.sse < b.sse) {
+                best = Some(fit);
+            }
+        }
+        best.ok_or_else(|| ConceptualError::InvalidPoint("No similarity family to fit".to_string()))
+    }
+
+    /// Least-squares fit of one family to `(distance, rating)` observations
+    ///
+    /// Parameters are searched on a log scale: a coarse grid picks a
+    /// starting point, then golden-section steps refine it.
+    pub fn fit_family(family: SimilarityFamily, observations: &[(f64, f64)]) -> ConceptualResult<SimilarityFit> {
+        if observations.len() < 2 {
+            return Err(ConceptualError::InvalidPoint(
+                "At least two ratings are needed to fit a similarity function".to_string()
+            ));
+        }
+        if observations.iter().any(|(d, r)| !d.is_finite() || !r.is_finite() || *d < 0.0) {
+            return Err(ConceptualError::InvalidPoint(
+                "Observations need finite ratings and finite, non-negative distances".to_string()
+            ));
+        }
+
+        let function = match family {
+            SimilarityFamily::Exponential => {
+                let c = minimize_log(|c| sse(&SimilarityFunction::Exponential { c }, observations));
+                SimilarityFunction::Exponential { c }
+            }
+            SimilarityFamily::Gaussian => {
+                let c = minimize_log(|c| sse(&SimilarityFunction::Gaussian { c }, observations));
+                SimilarityFunction::Gaussian { c }
+            }
+            SimilarityFamily::InversePower => {
+                let (c, p) = minimize_log_2d(|c, p| sse(&SimilarityFunction::InversePower { c, p }, observations));
+                SimilarityFunction::InversePower { c, p }
+            }
+        };
+
+        Ok(SimilarityFit::new(function, observations))
+    }
+}
+
+/// The shape of a similarity function, without parameters
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum SimilarityFamily {
+    /// `exp(-c·d)`
+    Exponential,
+    /// `exp(-c·d²)`
+    Gaussian,
+    /// `1 / (1 + c·d)^p`
+    InversePower,
+}
+
+/// How a space turns distances into similarities
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct SimilarityCalibration {
+    /// Similarity between two points
+    pub function: SimilarityFunction,
+
+    /// Typicality of a point relative to a category prototype
+    pub prototype_function: SimilarityFunction,
+
+    /// Minimum similarity of two points sharing a category
+    ///
+    /// Distance-based similarity fills the remaining `1 - floor`.
+    pub shared_category_floor: f64,
+}
+
+impl Default for SimilarityCalibration {
+    fn default() -> Self {
+        Self {
+            function: SimilarityFunction::default(),
+            prototype_function: SimilarityFunction::Exponential { c: 1.0 },
+            shared_category_floor: 0.9,
+        }
+    }
+}
+
+/// Result of fitting a similarity function to ratings
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SimilarityFit {
+    /// The fitted function
+    pub function: SimilarityFunction,
+
+    /// Sum of squared residuals
+    pub sse: f64,
+
+    /// Coefficient of determination
+    pub r_squared: f64,
+
+    /// Number of ratings fitted
+    pub observations: usize,
+}
+
+impl SimilarityFit {
+    fn new(function: SimilarityFunction, observations: &[(f64, f64)]) -> Self {
+        let sse = sse(&function, observations);
+        let mean = observations.iter().map(|(_, r)| r).sum::<f64>() / observations.len() as f64;
+        let sst: f64 = observations.iter().map(|(_, r)| (r - mean).powi(2)).sum();
+        let r_squared = if sst > 0.0 {
+            1.0 - sse / sst
+        } else if sse == 0.0 {
+            1.0
+        } else {
+            0.0
+        };
+
+        Self {
+            function,
+            sse,
+            r_squared,
+            observations: observations.len(),
+        }
+    }
+}
+
+fn sse(function: &SimilarityFunction, observations: &[(f64, f64)]) -> f64 {
+    observations.iter()
+        .map(|(d, r)| (function.similarity(*d) - r).powi(2))
+        .sum()
+}
+
+/// Search range for parameters, as natural logs
+const LOG_RANGE: (f64, f64) = (-9.0, 9.0);
+
+/// Minimize over a positive parameter by grid scan and golden-section refinement
+fn minimize_log(f: impl Fn(f64) -> f64) -> f64 {
+    let g = |u: f64| f(u.exp());
+    let step = (LOG_RANGE.1 - LOG_RANGE.0) / 72.0;
+    let start = grid_argmin(&g, step);
+    golden_section(&g, start - step, start + step).exp()
+}
+
+/// Minimize over two positive parameters by profiling out the first
+///
+/// The two inverse-power parameters trade off along a curved ridge, which
+/// defeats coordinate descent; minimizing the first parameter exactly for
+/// every candidate second parameter follows the ridge instead.
+fn minimize_log_2d(f: impl Fn(f64, f64) -> f64) -> (f64, f64) {
+    let best_first = |v: f64| minimize_log(|x| f(x, v.exp()));
+    let profile = |v: f64| f(best_first(v), v.exp());
+
+    let step = (LOG_RANGE.1 - LOG_RANGE.0) / 72.0;
+    let start = grid_argmin(&profile, step);
+    let v = golden_section(&profile, start - step, start + step);
+    (best_first(v), v.exp())
+}
+
+fn grid_argmin(g: &impl Fn(f64) -> f64, step: f64) -> f64 {
+    let mut best = (LOG_RANGE.0, f64::INFINITY);
+    let mut u = LOG_RANGE.0;
+    while u <= LOG_RANGE.1 {
+        let value = g(u);
+        if value < best.1 {
+            best = (u, value);
+        }
+        u += step;
+    }
+    best.0
+}
+
+fn golden_section(g: &impl Fn(f64) -> f64, mut lo: f64, mut hi: f64) -> f64 {
+    let ratio = (5.0_f64.sqrt() - 1.0) / 2.0;
+    let mut a = hi - ratio * (hi - lo);
+    let mut b = lo + ratio * (hi - lo);
+    let (mut ga, mut gb) = (g(a), g(b));
+
+    for _ in 0..60 {
+        if ga <= gb {
+            hi = b;
+            b = a;
+            gb = ga;
+            a = hi - ratio * (hi - lo);
+            ga = g(a);
+        } else {
+            lo = a;
+            a = b;
+            ga = gb;
+            b = lo + ratio * (hi - lo);
+            gb = g(b);
+        }
+    }
+    (lo + hi) / 2.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ConceptualMetric, ConceptualPoint, ConceptualSpace, DimensionId};
+    use std::collections::HashMap;
+    use uuid::Uuid;
+
+    fn observations(function: SimilarityFunction) -> Vec<(f64, f64)> {
+        (0..30).map(|i| {
+            let d = i as f64 * 0.2;
+            (d, function.similarity(d))
+        })
+        .collect()
+    }
+
+    #[test]
+    fn test_defaults_reproduce_fixed_constants() {
+        let calibration = SimilarityCalibration::default();
+        assert_eq!(calibration.function.similarity(3.0), 1.0 / (1.0 + 3.0));
+        assert_eq!(calibration.prototype_function.similarity(3.0), (-3.0_f64).exp());
+        assert_eq!(calibration.shared_category_floor, 0.9);
+    }
+
+    #[test]
+    fn test_fit_recovers_each_family() {
+        let truths = [
+            SimilarityFunction::Exponential { c: 0.7 },
+            SimilarityFunction::Gaussian { c: 0.3 },
+            SimilarityFunction::InversePower { c: 2.0, p: 1.5 },
+        ];
+
+        for truth in truths {
+            let fit = SimilarityFunction::fit_family(truth.family(), &observations(truth)).unwrap();
+            assert!(fit.sse < 1e-8, "{truth:?} fitted as {:?}", fit.function);
+            assert!(fit.r_squared > 0.9999);
+
+            let best = SimilarityFunction::fit(&observations(truth)).unwrap();
+            assert_eq!(best.function.family(), truth.family());
+        }
+    }
+
+    #[test]
+    fn test_calibrate_space_from_ratings() {
+        let mut space = ConceptualSpace::new(
+            "Rated".to_string(),
+            vec![DimensionId::new()],
+            ConceptualMetric::uniform(1, 2.0),
+        );
+        let ids: Vec<Uuid> = (0..8)
+            .map(|i| space.add_point(ConceptualPoint::new(vec![i as f64 * 0.5], HashMap::new())).unwrap())
+            .collect();
+
+        let shepard = SimilarityFunction::Exponential { c: 1.2 };
+        let ratings: Vec<_> = ids.iter()
+            .skip(1)
+            .map(|b| {
+                let d = space.metric.distance(&space.points.get(&ids[0]).unwrap(), &space.points.get(b).unwrap()).unwrap();
+                (ids[0], *b, shepard.similarity(d))
+            })
+            .collect();
+
+        let fit = space.calibrate_similarity(&ratings).unwrap();
+        assert_eq!(fit.function.family(), SimilarityFamily::Exponential);
+        assert_eq!(space.similarity.function, fit.function);
+        assert!(fit.r_squared > 0.9999);
+
+        let missing = [(ids[0], Uuid::new_v4(), 0.5)];
+        assert!(space.fit_similarity(&missing).is_err());
+    }
+}
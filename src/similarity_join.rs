@@ -0,0 +1,304 @@
+//! Similarity joins between two point sets
+//!
+//! A join pairs every point of a left set with its counterparts in a right
+//! set, either all within a distance ε or the k nearest. The right side is
+//! always answered through a spatial index, and matches are streamed one
+//! left point at a time as `(left id, right id, distance)` triples. A dense
+//! right space on the flat scan index gets a KD-tree over its store for the
+//! duration of the join, so the join stays below n·m distance evaluations.
+
+use crate::{
+    ConceptualPoint, ConceptualResult, ConceptualSpace, DistanceMetric, KdTreeIndex, PointStorage,
+    SpaceIndex, SpatialIndex, SpatialIndexConfig,
+};
+use std::borrow::Cow;
+use std::collections::{HashMap, VecDeque};
+use uuid::Uuid;
+
+/// A matched pair: left id, right id and their distance
+pub type JoinPair = (Uuid, Uuid, f64);
+
+/// How left points are matched against the right side
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum JoinPredicate {
+    /// All right points within this distance
+    Epsilon(f64),
+
+    /// The k nearest right points
+    KNearest(usize),
+}
+
+/// Left-hand side of a join, walked one point at a time
+enum JoinSource<'a> {
+    /// Sorted ids of a space, each point read from its store when reached
+    Space {
+        space: &'a ConceptualSpace,
+        ids: std::vec::IntoIter<Uuid>,
+    },
+
+    /// Borrowed points; those without an id are skipped
+    Points(std::slice::Iter<'a, ConceptualPoint>),
+}
+
+impl<'a> JoinSource<'a> {
+    fn next_point(&mut self) -> Option<(Uuid, Cow<'a, ConceptualPoint>)> {
+        match self {
+            JoinSource::Space { space, ids } => ids.by_ref()
+                .find_map(|id| space.points.get(&id).map(|point| (id, Cow::Owned(point)))),
+            JoinSource::Points(points) => points.by_ref()
+                .find_map(|point| point.id.map(|id| (id, Cow::Borrowed(point)))),
+        }
+    }
+}
+
+/// Indexed right-hand side of a join
+enum JoinTarget<'a> {
+    /// A conceptual space queried through its own index and metric
+    Space(&'a ConceptualSpace),
+
+    /// A conceptual space queried through a KD-tree built over its store
+    Reindexed {
+        space: &'a ConceptualSpace,
+        index: SpaceIndex,
+    },
+
+    /// A bare point set indexed for the duration of the join
+    Points {
+        index: KdTreeIndex,
+        points: HashMap<Uuid, &'a ConceptualPoint>,
+        metric: DistanceMetric,
+    },
+}
+
+impl JoinTarget<'_> {
+    fn matches(&self, query: &ConceptualPoint, predicate: JoinPredicate) -> ConceptualResult<Vec<(Uuid, f64)>> {
+        match (self, predicate) {
+            (JoinTarget::Space(space), JoinPredicate::Epsilon(epsilon)) => {
+                Ok(space.range_search(query, epsilon)?
+                    .into_iter()
+                    .map(|(id, d)| (*id, d))
+                    .collect())
+            }
+            (JoinTarget::Space(space), JoinPredicate::KNearest(k)) => {
+                Ok(space.k_nearest_neighbors(query, k)?
+                    .into_iter()
+                    .map(|(id, d)| (*id, d))
+                    .collect())
+            }
+            (JoinTarget::Reindexed { space, index }, JoinPredicate::Epsilon(epsilon)) => {
+                let mut found = Vec::new();
+                for id in index.range_search(&space.points, query, epsilon)? {
+                    if let Some(point) = space.points.get(&id) {
+                        found.push((id, space.metric.distance(query, &point)?));
+                    }
+                }
+                found.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal));
+                Ok(found)
+            }
+            (JoinTarget::Reindexed { space, index }, JoinPredicate::KNearest(k)) => {
+                index.k_nearest_neighbors(&space.points, query, k)
+            }
+            (JoinTarget::Points { index, points, metric }, JoinPredicate::Epsilon(epsilon)) => {
+                let mut found = Vec::new();
+                for id in index.range_search(query, epsilon)? {
+                    if let Some(point) = points.get(&id) {
+                        found.push((id, metric.calculate(query, point)?));
+                    }
+                }
+                found.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal));
+                Ok(found)
+            }
+            (JoinTarget::Points { index, .. }, JoinPredicate::KNearest(k)) => {
+                index.k_nearest_neighbors(query, k)
+            }
+        }
+    }
+}
+
+/// Streaming similarity join
+///
+/// Each call to `next` yields one matched pair; the next left point is read
+/// and the right side queried only once the pending matches run out.
+///
+/// ```mermaid
+/// graph LR
+///     L[Left Point] --> Q[Index Query on Right]
+///     Q --> P[Pending Matches]
+///     P --> T["(left, right, distance)"]
+///     T --> L
+/// ```
+pub struct SimilarityJoin<'a> {
+    left: JoinSource<'a>,
+    right: JoinTarget<'a>,
+    predicate: JoinPredicate,
+    pending: VecDeque<JoinPair>,
+}
+
+impl<'a> SimilarityJoin<'a> {
+    /// Join two conceptual spaces, querying the right space's index
+    ///
+    /// A flat scan index on a dense right space is replaced for the join by
+    /// a KD-tree over the same store; sparse spaces keep scanning, as the
+    /// tree needs dense nodes.
+    pub fn spaces(left: &'a ConceptualSpace, right: &'a ConceptualSpace, predicate: JoinPredicate) -> Self {
+        let mut ids = left.points.ids().to_vec();
+        ids.sort();
+
+        let target = match Self::tree_over(right) {
+            Some(index) => JoinTarget::Reindexed { space: right, index },
+            None => JoinTarget::Space(right),
+        };
+
+        Self {
+            left: JoinSource::Space { space: left, ids: ids.into_iter() },
+            right: target,
+            predicate,
+            pending: VecDeque::new(),
+        }
+    }
+
+    /// A KD-tree over a dense space that only has the flat scan index
+    fn tree_over(space: &ConceptualSpace) -> Option<SpaceIndex> {
+        let flat = matches!(space.spatial_index(), SpaceIndex::Flat(_));
+        if !flat || space.points.storage() != PointStorage::Dense {
+            return None;
+        }
+        let mut index = SpaceIndex::new(
+            SpatialIndexConfig::KdTree,
+            space.points.dimensions(),
+            space.metric.as_distance_metric(),
+        );
+        index.rebuild(&space.points).ok()?;
+        Some(index)
+    }
+
+    /// Join two point sets under a metric, indexing the right set
+    ///
+    /// Points without an id cannot be reported and are skipped.
+    pub fn points(
+        left: &'a [ConceptualPoint],
+        right: &'a [ConceptualPoint],
+        metric: DistanceMetric,
+        predicate: JoinPredicate,
+    ) -> ConceptualResult<Self> {
+        let dimensions = right.first().map(|p| p.coordinates.len()).unwrap_or(1);
+        let mut index = KdTreeIndex::new(dimensions, metric.clone());
+        let right_points: HashMap<_, _> = right.iter()
+            .filter_map(|p| p.id.map(|id| (id, p)))
+            .collect();
+        index.build_from_points(right_points.values().map(|p| (*p).clone()).collect())?;
+
+        Ok(Self {
+            left: JoinSource::Points(left.iter()),
+            right: JoinTarget::Points { index, points: right_points, metric },
+            predicate,
+            pending: VecDeque::new(),
+        })
+    }
+}
+
+impl Iterator for SimilarityJoin<'_> {
+    type Item = ConceptualResult<JoinPair>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(pair) = self.pending.pop_front() {
+                return Some(Ok(pair));
+            }
+
+            let (left_id, point) = self.left.next_point()?;
+            match self.right.matches(&point, self.predicate) {
+                Ok(matches) => {
+                    self.pending.extend(matches.into_iter().map(|(right_id, d)| (left_id, right_id, d)));
+                }
+                Err(e) => return Some(Err(e)),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ConceptualMetric, DimensionId};
+
+    fn space_with(points: &[[f64; 2]]) -> (ConceptualSpace, Vec<Uuid>) {
+        let mut space = ConceptualSpace::new(
+            "Join Space".to_string(),
+            vec![DimensionId::new(), DimensionId::new()],
+            ConceptualMetric::uniform(2, 2.0),
+        );
+        let ids = points.iter()
+            .map(|c| space.add_point(ConceptualPoint::new(c.to_vec(), HashMap::new())).unwrap())
+            .collect();
+        (space, ids)
+    }
+
+    #[test]
+    fn test_epsilon_join_matches_brute_force() {
+        let (workflow, _) = space_with(&[[0.0, 0.0], [1.0, 1.0], [5.0, 5.0]]);
+        let (identity, _) = space_with(&[[0.1, 0.0], [1.2, 1.0], [1.0, 1.3], [9.0, 9.0]]);
+
+        let mut joined: Vec<_> = workflow.similarity_join(&identity, 0.5)
+            .collect::<ConceptualResult<Vec<_>>>()
+            .unwrap();
+        joined.sort_by_key(|pair| (pair.0, pair.1));
+
+        let mut expected = Vec::new();
+        for (l_id, l) in workflow.points.iter() {
+            for (r_id, r) in identity.points.iter() {
+                let d = identity.metric.distance(&l, &r).unwrap();
+                if d <= 0.5 {
+                    expected.push((*l_id, *r_id, d));
+                }
+            }
+        }
+        expected.sort_by_key(|pair| (pair.0, pair.1));
+
+        assert_eq!(joined.len(), 3);
+        assert_eq!(joined, expected);
+    }
+
+    #[test]
+    fn test_joins_agree_across_right_indexes() {
+        let coordinates = [[0.1, 0.0], [1.2, 1.0], [1.0, 1.3], [9.0, 9.0], [4.8, 5.1]];
+        let (workflow, _) = space_with(&[[0.0, 0.0], [1.0, 1.0], [5.0, 5.0]]);
+        let (flat, _) = space_with(&coordinates);
+        let tree = flat.clone().with_spatial_index(SpatialIndexConfig::KdTree).unwrap();
+
+        for predicate in [JoinPredicate::Epsilon(0.5), JoinPredicate::KNearest(2)] {
+            let join = |right: &ConceptualSpace| {
+                let mut pairs = SimilarityJoin::spaces(&workflow, right, predicate)
+                    .collect::<ConceptualResult<Vec<_>>>()
+                    .unwrap();
+                pairs.sort_by_key(|pair| (pair.0, pair.1));
+                pairs
+            };
+            let from_tree = join(&tree);
+            assert!(!from_tree.is_empty());
+            assert_eq!(from_tree, join(&flat));
+        }
+    }
+
+    #[test]
+    fn test_knn_join_over_point_sets() {
+        let left = vec![
+            ConceptualPoint::new(vec![0.0, 0.0], HashMap::new()),
+            ConceptualPoint::new(vec![4.0, 4.0], HashMap::new()),
+        ];
+        let right = vec![
+            ConceptualPoint::new(vec![0.5, 0.0], HashMap::new()),
+            ConceptualPoint::new(vec![3.0, 4.0], HashMap::new()),
+            ConceptualPoint::new(vec![4.0, 4.5], HashMap::new()),
+        ];
+
+        let joined: Vec<_> = SimilarityJoin::points(&left, &right, DistanceMetric::Euclidean, JoinPredicate::KNearest(1))
+            .unwrap()
+            .collect::<ConceptualResult<Vec<_>>>()
+            .unwrap();
+
+        let pairs: HashMap<_, _> = joined.iter().map(|(l, r, _)| (*l, *r)).collect();
+        assert_eq!(pairs[&left[0].id.unwrap()], right[0].id.unwrap());
+        assert_eq!(pairs[&left[1].id.unwrap()], right[2].id.unwrap());
+    }
+}
@@ -6,8 +6,16 @@
 //! - Regions representing natural concepts are convex
 //! - The space forms a natural shape based on the distribution of points
 
-use crate::{ConceptualError, ConceptualResult};
-use nalgebra::DVector;
+use crate::spatial_index::{
+    map_queries, ContentHasher, IndexSnapshot, SpaceIndex, SpatialIndexConfig,
+};
+use crate::set_distance;
+use crate::{
+    ConceptualError, ConceptualResult, DistanceMetric, JoinPredicate, KnnGraph, MutationClock,
+    MutualKnnGraph, PointStorage, PointStore, RegionSimilarity, RegionSimilarityConfig, SetDistance,
+    SimilarityCalibration, SimilarityFit, SimilarityFunction, SimilarityJoin, SparsePoint,
+};
+use nalgebra::{DMatrix, DMatrixView, DVector};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use uuid::Uuid;
@@ -142,12 +150,25 @@ impl ConceptualMetric {
             .collect()
     }
 
+    /// The equivalent `DistanceMetric`, used by spatial indexes
+    pub fn as_distance_metric(&self) -> DistanceMetric {
+        DistanceMetric::WeightedMinkowski {
+            weights: self.get_weights(),
+            p: self.minkowski_p,
+        }
+    }
+
     /// Calculate distance between two points using this metric
     pub fn distance(&self, p1: &ConceptualPoint, p2: &ConceptualPoint) -> ConceptualResult<f64> {
         let weights = self.get_weights();
         p1.weighted_distance(p2, &weights, self.minkowski_p)
     }
 
+    /// Distances from a query to every row of a point matrix
+    pub fn distances_to_rows(&self, query: &DVector<f64>, rows: DMatrixView<'_, f64>) -> ConceptualResult<DVector<f64>> {
+        self.as_distance_metric().distances_to_rows(query, rows)
+    }
+
     /// Create an open ball (neighborhood) around a point
     pub fn open_ball(&self, center: &ConceptualPoint, radius: f64) -> OpenBall {
         OpenBall {
@@ -179,8 +200,38 @@ impl OpenBall {
     }
 }
 
+/// Pairwise distances between the points of a space
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DistanceMatrix {
+    /// Point ids labelling both rows and columns
+    pub ids: Vec<Uuid>,
+
+    /// Symmetric matrix of distances under the space metric
+    pub distances: DMatrix<f64>,
+}
+
+impl DistanceMatrix {
+    /// Look up the distance between two points
+    pub fn get(&self, a: &Uuid, b: &Uuid) -> Option<f64> {
+        let i = self.ids.binary_search(a).ok()?;
+        let j = self.ids.binary_search(b).ok()?;
+        Some(self.distances[(i, j)])
+    }
+}
+
+/// Outcome of restoring an index snapshot into a space
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IndexRestore {
+    /// The snapshot matched the space and was installed as is
+    Restored,
+
+    /// The snapshot was stale and the index was rebuilt from the points
+    Rebuilt,
+}
+
 /// A topological conceptual space
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(try_from = "ConceptualSpaceRepr")]
 pub struct ConceptualSpace {
     /// Unique identifier for this space
     pub id: ConceptualSpaceId,
@@ -198,35 +249,296 @@ pub struct ConceptualSpace {
     pub regions: HashMap<Uuid, ConvexRegion>,
 
     /// All points in the space (forms the point cloud)
-    pub points: HashMap<Uuid, ConceptualPoint>,
+    ///
+    /// Read-only outside the crate; points change through `add_point`,
+    /// `move_point` and `remove_point` so the spatial index stays in sync.
+    /// Spaces stored before the point store, with points keyed by id, load
+    /// into it as well.
+    pub points: PointStore,
+
+    /// How distances in this space map to similarities
+    #[serde(default)]
+    pub similarity: SimilarityCalibration,
+
+    /// Spatial index over `points` answering all neighbour queries
+    ///
+    /// The default flat index scans `points` directly and holds no copy.
+    /// A space stored without one gets a flat index rebuilt on load.
+    index: SpaceIndex,
+
+    /// Counts point and metric changes for caches that depend on them
+    #[serde(skip)]
+    mutations: MutationClock,
+}
+
+/// Stored form of a space, also accepting layouts from before the point store
+#[derive(Deserialize)]
+struct ConceptualSpaceRepr {
+    id: ConceptualSpaceId,
+    name: String,
+    dimension_ids: Vec<DimensionId>,
+    metric: ConceptualMetric,
+    regions: HashMap<Uuid, ConvexRegion>,
+    points: StoredPoints,
+    #[serde(default)]
+    similarity: SimilarityCalibration,
+    #[serde(default)]
+    index: Option<SpaceIndex>,
+}
+
+/// Points as a point store, or keyed by id as older spaces stored them
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum StoredPoints {
+    Store(PointStore),
+    Map(HashMap<Uuid, ConceptualPoint>),
+}
+
+impl TryFrom<ConceptualSpaceRepr> for ConceptualSpace {
+    type Error = String;
+
+    fn try_from(repr: ConceptualSpaceRepr) -> Result<Self, Self::Error> {
+        let points = match repr.points {
+            StoredPoints::Store(store) => store,
+            StoredPoints::Map(map) => {
+                let mut legacy: Vec<_> = map.into_iter().collect();
+                legacy.sort_by_key(|(id, _)| *id);
+                let mut store = PointStore::new(&repr.dimension_ids);
+                for (id, mut point) in legacy {
+                    point.id = Some(id);
+                    store.insert(point).map_err(|e| e.to_string())?;
+                }
+                store
+            }
+        };
+
+        // An index that does not cover the loaded points is rebuilt in kind
+        let mut config = repr.index.as_ref().map_or_else(SpatialIndexConfig::default, SpaceIndex::config);
+        if Self::check_layout(config, points.storage()).is_err() {
+            config = SpatialIndexConfig::default();
+        }
+        let current = repr.index.filter(|index| {
+            index.config() == config && index.size() == points.len()
+        });
+        let rebuild = current.is_none();
+        let index = current.unwrap_or_else(|| {
+            SpaceIndex::new(config, repr.dimension_ids.len(), repr.metric.as_distance_metric())
+        });
+
+        let mut space = Self {
+            id: repr.id,
+            name: repr.name,
+            dimension_ids: repr.dimension_ids,
+            metric: repr.metric,
+            regions: repr.regions,
+            points,
+            similarity: repr.similarity,
+            index,
+            mutations: MutationClock::default(),
+        };
+        if rebuild {
+            space.rebuild_index().map_err(|e| e.to_string())?;
+        }
+        Ok(space)
+    }
 }
 
 impl ConceptualSpace {
     /// Create a new conceptual space
     pub fn new(name: String, dimension_ids: Vec<DimensionId>, metric: ConceptualMetric) -> Self {
+        let index = SpaceIndex::new(
+            SpatialIndexConfig::default(),
+            dimension_ids.len(),
+            metric.as_distance_metric(),
+        );
+        let points = PointStore::new(&dimension_ids);
+
         Self {
             id: ConceptualSpaceId::new(),
             name,
             dimension_ids,
             metric,
             regions: HashMap::new(),
-            points: HashMap::new(),
+            points,
+            similarity: SimilarityCalibration::default(),
+            index,
+            mutations: MutationClock::default(),
+        }
+    }
+
+    /// Use a different spatial index implementation
+    ///
+    /// Sparse spaces only support the flat index, see `with_point_storage`.
+    pub fn with_spatial_index(mut self, config: SpatialIndexConfig) -> ConceptualResult<Self> {
+        Self::check_layout(config, self.points.storage())?;
+        self.index = self.fresh_index(config);
+        self.rebuild_index()?;
+        Ok(self)
+    }
+
+    /// Store points densely or sparsely
+    ///
+    /// Sparse storage suits very high-dimensional spaces where each point
+    /// activates only a few dimensions. The flat index scans sparse rows
+    /// natively; the KD-tree needs dense nodes, so it is rejected here
+    /// rather than keeping a dense copy of every point.
+    pub fn with_point_storage(mut self, storage: PointStorage) -> ConceptualResult<Self> {
+        Self::check_layout(self.index.config(), storage)?;
+        self.points.set_storage(storage);
+        self.index = self.fresh_index(self.index.config());
+        self.rebuild_index()?;
+        Ok(self)
+    }
+
+    fn check_layout(config: SpatialIndexConfig, storage: PointStorage) -> ConceptualResult<()> {
+        if config == SpatialIndexConfig::KdTree && storage == PointStorage::Sparse {
+            return Err(ConceptualError::InvalidDimension(
+                "A KD-tree index cannot be kept over sparse point storage".to_string()
+            ));
+        }
+        Ok(())
+    }
+
+    fn fresh_index(&self, config: SpatialIndexConfig) -> SpaceIndex {
+        SpaceIndex::new(config, self.dimension_ids.len(), self.metric.as_distance_metric())
+    }
+
+    /// Clock ticked by every point and metric mutation
+    pub fn mutation_clock(&self) -> &MutationClock {
+        &self.mutations
+    }
+
+    /// The spatial index backing neighbour queries
+    pub fn spatial_index(&self) -> &SpaceIndex {
+        &self.index
+    }
+
+    /// Rebuild the spatial index from `points`
+    pub fn rebuild_index(&mut self) -> ConceptualResult<()> {
+        self.index.set_metric(self.metric.as_distance_metric());
+        self.index.rebuild(&self.points)
+    }
+
+    /// Hash of the points and metric that determine the index contents
+    ///
+    /// Points are hashed in id order from their raw coordinate bits, so the
+    /// value is stable across processes and map iteration order.
+    pub fn content_hash(&self) -> u64 {
+        let mut hasher = ContentHasher::new();
+
+        let mut ids: Vec<&Uuid> = self.points.keys().collect();
+        ids.sort();
+        for id in ids {
+            hasher.write(id.as_bytes());
+            for (index, value) in self.points.get_sparse(id).iter().flat_map(|p| p.entries()) {
+                hasher.write(&(index as u64).to_le_bytes());
+                hasher.write_f64(value);
+            }
+        }
+
+        for weight in self.metric.get_weights() {
+            hasher.write_f64(weight);
+        }
+        hasher.write_f64(self.metric.minkowski_p);
+        hasher.finish()
+    }
+
+    /// Snapshot the spatial index for later restoration
+    pub fn index_snapshot(&self) -> IndexSnapshot {
+        IndexSnapshot::new(self.index.clone(), self.content_hash())
+    }
+
+    /// Install a previously saved index, rebuilding it if it is stale
+    ///
+    /// A snapshot whose content hash no longer matches the space is
+    /// discarded and an index of the same kind is rebuilt from `points`.
+    pub fn restore_index(&mut self, snapshot: IndexSnapshot) -> ConceptualResult<IndexRestore> {
+        Self::check_layout(snapshot.index.config(), self.points.storage())?;
+        if snapshot.is_current(self.content_hash()) && snapshot.index.size() == self.points.len() {
+            self.index = snapshot.index;
+            return Ok(IndexRestore::Restored);
         }
+
+        self.index = self.fresh_index(snapshot.index.config());
+        self.rebuild_index()?;
+        Ok(IndexRestore::Rebuilt)
+    }
+
+    /// Replace the metric, re-keying the spatial index to it
+    pub fn set_metric(&mut self, metric: ConceptualMetric) {
+        self.index.set_metric(metric.as_distance_metric());
+        self.metric = metric;
+        self.mutations.tick();
     }
 
     /// Add a point to the space
-    pub fn add_point(&mut self, point: ConceptualPoint) -> ConceptualResult<Uuid> {
-        let id = point.id.unwrap_or_else(Uuid::new_v4);
-        self.points.insert(id, point);
+    ///
+    /// The point is stored in the space's dimension layout, so it must have
+    /// one coordinate per dimension.
+    pub fn add_point(&mut self, mut point: ConceptualPoint) -> ConceptualResult<Uuid> {
+        let id = *point.id.get_or_insert_with(Uuid::new_v4);
+        let existed = self.points.contains_key(&id);
+
+        self.points.insert(point)?;
+        self.mutations.tick();
+        if existed {
+            self.index.update(&self.points, &id)?;
+        } else {
+            self.index.insert(&self.points, &id)?;
+        }
+        Ok(id)
+    }
+
+    /// Add a sparse point to the space
+    ///
+    /// Its indices refer to positions in `dimension_ids`. In a sparse space
+    /// the point is stored and scanned without ever being densified.
+    pub fn add_sparse_point(&mut self, mut point: SparsePoint) -> ConceptualResult<Uuid> {
+        let id = *point.id.get_or_insert_with(Uuid::new_v4);
+        let existed = self.points.contains_key(&id);
+
+        self.points.insert_sparse(point)?;
+        self.mutations.tick();
+        if existed {
+            self.index.update(&self.points, &id)?;
+        } else {
+            self.index.insert(&self.points, &id)?;
+        }
         Ok(id)
     }
 
+    /// Move an existing point to new coordinates
+    pub fn move_point(&mut self, id: &Uuid, coordinates: Vec<f64>) -> ConceptualResult<()> {
+        self.points.set_coordinates(id, &coordinates)?;
+        self.mutations.tick();
+        self.index.update(&self.points, id)
+    }
+
+    fn stored_point(&self, id: &Uuid) -> ConceptualResult<ConceptualPoint> {
+        self.points.get(id).ok_or_else(|| {
+            ConceptualError::InvalidPoint(format!("Point {id} not found"))
+        })
+    }
+
+    /// Remove a point from the space and from any region memberships
+    pub fn remove_point(&mut self, id: &Uuid) -> ConceptualResult<Option<ConceptualPoint>> {
+        let Some(point) = self.points.remove(id) else {
+            return Ok(None);
+        };
+
+        self.mutations.tick();
+        self.index.remove(id)?;
+        for region in self.regions.values_mut() {
+            region.remove_member(id);
+        }
+        Ok(Some(point))
+    }
+
     /// Add a convex region to the space
     pub fn add_region(&mut self, region: ConvexRegion) -> ConceptualResult<()> {
         // Verify the region is actually convex
         let sample_points: Vec<_> = region.member_points.iter()
             .filter_map(|id| self.points.get(id))
-            .cloned()
             .collect();
 
         if !sample_points.is_empty() && !region.is_convex(&sample_points) {
@@ -239,6 +551,11 @@ impl ConceptualSpace {
         Ok(())
     }
 
+    /// Remove a region, returning it if it was present
+    pub fn remove_region(&mut self, id: &Uuid) -> Option<ConvexRegion> {
+        self.regions.remove(id)
+    }
+
     /// Find all regions that contain a given point
     pub fn find_containing_regions(&self, point: &ConceptualPoint) -> Vec<&ConvexRegion> {
         self.regions.values()
@@ -248,30 +565,104 @@ impl ConceptualSpace {
 
     /// Find k-nearest neighbors to a point
     pub fn k_nearest_neighbors(&self, point: &ConceptualPoint, k: usize) -> ConceptualResult<Vec<(&Uuid, f64)>> {
-        let mut distances: Vec<_> = self.points.iter()
-            .filter_map(|(id, p)| {
-                self.metric.distance(point, p).ok().map(|d| (id, d))
-            })
+        let neighbors = self.index.k_nearest_neighbors(&self.points, point, k)?;
+
+        Ok(neighbors.into_iter()
+            .filter_map(|(id, d)| self.points.key(&id).map(|id| (id, d)))
+            .collect())
+    }
+
+    /// Find all points within `radius` of a point, nearest first
+    pub fn range_search(&self, center: &ConceptualPoint, radius: f64) -> ConceptualResult<Vec<(&Uuid, f64)>> {
+        let mut found = Vec::new();
+
+        for id in self.index.range_search(&self.points, center, radius)? {
+            if let (Some(id), Some(point)) = (self.points.key(&id), self.points.get(&id)) {
+                found.push((id, self.metric.distance(center, &point)?));
+            }
+        }
+
+        found.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal));
+        Ok(found)
+    }
+
+    /// Find k-nearest neighbors for each of many query points
+    pub fn k_nearest_neighbors_batch(
+        &self,
+        queries: &[ConceptualPoint],
+        k: usize,
+    ) -> ConceptualResult<Vec<Vec<(Uuid, f64)>>> {
+        self.index.k_nearest_neighbors_batch(&self.points, queries, k)
+    }
+
+    /// Find all points within `radius` of each of many query points
+    pub fn range_search_batch(
+        &self,
+        centers: &[ConceptualPoint],
+        radius: f64,
+    ) -> ConceptualResult<Vec<Vec<Uuid>>> {
+        self.index.range_search_batch(&self.points, centers, radius)
+    }
+
+    /// Distances from a query to every point under the space metric
+    ///
+    /// One batched kernel pass over the stored rows, in store row order.
+    pub fn distances_from(&self, query: &ConceptualPoint) -> ConceptualResult<Vec<(Uuid, f64)>> {
+        let distances = self.points.distances(query, &self.metric.as_distance_metric())?;
+        Ok(self.points.ids().iter().copied().zip(distances.iter().copied()).collect())
+    }
+
+    /// Compute the pairwise distance matrix over all points
+    ///
+    /// Rows and columns follow `DistanceMatrix::ids`, which is sorted so the
+    /// layout is stable across runs.
+    pub fn distance_matrix(&self) -> ConceptualResult<DistanceMatrix> {
+        let mut order: Vec<(Uuid, usize)> = self.points.ids().iter()
+            .enumerate()
+            .map(|(row, id)| (*id, row))
             .collect();
+        order.sort();
+
+        let metric = self.metric.as_distance_metric();
+
+        // Each task scores one point against every row with the batched kernel
+        let rows = map_queries(&order, |(id, _)| {
+            let point = self.stored_point(id)?;
+            self.points.distances(&point, &metric)
+        })?;
 
-        distances.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap());
-        distances.truncate(k);
+        let n = order.len();
+        let distances = DMatrix::from_fn(n, n, |i, j| if i == j { 0.0 } else { rows[i][order[j].1] });
+        let ids = order.into_iter().map(|(id, _)| id).collect();
 
-        Ok(distances)
+        Ok(DistanceMatrix { ids, distances })
     }
 
     /// Compute the Voronoi cell for a prototype
-    pub fn voronoi_cell(&self, prototype: &ConceptualPoint) -> ConceptualResult<Vec<&ConceptualPoint>> {
-        let mut cell_points = Vec::new();
+    ///
+    /// Breaking: points are now returned owned, since the store keeps rows
+    /// rather than `ConceptualPoint` values to lend out. Callers that only
+    /// need to know which points fall in the cell can borrow their ids from
+    /// [`Self::voronoi_cell_ids`] instead.
+    pub fn voronoi_cell(&self, prototype: &ConceptualPoint) -> ConceptualResult<Vec<ConceptualPoint>> {
+        Ok(self.voronoi_cell_ids(prototype)?
+            .into_iter()
+            .filter_map(|id| self.points.get(id))
+            .collect())
+    }
 
-        for point in self.points.values() {
-            let dist_to_prototype = self.metric.distance(point, prototype)?;
+    /// Ids of the points in the Voronoi cell of a prototype, borrowed from the store
+    pub fn voronoi_cell_ids(&self, prototype: &ConceptualPoint) -> ConceptualResult<Vec<&Uuid>> {
+        let mut cell_ids = Vec::new();
+
+        for (id, point) in self.points.iter() {
+            let dist_to_prototype = self.metric.distance(&point, prototype)?;
 
             // Check if this prototype is the nearest
             let mut is_nearest = true;
             for other_region in self.regions.values() {
                 if other_region.prototype.id != prototype.id {
-                    let dist_to_other = self.metric.distance(point, &other_region.prototype)?;
+                    let dist_to_other = self.metric.distance(&point, &other_region.prototype)?;
                     if dist_to_other < dist_to_prototype {
                         is_nearest = false;
                         break;
@@ -280,11 +671,11 @@ impl ConceptualSpace {
             }
 
             if is_nearest {
-                cell_points.push(point);
+                cell_ids.push(id);
             }
         }
 
-        Ok(cell_points)
+        Ok(cell_ids)
     }
 
     /// Test if the space satisfies the axioms of a metric space
@@ -293,7 +684,7 @@ impl ConceptualSpace {
 
         for i in 0..points.len() {
             for j in 0..points.len() {
-                let d_ij = self.metric.distance(points[i], points[j])?;
+                let d_ij = self.metric.distance(&points[i], &points[j])?;
 
                 // Non-negativity
                 if d_ij < 0.0 {
@@ -306,15 +697,15 @@ impl ConceptualSpace {
                 }
 
                 // Symmetry
-                let d_ji = self.metric.distance(points[j], points[i])?;
+                let d_ji = self.metric.distance(&points[j], &points[i])?;
                 if (d_ij - d_ji).abs() > f64::EPSILON {
                     return Ok(false);
                 }
 
                 // Triangle inequality
                 for k in 0..points.len() {
-                    let d_ik = self.metric.distance(points[i], points[k])?;
-                    let d_kj = self.metric.distance(points[k], points[j])?;
+                    let d_ik = self.metric.distance(&points[i], &points[k])?;
+                    let d_kj = self.metric.distance(&points[k], &points[j])?;
                     if d_ij > d_ik + d_kj + f64::EPSILON {
                         return Ok(false);
                     }
@@ -324,5 +715,170 @@ impl ConceptualSpace {
 
         Ok(true)
     }
+
+    /// Similarity between two points under the space calibration
+    pub fn point_similarity(&self, a: &ConceptualPoint, b: &ConceptualPoint) -> ConceptualResult<f64> {
+        Ok(self.similarity.function.similarity(self.metric.distance(a, b)?))
+    }
+
+    /// Fit a similarity function to `(point_a, point_b, rating)` triples
+    ///
+    /// Distances come from the space metric; every referenced point must be
+    /// in the space.
+    pub fn fit_similarity(&self, ratings: &[(Uuid, Uuid, f64)]) -> ConceptualResult<SimilarityFit> {
+        let observations = ratings.iter()
+            .map(|(a, b, rating)| {
+                let lookup = |id: &Uuid| self.points.get(id).ok_or_else(|| {
+                    ConceptualError::InvalidPoint(format!("Point {id} not found"))
+                });
+                Ok((self.metric.distance(&lookup(a)?, &lookup(b)?)?, *rating))
+            })
+            .collect::<ConceptualResult<Vec<_>>>()?;
+
+        SimilarityFunction::fit(&observations)
+    }
+
+    /// Fit a similarity function to ratings and adopt it for the space
+    pub fn calibrate_similarity(&mut self, ratings: &[(Uuid, Uuid, f64)]) -> ConceptualResult<SimilarityFit> {
+        let fit = self.fit_similarity(ratings)?;
+        self.similarity.function = fit.function;
+        Ok(fit)
+    }
+
+    /// Pair each point with every point of `other` within `epsilon`
+    ///
+    /// Distances follow `other`'s metric, since its index answers the queries.
+    pub fn similarity_join<'a>(&'a self, other: &'a ConceptualSpace, epsilon: f64) -> SimilarityJoin<'a> {
+        SimilarityJoin::spaces(self, other, JoinPredicate::Epsilon(epsilon))
+    }
+
+    /// Pair each point with its `k` nearest points in `other`
+    pub fn knn_join<'a>(&'a self, other: &'a ConceptualSpace, k: usize) -> SimilarityJoin<'a> {
+        SimilarityJoin::spaces(self, other, JoinPredicate::KNearest(k))
+    }
+
+    /// Build the directed k-NN graph over all points
+    ///
+    /// ```mermaid
+    /// graph LR
+    ///     P[All Points] --> B[Batched k+1 NN Queries]
+    ///     B --> S[Drop Self Matches]
+    ///     S --> G[Directed Edges a -> b]
+    /// ```
+    pub fn knn_graph(&self, k: usize) -> ConceptualResult<KnnGraph> {
+        KnnGraph::build(self, k)
+    }
+
+    /// Build the mutual k-NN graph over all points
+    pub fn mutual_knn_graph(&self, k: usize) -> ConceptualResult<MutualKnnGraph> {
+        Ok(self.knn_graph(k)?.to_mutual())
+    }
+
+    /// Find the points that would count `query` among their k nearest neighbours
+    ///
+    /// A point `p` qualifies when `query` is no farther from it than its own
+    /// k-th nearest neighbour. The k-distances come from `graph`, which must
+    /// have been built over this space's current points, so one range search
+    /// out to the largest k-distance finds every candidate. If `query` is
+    /// itself in the space it is not reported as its own reverse neighbour.
+    pub fn reverse_k_nearest_neighbors(
+        &self,
+        graph: &KnnGraph,
+        query: &ConceptualPoint,
+    ) -> ConceptualResult<Vec<(Uuid, f64)>> {
+        graph.reverse_neighbors_of(self, query)
+    }
+
+    /// Compare two regions of this space
+    pub fn region_similarity(
+        &self,
+        a: &Uuid,
+        b: &Uuid,
+        config: &RegionSimilarityConfig,
+    ) -> ConceptualResult<RegionSimilarity> {
+        RegionSimilarity::between(self, a, b, config)
+    }
+
+    /// Region pairs scoring at least `threshold`, most similar first
+    pub fn redundant_regions(
+        &self,
+        threshold: f64,
+        config: &RegionSimilarityConfig,
+    ) -> ConceptualResult<Vec<(Uuid, Uuid, RegionSimilarity)>> {
+        RegionSimilarity::redundant(self, threshold, config)
+    }
+
+    /// Largest distance from a point of `a` to its nearest point of `b`
+    ///
+    /// `b` is indexed once so every nearest-point lookup is a tree query.
+    pub fn directed_hausdorff_distance(
+        &self,
+        a: &[ConceptualPoint],
+        b: &[ConceptualPoint],
+    ) -> ConceptualResult<f64> {
+        set_distance::directed_hausdorff(self, a, b)
+    }
+
+    /// Hausdorff distance: the larger of the two directed distances
+    ///
+    /// ```mermaid
+    /// graph LR
+    ///     A[Set A] --> IA[Nearest in B via Index]
+    ///     B[Set B] --> IB[Nearest in A via Index]
+    ///     IA --> M[Max over A]
+    ///     IB --> N[Max over B]
+    ///     M --> H[max of both]
+    ///     N --> H
+    /// ```
+    pub fn hausdorff_distance(&self, a: &[ConceptualPoint], b: &[ConceptualPoint]) -> ConceptualResult<f64> {
+        SetDistance::Hausdorff.between(self, a, b)
+    }
+
+    /// Mean distance over all pairs drawn one from each set
+    pub fn average_linkage_distance(&self, a: &[ConceptualPoint], b: &[ConceptualPoint]) -> ConceptualResult<f64> {
+        SetDistance::AverageLinkage.between(self, a, b)
+    }
+
+    /// Earth Mover's distance with equal mass on every point
+    pub fn earth_movers_distance(&self, a: &[ConceptualPoint], b: &[ConceptualPoint]) -> ConceptualResult<f64> {
+        SetDistance::EarthMovers.between(self, a, b)
+    }
+
+    /// Earth Mover's distance between weighted sets
+    ///
+    /// Both mass vectors are normalised to one, so the result is the
+    /// average distance mass travels under the cheapest transport plan.
+    pub fn weighted_earth_movers_distance(
+        &self,
+        a: &[ConceptualPoint],
+        a_masses: &[f64],
+        b: &[ConceptualPoint],
+        b_masses: &[f64],
+    ) -> ConceptualResult<f64> {
+        set_distance::earth_movers(self, a, a_masses, b, b_masses)
+    }
+
+    /// Distance between two sets of the chosen kind
+    pub fn set_distance(
+        &self,
+        a: &[ConceptualPoint],
+        b: &[ConceptualPoint],
+        kind: SetDistance,
+    ) -> ConceptualResult<f64> {
+        kind.between(self, a, b)
+    }
+
+    /// Distance between the member sets of two regions of this space
+    pub fn region_set_distance(&self, a: &Uuid, b: &Uuid, kind: SetDistance) -> ConceptualResult<f64> {
+        let members = |id: &Uuid| -> ConceptualResult<Vec<ConceptualPoint>> {
+            let region = self.regions.get(id)
+                .ok_or_else(|| ConceptualError::InvalidPoint(format!("Region {id} not found")))?;
+            let mut ids: Vec<&Uuid> = region.member_points.iter().collect();
+            ids.sort();
+            Ok(ids.into_iter().filter_map(|id| self.points.get(id)).collect())
+        };
+
+        self.set_distance(&members(a)?, &members(b)?, kind)
+    }
 }
 
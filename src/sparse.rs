@@ -0,0 +1,369 @@
+//! Sparse conceptual points
+//!
+//! Concepts derived from feature extraction usually activate a handful of
+//! dimensions out of thousands. A `SparsePoint` keeps only the non-zero
+//! coordinates, sorted by dimension index, and computes distances by merging
+//! index lists, so the cost follows the number of active dimensions rather
+//! than the size of the space.
+
+use crate::{ConceptualError, ConceptualPoint, ConceptualResult, DimensionId, DistanceMetric};
+use nalgebra::DVector;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// A point storing only its non-zero coordinates
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "SparsePointRepr", into = "SparsePointRepr")]
+pub struct SparsePoint {
+    /// Total number of dimensions
+    dimensions: usize,
+
+    /// Dimension indices of the non-zero coordinates, strictly increasing
+    indices: Vec<usize>,
+
+    /// Coordinate values matching `indices`
+    values: Vec<f64>,
+
+    /// Optional point identifier
+    pub id: Option<Uuid>,
+}
+
+impl SparsePoint {
+    /// Create a sparse point from `(dimension index, value)` entries
+    ///
+    /// Entries may come in any order; repeated indices are summed and zeros
+    /// are dropped.
+    pub fn new(dimensions: usize, entries: impl IntoIterator<Item = (usize, f64)>) -> ConceptualResult<Self> {
+        let mut entries: Vec<(usize, f64)> = entries.into_iter().collect();
+        if let Some((index, _)) = entries.iter().find(|(i, _)| *i >= dimensions) {
+            return Err(ConceptualError::InvalidDimension(format!(
+                "Index {index} outside {dimensions} dimensions"
+            )));
+        }
+        entries.sort_by_key(|(i, _)| *i);
+
+        let mut indices: Vec<usize> = Vec::with_capacity(entries.len());
+        let mut values: Vec<f64> = Vec::with_capacity(entries.len());
+        for (index, value) in entries {
+            if indices.last() == Some(&index) {
+                *values.last_mut().unwrap() += value;
+            } else {
+                indices.push(index);
+                values.push(value);
+            }
+        }
+
+        let mut point = Self {
+            dimensions,
+            indices,
+            values,
+            id: Some(Uuid::new_v4()),
+        };
+        point.prune_zeros();
+        Ok(point)
+    }
+
+    /// Sparse copy of a dense point, keeping its id
+    pub fn from_dense(point: &ConceptualPoint) -> Self {
+        Self::from_coordinates(point.coordinates.as_slice(), point.id)
+    }
+
+    /// Sparse copy of a dense coordinate slice
+    pub(crate) fn from_coordinates(coordinates: &[f64], id: Option<Uuid>) -> Self {
+        let mut point = Self {
+            dimensions: 0,
+            indices: Vec::new(),
+            values: Vec::new(),
+            id,
+        };
+        point.assign_dense(coordinates);
+        point
+    }
+
+    /// Dense copy of this point with the given dimension map
+    pub fn to_dense(&self, dimension_map: HashMap<DimensionId, usize>) -> ConceptualPoint {
+        ConceptualPoint {
+            coordinates: self.to_vector(),
+            dimension_map,
+            id: self.id,
+        }
+    }
+
+    /// All coordinates as a dense vector
+    pub fn to_vector(&self) -> DVector<f64> {
+        let mut coordinates = DVector::zeros(self.dimensions);
+        for (i, v) in self.entries() {
+            coordinates[i] = v;
+        }
+        coordinates
+    }
+
+    /// Total number of dimensions
+    pub fn dimensions(&self) -> usize {
+        self.dimensions
+    }
+
+    /// Number of non-zero coordinates
+    pub fn nnz(&self) -> usize {
+        self.indices.len()
+    }
+
+    /// Dimension indices of the non-zero coordinates
+    pub fn indices(&self) -> &[usize] {
+        &self.indices
+    }
+
+    /// Values of the non-zero coordinates
+    pub fn values(&self) -> &[f64] {
+        &self.values
+    }
+
+    /// Iterate over `(dimension index, value)` for the non-zero coordinates
+    pub fn entries(&self) -> impl Iterator<Item = (usize, f64)> + '_ {
+        self.indices.iter().copied().zip(self.values.iter().copied())
+    }
+
+    /// Value along one dimension
+    pub fn get(&self, index: usize) -> f64 {
+        self.indices.binary_search(&index).map(|pos| self.values[pos]).unwrap_or(0.0)
+    }
+
+    /// Euclidean norm
+    pub fn norm(&self) -> f64 {
+        self.values.iter().map(|v| v * v).sum::<f64>().sqrt()
+    }
+
+    /// Dot product with a dense vector
+    pub fn dot_dense(&self, other: &DVector<f64>) -> f64 {
+        self.entries().map(|(i, v)| v * other[i]).sum()
+    }
+
+    /// Weighted Minkowski distance, matching `ConceptualPoint::weighted_distance`
+    pub fn weighted_distance(&self, other: &SparsePoint, weights: &[f64], p: f64) -> ConceptualResult<f64> {
+        self.distance(other, &DistanceMetric::WeightedMinkowski { weights: weights.to_vec(), p })
+    }
+
+    /// Distance to another sparse point
+    pub fn distance(&self, other: &SparsePoint, metric: &DistanceMetric) -> ConceptualResult<f64> {
+        metric_distance(self.dimensions, self.entries(), other.dimensions, other.entries(), metric)
+    }
+
+    /// Distance to a dense point
+    pub fn distance_to_dense(&self, other: &ConceptualPoint, metric: &DistanceMetric) -> ConceptualResult<f64> {
+        metric_distance(
+            self.dimensions,
+            self.entries(),
+            other.coordinates.len(),
+            dense_entries(&other.coordinates),
+            metric,
+        )
+    }
+
+    /// Overwrite all coordinates from a dense slice
+    pub(crate) fn assign_dense(&mut self, coordinates: &[f64]) {
+        self.dimensions = coordinates.len();
+        (self.indices, self.values) = coordinates.iter()
+            .enumerate()
+            .filter(|(_, v)| **v != 0.0)
+            .map(|(i, v)| (i, *v))
+            .unzip();
+    }
+
+    fn prune_zeros(&mut self) {
+        let (indices, values) = self.entries().filter(|(_, v)| *v != 0.0).unzip();
+        self.indices = indices;
+        self.values = values;
+    }
+}
+
+/// Non-zero entries of a dense vector
+fn dense_entries(coordinates: &DVector<f64>) -> impl Iterator<Item = (usize, f64)> + '_ {
+    coordinates.iter().copied().enumerate().filter(|(_, v)| *v != 0.0)
+}
+
+/// Visit the union of two sorted entry lists, with zero for missing sides
+fn merge_entries(
+    a: impl Iterator<Item = (usize, f64)>,
+    b: impl Iterator<Item = (usize, f64)>,
+    mut visit: impl FnMut(usize, f64, f64),
+) {
+    let (mut a, mut b) = (a.peekable(), b.peekable());
+    loop {
+        match (a.peek().copied(), b.peek().copied()) {
+            (Some((i, x)), Some((j, y))) => match i.cmp(&j) {
+                Ordering::Less => {
+                    visit(i, x, 0.0);
+                    a.next();
+                }
+                Ordering::Greater => {
+                    visit(j, 0.0, y);
+                    b.next();
+                }
+                Ordering::Equal => {
+                    visit(i, x, y);
+                    a.next();
+                    b.next();
+                }
+            },
+            (Some((i, x)), None) => {
+                visit(i, x, 0.0);
+                a.next();
+            }
+            (None, Some((j, y))) => {
+                visit(j, 0.0, y);
+                b.next();
+            }
+            (None, None) => break,
+        }
+    }
+}
+
+/// Distance between two sorted entry lists
+///
+/// Dimensions where both sides are zero contribute nothing to any metric,
+/// so only the union of the non-zero indices is visited.
+fn metric_distance(
+    dimensions_a: usize,
+    a: impl Iterator<Item = (usize, f64)>,
+    dimensions_b: usize,
+    b: impl Iterator<Item = (usize, f64)>,
+    metric: &DistanceMetric,
+) -> ConceptualResult<f64> {
+    if dimensions_a != dimensions_b {
+        return Err(ConceptualError::InvalidPoint(
+            "Points have different dimensions".to_string()
+        ));
+    }
+
+    let check_weights = |weights: &[f64]| {
+        if weights.len() == dimensions_a {
+            Ok(())
+        } else {
+            Err(ConceptualError::InvalidDimension(
+                "Weight vector has incorrect length".to_string()
+            ))
+        }
+    };
+
+    let mut sum = 0.0;
+    match metric {
+        DistanceMetric::Euclidean => {
+            merge_entries(a, b, |_, x, y| sum += (x - y) * (x - y));
+            Ok(sum.sqrt())
+        }
+        DistanceMetric::Manhattan => {
+            merge_entries(a, b, |_, x, y| sum += (x - y).abs());
+            Ok(sum)
+        }
+        DistanceMetric::WeightedEuclidean { weights } => {
+            check_weights(weights)?;
+            merge_entries(a, b, |j, x, y| sum += weights[j] * (x - y) * (x - y));
+            Ok(sum.sqrt())
+        }
+        DistanceMetric::WeightedMinkowski { weights, p } => {
+            check_weights(weights)?;
+            merge_entries(a, b, |j, x, y| sum += weights[j] * (x - y).abs().powf(*p));
+            Ok(sum.powf(1.0 / p))
+        }
+        DistanceMetric::Cosine => {
+            let (mut norm_a, mut norm_b) = (0.0, 0.0);
+            merge_entries(a, b, |_, x, y| {
+                sum += x * y;
+                norm_a += x * x;
+                norm_b += y * y;
+            });
+
+            if norm_a == 0.0 || norm_b == 0.0 {
+                return Err(ConceptualError::InvalidPoint(
+                    "Cannot calculate cosine distance for zero vector".to_string()
+                ));
+            }
+            Ok(1.0 - sum / (norm_a.sqrt() * norm_b.sqrt()))
+        }
+        DistanceMetric::Custom(name) => {
+            Err(ConceptualError::InvalidDimension(
+                format!("Custom metric '{name}' not implemented")
+            ))
+        }
+    }
+}
+
+/// Serialized form of a `SparsePoint`, validated on the way in
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct SparsePointRepr {
+    dimensions: usize,
+    indices: Vec<usize>,
+    values: Vec<f64>,
+    id: Option<Uuid>,
+}
+
+impl From<SparsePoint> for SparsePointRepr {
+    fn from(point: SparsePoint) -> Self {
+        Self {
+            dimensions: point.dimensions,
+            indices: point.indices,
+            values: point.values,
+            id: point.id,
+        }
+    }
+}
+
+impl TryFrom<SparsePointRepr> for SparsePoint {
+    type Error = String;
+
+    fn try_from(repr: SparsePointRepr) -> Result<Self, Self::Error> {
+        if repr.indices.len() != repr.values.len() {
+            return Err("Sparse indices and values differ in length".to_string());
+        }
+
+        let mut point = SparsePoint::new(repr.dimensions, repr.indices.into_iter().zip(repr.values))
+            .map_err(|e| e.to_string())?;
+        point.id = repr.id;
+        Ok(point)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Hyperplane;
+
+    #[test]
+    fn test_sparse_distances_match_dense() {
+        let a = SparsePoint::new(6, [(4, 2.0), (1, 0.5), (4, 1.0), (2, 0.0)]).unwrap();
+        let b = SparsePoint::new(6, [(0, -1.0), (4, 1.5)]).unwrap();
+        assert_eq!(a.indices(), &[1, 4]);
+        assert_eq!(a.get(4), 3.0);
+
+        let (dense_a, dense_b) = (a.to_dense(HashMap::new()), b.to_dense(HashMap::new()));
+        let weights = vec![1.0, 0.5, 2.0, 1.0, 0.25, 3.0];
+        let metrics = [
+            DistanceMetric::Euclidean,
+            DistanceMetric::Manhattan,
+            DistanceMetric::WeightedEuclidean { weights: weights.clone() },
+            DistanceMetric::WeightedMinkowski { weights, p: 3.0 },
+            DistanceMetric::Cosine,
+        ];
+
+        for metric in &metrics {
+            let expected = metric.calculate(&dense_a, &dense_b).unwrap();
+            assert!((a.distance(&b, metric).unwrap() - expected).abs() < 1e-12);
+            assert!((a.distance_to_dense(&dense_b, metric).unwrap() - expected).abs() < 1e-12);
+        }
+
+        let plane = Hyperplane::new(DVector::from_vec(vec![1.0, 2.0, 0.0, 0.0, -1.0, 0.0]), 0.5);
+        assert_eq!(plane.signed_distance_sparse(&a), plane.signed_distance(&dense_a));
+    }
+
+    #[test]
+    fn test_sparse_serde_validates() {
+        let point = SparsePoint::new(1000, [(999, 1.0), (3, -2.0)]).unwrap();
+        let json = serde_json::to_string(&point).unwrap();
+        assert_eq!(serde_json::from_str::<SparsePoint>(&json).unwrap(), point);
+
+        let invalid = r#"{"dimensions":2,"indices":[5],"values":[1.0],"id":null}"#;
+        assert!(serde_json::from_str::<SparsePoint>(invalid).is_err());
+    }
+}
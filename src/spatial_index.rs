@@ -3,7 +3,8 @@
 //! This module provides spatial data structures for fast nearest neighbor search,
 //! range queries, and region-based operations in high-dimensional conceptual spaces.
 
-use crate::{ConceptualPoint, ConceptualResult, DistanceMetric};
+use crate::{ConceptualError, ConceptualPoint, ConceptualResult, DistanceMetric, PointStorage, PointStore};
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use std::collections::BinaryHeap;
 use std::cmp::Ordering;
@@ -28,13 +29,373 @@ pub trait SpatialIndex {
 
     /// Clear all points from the index
     fn clear(&mut self);
+
+    /// Replace a point that moved, keyed by its id
+    fn update(&mut self, point: ConceptualPoint) -> ConceptualResult<()> {
+        if let Some(id) = point.id {
+            self.remove(&id)?;
+        }
+        self.insert(point)
+    }
+
+    /// Find k nearest neighbors for each of many query points
+    ///
+    /// Results are in query order and identical to calling
+    /// `k_nearest_neighbors` per query.
+    fn k_nearest_neighbors_batch(
+        &self,
+        queries: &[ConceptualPoint],
+        k: usize,
+    ) -> ConceptualResult<Vec<Vec<(Uuid, f64)>>>
+    where
+        Self: Sync,
+    {
+        map_queries(queries, |query| self.k_nearest_neighbors(query, k))
+    }
+
+    /// Find all points within a radius of each of many query points
+    ///
+    /// Results are in query order and identical to calling `range_search`
+    /// per query.
+    fn range_search_batch(
+        &self,
+        centers: &[ConceptualPoint],
+        radius: f64,
+    ) -> ConceptualResult<Vec<Vec<Uuid>>>
+    where
+        Self: Sync,
+    {
+        map_queries(centers, |center| self.range_search(center, radius))
+    }
+}
+
+/// Apply a fallible query to every item, in order
+///
+/// With the `parallel` feature the items are spread over the rayon thread
+/// pool; otherwise they are processed sequentially.
+pub(crate) fn map_queries<Q, T, F>(items: &[Q], query: F) -> ConceptualResult<Vec<T>>
+where
+    Q: Sync,
+    T: Send,
+    F: Fn(&Q) -> ConceptualResult<T> + Sync + Send,
+{
+    #[cfg(feature = "parallel")]
+    {
+        use rayon::prelude::*;
+        items.par_iter().map(query).collect()
+    }
+
+    #[cfg(not(feature = "parallel"))]
+    {
+        items.iter().map(query).collect()
+    }
+}
+
+/// Which spatial index implementation a conceptual space maintains
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum SpatialIndexConfig {
+    /// Flat scan with cheap updates, suited to small or churning spaces
+    ///
+    /// This is the default, and every query is a linear pass over the
+    /// points; choose `KdTree` for sublinear neighbour queries.
+    #[default]
+    Flat,
+
+    /// KD-tree, faster queries on low-dimensional spaces
+    KdTree,
+}
+
+/// A spatial index selected by [`SpatialIndexConfig`]
+///
+/// The index belongs to a space and is queried against that space's point
+/// store, so the flat variant holds no coordinates of its own.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum SpaceIndex {
+    /// Flat scan over the owning space's point store
+    Flat(ScanIndex),
+
+    /// KD-tree index
+    KdTree(KdTreeIndex),
+}
+
+impl SpaceIndex {
+    /// Create an empty index of the configured kind
+    pub fn new(config: SpatialIndexConfig, dimensions: usize, metric: DistanceMetric) -> Self {
+        match config {
+            SpatialIndexConfig::Flat => SpaceIndex::Flat(ScanIndex::new(metric)),
+            SpatialIndexConfig::KdTree => SpaceIndex::KdTree(KdTreeIndex::new(dimensions, metric)),
+        }
+    }
+
+    /// The configuration this index was built from
+    pub fn config(&self) -> SpatialIndexConfig {
+        match self {
+            SpaceIndex::Flat(_) => SpatialIndexConfig::Flat,
+            SpaceIndex::KdTree(_) => SpatialIndexConfig::KdTree,
+        }
+    }
+
+    /// Replace the distance metric used for queries
+    pub fn set_metric(&mut self, metric: DistanceMetric) {
+        match self {
+            SpaceIndex::Flat(index) => index.metric = metric,
+            SpaceIndex::KdTree(index) => index.set_metric(metric),
+        }
+    }
+
+    /// Record a point newly added to the space's store
+    pub fn insert(&mut self, points: &PointStore, id: &Uuid) -> ConceptualResult<()> {
+        match self {
+            SpaceIndex::Flat(index) => {
+                index.len += 1;
+                Ok(())
+            }
+            SpaceIndex::KdTree(index) => index.insert(stored(points, id)?),
+        }
+    }
+
+    /// Record that a point in the space's store moved
+    pub fn update(&mut self, points: &PointStore, id: &Uuid) -> ConceptualResult<()> {
+        match self {
+            SpaceIndex::Flat(_) => Ok(()),
+            SpaceIndex::KdTree(index) => index.update(stored(points, id)?),
+        }
+    }
+
+    /// Record a point removed from the space's store
+    pub fn remove(&mut self, point_id: &Uuid) -> ConceptualResult<bool> {
+        match self {
+            SpaceIndex::Flat(index) => {
+                index.len = index.len.saturating_sub(1);
+                Ok(true)
+            }
+            SpaceIndex::KdTree(index) => index.remove(point_id),
+        }
+    }
+
+    /// Get the number of points in the index
+    pub fn size(&self) -> usize {
+        match self {
+            SpaceIndex::Flat(index) => index.len,
+            SpaceIndex::KdTree(index) => index.size(),
+        }
+    }
+
+    /// Re-index every point in the space's store
+    pub fn rebuild(&mut self, points: &PointStore) -> ConceptualResult<()> {
+        match self {
+            SpaceIndex::Flat(index) => index.len = points.len(),
+            SpaceIndex::KdTree(index) => {
+                let indexed = points.ids().iter()
+                    .map(|id| stored(points, id))
+                    .collect::<ConceptualResult<_>>()?;
+                index.build_from_points(indexed)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Find k nearest neighbors to a query among the indexed points
+    pub fn k_nearest_neighbors(
+        &self,
+        points: &PointStore,
+        query: &ConceptualPoint,
+        k: usize,
+    ) -> ConceptualResult<Vec<(Uuid, f64)>> {
+        match self {
+            SpaceIndex::Flat(index) => scan_k_nearest(points, &index.metric, query, k),
+            SpaceIndex::KdTree(index) => index.k_nearest_neighbors(query, k),
+        }
+    }
+
+    /// Find all indexed points within a radius
+    pub fn range_search(
+        &self,
+        points: &PointStore,
+        center: &ConceptualPoint,
+        radius: f64,
+    ) -> ConceptualResult<Vec<Uuid>> {
+        match self {
+            SpaceIndex::Flat(index) => scan_range(points, &index.metric, center, radius),
+            SpaceIndex::KdTree(index) => index.range_search(center, radius),
+        }
+    }
+
+    /// Find k nearest neighbors for each of many query points
+    pub fn k_nearest_neighbors_batch(
+        &self,
+        points: &PointStore,
+        queries: &[ConceptualPoint],
+        k: usize,
+    ) -> ConceptualResult<Vec<Vec<(Uuid, f64)>>> {
+        map_queries(queries, |query| self.k_nearest_neighbors(points, query, k))
+    }
+
+    /// Find all points within a radius of each of many query points
+    pub fn range_search_batch(
+        &self,
+        points: &PointStore,
+        centers: &[ConceptualPoint],
+        radius: f64,
+    ) -> ConceptualResult<Vec<Vec<Uuid>>> {
+        map_queries(centers, |center| self.range_search(points, center, radius))
+    }
+}
+
+/// Flat index that scans the point store of the space owning it
+///
+/// Queries run the metric's batched kernel over the store in its own
+/// layout, dense or sparse, so the space keeps a single copy of its points.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScanIndex {
+    /// Distance metric for calculations
+    metric: DistanceMetric,
+
+    /// Number of points recorded in the owning store
+    len: usize,
+}
+
+impl ScanIndex {
+    /// Create an empty scan index
+    pub fn new(metric: DistanceMetric) -> Self {
+        Self { metric, len: 0 }
+    }
+}
+
+/// An owned copy of a stored point, leaving the store's borrowed views alone
+fn stored(points: &PointStore, id: &Uuid) -> ConceptualResult<ConceptualPoint> {
+    points.get(id).ok_or_else(|| {
+        ConceptualError::InvalidPoint(format!("Point {id} not found"))
+    })
+}
+
+/// The k rows of a store nearest to a query, skipping undefined distances
+fn scan_k_nearest(
+    points: &PointStore,
+    metric: &DistanceMetric,
+    query: &ConceptualPoint,
+    k: usize,
+) -> ConceptualResult<Vec<(Uuid, f64)>> {
+    if points.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let distances = points.distances(query, metric)?;
+    let mut distances: Vec<_> = points.ids().iter().copied()
+        .zip(distances.iter().copied())
+        .filter(|(_, d)| !d.is_nan())
+        .collect();
+
+    distances.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(Ordering::Equal));
+    distances.truncate(k);
+    Ok(distances)
+}
+
+/// The rows of a store within `radius` of a center, in row order
+fn scan_range(
+    points: &PointStore,
+    metric: &DistanceMetric,
+    center: &ConceptualPoint,
+    radius: f64,
+) -> ConceptualResult<Vec<Uuid>> {
+    if points.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let distances = points.distances(center, metric)?;
+    Ok(points.ids().iter().copied()
+        .zip(distances.iter().copied())
+        .filter(|(_, d)| *d <= radius)
+        .map(|(id, _)| id)
+        .collect())
+}
+
+/// Current binary layout of `IndexSnapshot`
+///
+/// Bump this whenever an index type changes its serialized fields.
+pub const INDEX_SNAPSHOT_FORMAT: u32 = 1;
+
+/// A persisted spatial index tied to the content it was built from
+///
+/// The format version is encoded first so incompatible snapshots are
+/// rejected before the index itself is decoded.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndexSnapshot<I = SpaceIndex> {
+    /// Snapshot layout version, see `INDEX_SNAPSHOT_FORMAT`
+    pub format: u32,
+
+    /// Hash of the points and metric the index was built from
+    pub content_hash: u64,
+
+    /// The index itself
+    pub index: I,
+}
+
+impl<I: Serialize + DeserializeOwned> IndexSnapshot<I> {
+    /// Snapshot an index built from content with the given hash
+    pub fn new(index: I, content_hash: u64) -> Self {
+        Self {
+            format: INDEX_SNAPSHOT_FORMAT,
+            content_hash,
+            index,
+        }
+    }
+
+    /// Whether the snapshot was taken from content with this hash
+    pub fn is_current(&self, content_hash: u64) -> bool {
+        self.content_hash == content_hash
+    }
+
+    /// Encode in the compact binary form
+    pub fn to_bytes(&self) -> ConceptualResult<Vec<u8>> {
+        bincode::serialize(self).map_err(|e| ConceptualError::SnapshotError(e.to_string()))
+    }
+
+    /// Decode from the compact binary form, rejecting other formats
+    pub fn from_bytes(bytes: &[u8]) -> ConceptualResult<Self> {
+        let format: u32 = bincode::deserialize(bytes)
+            .map_err(|e| ConceptualError::SnapshotError(e.to_string()))?;
+        if format != INDEX_SNAPSHOT_FORMAT {
+            return Err(ConceptualError::SnapshotError(format!(
+                "unsupported snapshot format {format}, expected {INDEX_SNAPSHOT_FORMAT}"
+            )));
+        }
+
+        bincode::deserialize(bytes).map_err(|e| ConceptualError::SnapshotError(e.to_string()))
+    }
+}
+
+/// FNV-1a hasher with a fixed byte order, stable across processes
+pub(crate) struct ContentHasher(u64);
+
+impl ContentHasher {
+    pub(crate) fn new() -> Self {
+        Self(0xcbf2_9ce4_8422_2325)
+    }
+
+    pub(crate) fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= u64::from(*byte);
+            self.0 = self.0.wrapping_mul(0x0000_0100_0000_01b3);
+        }
+    }
+
+    pub(crate) fn write_f64(&mut self, value: f64) {
+        self.write(&value.to_bits().to_le_bytes());
+    }
+
+    pub(crate) fn finish(&self) -> u64 {
+        self.0
+    }
 }
 
 /// A simple R-tree implementation for conceptual spaces
+///
+/// Queries scan the whole point matrix with the metric's batched kernel.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RTreeIndex {
     /// Points stored in the index
-    points: Vec<ConceptualPoint>,
+    points: PointStore,
 
     /// Distance metric for calculations
     metric: DistanceMetric,
@@ -44,69 +405,47 @@ impl RTreeIndex {
     /// Create a new R-tree index
     pub fn new(metric: DistanceMetric) -> Self {
         Self {
-            points: Vec::new(),
+            points: PointStore::new(&[]),
             metric,
         }
     }
 
+    /// Replace the distance metric used for queries
+    pub fn set_metric(&mut self, metric: DistanceMetric) {
+        self.metric = metric;
+    }
+
+    /// Store indexed points densely or sparsely
+    pub fn with_point_storage(mut self, storage: PointStorage) -> Self {
+        self.points.set_storage(storage);
+        self
+    }
+
     /// Find k nearest points to a query point
     pub fn find_k_nearest(&self, query: &ConceptualPoint, k: usize) -> ConceptualResult<Vec<(ConceptualPoint, f64)>> {
-        let mut distances: Vec<_> = self.points.iter()
-            .filter_map(|point| {
-                self.metric.calculate(query, point).ok().map(|d| (point.clone(), d))
-            })
-            .collect();
-
-        distances.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(Ordering::Equal));
-        distances.truncate(k);
-        Ok(distances)
+        Ok(self.k_nearest_neighbors(query, k)?
+            .into_iter()
+            .filter_map(|(id, d)| self.points.get(&id).map(|point| (point, d)))
+            .collect())
     }
 }
 
 impl SpatialIndex for RTreeIndex {
     fn insert(&mut self, point: ConceptualPoint) -> ConceptualResult<()> {
-        self.points.push(point);
+        self.points.insert(point)?;
         Ok(())
     }
 
     fn remove(&mut self, point_id: &Uuid) -> ConceptualResult<bool> {
-        if let Some(pos) = self.points.iter().position(|p| p.id == Some(*point_id)) {
-            self.points.remove(pos);
-            Ok(true)
-        } else {
-            Ok(false)
-        }
+        Ok(self.points.remove(point_id).is_some())
     }
 
     fn k_nearest_neighbors(&self, query: &ConceptualPoint, k: usize) -> ConceptualResult<Vec<(Uuid, f64)>> {
-        let mut distances: Vec<_> = self.points.iter()
-            .filter_map(|point| {
-                if let Some(id) = point.id {
-                    self.metric.calculate(query, point).ok().map(|d| (id, d))
-                } else {
-                    None
-                }
-            })
-            .collect();
-
-        distances.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(Ordering::Equal));
-        distances.truncate(k);
-        Ok(distances)
+        scan_k_nearest(&self.points, &self.metric, query, k)
     }
 
     fn range_search(&self, center: &ConceptualPoint, radius: f64) -> ConceptualResult<Vec<Uuid>> {
-        let mut results = Vec::new();
-        
-        for point in &self.points {
-            if let Some(id) = point.id {
-                let distance = self.metric.calculate(center, point)?;
-                if distance <= radius {
-                    results.push(id);
-                }
-            }
-        }
-
-        Ok(results)
+        scan_range(&self.points, &self.metric, center, radius)
     }
 
     fn size(&self) -> usize {
@@ -154,12 +493,19 @@ impl KdTreeIndex {
     pub fn new(dimensions: usize, metric: DistanceMetric) -> Self {
         Self {
             root: None,
-            dimensions,
+            dimensions: dimensions.max(1),
             metric,
             point_count: 0,
         }
     }
 
+    /// Replace the distance metric used for queries
+    ///
+    /// The tree partitions on raw coordinates, so no rebuild is needed.
+    pub fn set_metric(&mut self, metric: DistanceMetric) {
+        self.metric = metric;
+    }
+
     /// Build the tree from a collection of points
     pub fn build_from_points(&mut self, mut points: Vec<ConceptualPoint>) -> ConceptualResult<()> {
         self.point_count = points.len();
@@ -213,9 +559,14 @@ impl SpatialIndex for KdTreeIndex {
         Ok(())
     }
 
-    fn remove(&mut self, _point_id: &Uuid) -> ConceptualResult<bool> {
-        // KD-tree removal is complex and typically requires rebuilding
-        Ok(false)
+    fn remove(&mut self, point_id: &Uuid) -> ConceptualResult<bool> {
+        let mut root = self.root.take();
+        let removed = self.remove_recursive(&mut root, point_id, 0);
+        self.root = root;
+        if removed {
+            self.point_count -= 1;
+        }
+        Ok(removed)
     }
 
     fn k_nearest_neighbors(&self, query: &ConceptualPoint, k: usize) -> ConceptualResult<Vec<(Uuid, f64)>> {
@@ -225,11 +576,11 @@ impl SpatialIndex for KdTreeIndex {
             self.search_knn_recursive(root, query, k, &mut best, 0)?;
         }
 
-        let mut result = Vec::new();
-        while let Some(neighbor) = best.pop() {
-            result.push((neighbor.id, neighbor.distance));
-        }
-        result.reverse(); // BinaryHeap is max-heap, we want min distances first
+        // Max-heap on distance, so sorting ascending puts the nearest first
+        let result = best.into_sorted_vec()
+            .into_iter()
+            .map(|neighbor| (neighbor.id, neighbor.distance))
+            .collect();
         Ok(result)
     }
 
@@ -275,12 +626,46 @@ impl PartialOrd for KdNeighbor {
 
 impl Ord for KdNeighbor {
     fn cmp(&self, other: &Self) -> Ordering {
-        // Reverse ordering for max-heap behavior
-        other.distance.partial_cmp(&self.distance).unwrap_or(Ordering::Equal)
+        // Natural ordering so the heap's top is the current worst neighbor
+        self.distance.partial_cmp(&other.distance).unwrap_or(Ordering::Equal)
     }
 }
 
 impl KdTreeIndex {
+    /// Remove the node holding `point_id`, rebuilding the subtree below it
+    fn remove_recursive(&self, slot: &mut Option<Box<KdTreeNode>>, point_id: &Uuid, depth: usize) -> bool {
+        let Some(node) = slot else {
+            return false;
+        };
+
+        if node.point.id == Some(*point_id) {
+            let mut descendants = Vec::new();
+            Self::collect_points(node.left.take(), &mut descendants);
+            Self::collect_points(node.right.take(), &mut descendants);
+            *slot = self.build_recursive(&mut descendants, depth);
+            return true;
+        }
+
+        self.remove_recursive(&mut node.left, point_id, depth + 1)
+            || self.remove_recursive(&mut node.right, point_id, depth + 1)
+    }
+
+    fn collect_points(node: Option<Box<KdTreeNode>>, points: &mut Vec<ConceptualPoint>) {
+        if let Some(node) = node {
+            let KdTreeNode { point, left, right, .. } = *node;
+            points.push(point);
+            Self::collect_points(left, points);
+            Self::collect_points(right, points);
+        }
+    }
+
+    /// Whether the far side of a splitting plane may hold points within `limit`
+    fn crosses_split(&self, split_dim: usize, delta: f64, limit: f64) -> bool {
+        self.metric
+            .axis_lower_bound(split_dim, delta)
+            .is_none_or(|bound| bound <= limit)
+    }
+
     fn insert_recursive(&self, node: Option<Box<KdTreeNode>>, point: ConceptualPoint, depth: usize) -> Box<KdTreeNode> {
         match node {
             None => Box::new(KdTreeNode {
@@ -341,8 +726,8 @@ impl KdTreeIndex {
         }
 
         // Check if we need to search far child
-        let dimension_distance = (query_val - node_val).abs();
-        if best.len() < k || (best.peek().map(|n| dimension_distance < n.distance).unwrap_or(true)) {
+        let worst = best.peek().map(|n| n.distance).unwrap_or(f64::INFINITY);
+        if best.len() < k || self.crosses_split(split_dim, query_val - node_val, worst) {
             if let Some(ref child) = far_child {
                 self.search_knn_recursive(child, query, k, best, depth + 1)?;
             }
@@ -369,10 +754,9 @@ impl KdTreeIndex {
         let split_dim = depth % self.dimensions;
         let center_val = center.coordinates.get(split_dim).unwrap_or(&0.0);
         let node_val = node.point.coordinates.get(split_dim).unwrap_or(&0.0);
-        let dimension_distance = (center_val - node_val).abs();
 
         // Search both children if the splitting plane intersects the search radius
-        if dimension_distance <= radius {
+        if self.crosses_split(split_dim, center_val - node_val, radius) {
             if let Some(ref left) = node.left {
                 self.search_range_recursive(left, center, radius, results, depth + 1)?;
             }
@@ -0,0 +1,421 @@
+//! Supervised category learning from labelled examples
+//!
+//! Analysts label example concepts ("bridge", "fix & flip", ...) and the
+//! learner turns each label into a convex region. Three modes trade off
+//! simplicity against fidelity: one prototype per label with Voronoi
+//! boundaries, maximum-margin separators between every pair of labels, or
+//! the labelled exemplars themselves. Each model reports how well it fits
+//! the training data and where labels break Gärdenfors' convexity criterion.
+
+use crate::partitioning::{bisector_weights, voronoi_cell};
+use crate::sampling::SplitMix64;
+use crate::{
+    ConceptualError, ConceptualPoint, ConceptualResult, ConvexRegion, DistanceMetric, Hyperplane,
+};
+use nalgebra::DVector;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use uuid::Uuid;
+
+/// Settings for the Pegasos solver of the linear SVMs
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct SvmConfig {
+    /// Regularisation strength λ; smaller values fit the data harder
+    pub lambda: f64,
+
+    /// Passes over the training pairs
+    pub epochs: usize,
+
+    /// Seed of the example shuffle
+    pub seed: u64,
+}
+
+impl Default for SvmConfig {
+    fn default() -> Self {
+        Self { lambda: 0.01, epochs: 200, seed: 0x5EED }
+    }
+}
+
+/// How labelled examples become regions
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum SupervisedMode {
+    /// Label centroids as prototypes, bounded by their Voronoi cells
+    Prototype,
+
+    /// One-vs-one linear SVMs; each label's region is its side of every separator
+    MaxMargin(SvmConfig),
+
+    /// Nearest-exemplar classification; regions are the exemplars' convex hulls
+    Exemplar,
+}
+
+/// A label's learned category
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LearnedCategory {
+    /// The analyst's label
+    pub label: String,
+
+    /// Region learned for the label
+    pub region: ConvexRegion,
+
+    /// Training examples carrying the label
+    pub exemplars: Vec<ConceptualPoint>,
+}
+
+/// A training example that falls inside another label's region
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ConvexityViolation {
+    /// Label whose region is intruded upon
+    pub label: String,
+
+    /// Region of that label
+    pub region_id: Uuid,
+
+    /// Position of the intruding example in the training set
+    pub example: usize,
+
+    /// Id of the intruding example, if it has one
+    pub concept_id: Option<Uuid>,
+
+    /// Label of the intruding example
+    pub example_label: String,
+}
+
+/// Regions learned from labelled examples
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SupervisedModel {
+    /// Mode the model was trained in
+    pub mode: SupervisedMode,
+
+    /// One category per label, in label order
+    pub categories: Vec<LearnedCategory>,
+
+    /// Fraction of training examples classified as their own label
+    pub training_accuracy: f64,
+
+    /// Training examples inside the region of a different label
+    pub convexity_violations: Vec<ConvexityViolation>,
+
+    metric: DistanceMetric,
+
+    /// Pairwise separators `(a, b, w, bias)`: w·x + bias ≥ 0 favours a
+    separators: Vec<(usize, usize, DVector<f64>, f64)>,
+}
+
+impl SupervisedModel {
+    /// Learned category of a label
+    pub fn category(&self, label: &str) -> Option<&LearnedCategory> {
+        self.categories.iter().find(|c| c.label == label)
+    }
+
+    /// The learned regions, in label order
+    pub fn regions(&self) -> Vec<ConvexRegion> {
+        self.categories.iter().map(|c| c.region.clone()).collect()
+    }
+
+    /// Most likely label of a point
+    pub fn classify(&self, point: &ConceptualPoint) -> ConceptualResult<&str> {
+        let index = match self.mode {
+            SupervisedMode::Prototype => {
+                self.nearest(self.categories.iter().map(|c| &c.region.prototype), point)?.0
+            }
+            SupervisedMode::Exemplar => {
+                let owners: Vec<usize> = self.categories.iter()
+                    .enumerate()
+                    .flat_map(|(i, c)| std::iter::repeat_n(i, c.exemplars.len()))
+                    .collect();
+                let nearest = self.nearest(self.categories.iter().flat_map(|c| &c.exemplars), point)?.0;
+                owners[nearest]
+            }
+            SupervisedMode::MaxMargin(_) => {
+                // One-vs-one voting, ties broken by summed margin
+                let mut votes = vec![(0usize, 0.0f64); self.categories.len()];
+                for (a, b, w, bias) in &self.separators {
+                    let margin = w.dot(&point.coordinates) + bias;
+                    let winner = if margin >= 0.0 { *a } else { *b };
+                    votes[winner].0 += 1;
+                    votes[winner].1 += margin.abs();
+                }
+                (0..votes.len())
+                    .max_by(|&x, &y| votes[x].0.cmp(&votes[y].0).then(votes[x].1.total_cmp(&votes[y].1)))
+                    .unwrap_or(0)
+            }
+        };
+        Ok(&self.categories[index].label)
+    }
+
+    fn nearest<'a>(
+        &self,
+        candidates: impl Iterator<Item = &'a ConceptualPoint>,
+        point: &ConceptualPoint,
+    ) -> ConceptualResult<(usize, f64)> {
+        let mut best = (0, f64::INFINITY);
+        for (i, candidate) in candidates.enumerate() {
+            let d = self.metric.calculate(point, candidate)?;
+            if d < best.1 {
+                best = (i, d);
+            }
+        }
+        Ok(best)
+    }
+}
+
+/// Learns one convex region per label from labelled examples
+#[derive(Debug, Clone)]
+pub struct SupervisedLearner {
+    metric: DistanceMetric,
+    mode: SupervisedMode,
+}
+
+impl SupervisedLearner {
+    /// Create a prototype-mode learner
+    pub fn new(metric: DistanceMetric) -> Self {
+        Self { metric, mode: SupervisedMode::Prototype }
+    }
+
+    /// Choose the learning mode
+    pub fn with_mode(mut self, mode: SupervisedMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Learn a region per label from `(point, label)` pairs
+    ///
+    /// ```mermaid
+    /// graph TD
+    ///     A[Labelled Examples] --> B{Mode}
+    ///     B -->|Prototype| C[Centroids + Voronoi Cells]
+    ///     B -->|MaxMargin| D[One-vs-One Linear SVMs]
+    ///     B -->|Exemplar| E[Exemplar Convex Hulls]
+    ///     C --> F[Regions per Label]
+    ///     D --> F
+    ///     E --> F
+    ///     F --> G[Training Accuracy]
+    ///     F --> H[Convexity Violations]
+    /// ```
+    pub fn learn(&self, examples: &[(ConceptualPoint, String)]) -> ConceptualResult<SupervisedModel> {
+        let Some((first, _)) = examples.first() else {
+            return Err(ConceptualError::InvalidPoint("No labelled examples".to_string()));
+        };
+        let dimensions = first.coordinates.len();
+        if examples.iter().any(|(p, _)| p.coordinates.len() != dimensions) {
+            return Err(ConceptualError::InvalidPoint("Points have different dimensions".to_string()));
+        }
+
+        let mut by_label: BTreeMap<&str, Vec<ConceptualPoint>> = BTreeMap::new();
+        for (point, label) in examples {
+            by_label.entry(label.as_str()).or_default().push(point.clone());
+        }
+        let labels: Vec<&str> = by_label.keys().copied().collect();
+
+        let centroids: Vec<ConceptualPoint> = by_label.values()
+            .map(|points| {
+                let sum = points.iter().fold(DVector::zeros(dimensions), |sum, p| sum + &p.coordinates);
+                ConceptualPoint::new((sum / points.len() as f64).as_slice().to_vec(), first.dimension_map.clone())
+            })
+            .collect();
+
+        let mut separators = Vec::new();
+        let boundaries: Vec<Vec<Hyperplane>> = match self.mode {
+            SupervisedMode::Prototype => {
+                let centres: Vec<DVector<f64>> = centroids.iter().map(|c| c.coordinates.clone()).collect();
+                let weights = bisector_weights(&self.metric, dimensions);
+                (0..centres.len()).map(|i| voronoi_cell(&centres, i, &weights)).collect()
+            }
+            SupervisedMode::Exemplar => by_label.values()
+                .zip(&centroids)
+                .map(|(points, centroid)| ConvexRegion::from_hull(centroid.clone(), points).boundaries)
+                .collect(),
+            SupervisedMode::MaxMargin(config) => {
+                let mut rng = SplitMix64::new(config.seed);
+                let mut boundaries = vec![Vec::new(); labels.len()];
+                for a in 0..labels.len() {
+                    for b in a + 1..labels.len() {
+                        let (w, bias) = pegasos(&by_label[labels[a]], &by_label[labels[b]], &config, &mut rng);
+                        let norm = w.norm();
+                        if norm > 0.0 {
+                            boundaries[a].push(Hyperplane::new(&w / norm, -bias / norm));
+                            boundaries[b].push(Hyperplane::new(-&w / norm, bias / norm));
+                        }
+                        separators.push((a, b, w, bias));
+                    }
+                }
+                boundaries
+            }
+        };
+
+        let categories: Vec<LearnedCategory> = labels.iter()
+            .zip(centroids)
+            .zip(boundaries)
+            .map(|((label, prototype), boundaries)| {
+                let exemplars = by_label[label].clone();
+                LearnedCategory {
+                    label: label.to_string(),
+                    region: ConvexRegion {
+                        boundaries,
+                        member_points: exemplars.iter().filter_map(|p| p.id).collect(),
+                        ..ConvexRegion::from_prototype(prototype)
+                    }
+                    .with_name(label.to_string())
+                    .with_description(format!("Learned from {} labelled examples", exemplars.len())),
+                    exemplars,
+                }
+            })
+            .collect();
+
+        let mut model = SupervisedModel {
+            mode: self.mode,
+            categories,
+            training_accuracy: 0.0,
+            convexity_violations: Vec::new(),
+            metric: self.metric.clone(),
+            separators,
+        };
+
+        let mut correct = 0;
+        for (index, (point, label)) in examples.iter().enumerate() {
+            if model.classify(point)? == label {
+                correct += 1;
+            }
+            for category in model.categories.iter().filter(|c| c.label != *label) {
+                if category.region.contains(point) {
+                    model.convexity_violations.push(ConvexityViolation {
+                        label: category.label.clone(),
+                        region_id: category.region.id,
+                        example: index,
+                        concept_id: point.id,
+                        example_label: label.clone(),
+                    });
+                }
+            }
+        }
+        model.training_accuracy = correct as f64 / examples.len() as f64;
+
+        Ok(model)
+    }
+}
+
+/// Linear SVM separating `positive` from `negative` by Pegasos subgradient steps
+///
+/// The bias is learned as the weight of a constant feature. Returns `(w, bias)`
+/// with w·x + bias ≥ 0 on the positive side.
+fn pegasos(
+    positive: &[ConceptualPoint],
+    negative: &[ConceptualPoint],
+    config: &SvmConfig,
+    rng: &mut SplitMix64,
+) -> (DVector<f64>, f64) {
+    let samples: Vec<(DVector<f64>, f64)> = positive.iter()
+        .map(|p| (p, 1.0))
+        .chain(negative.iter().map(|p| (p, -1.0)))
+        .map(|(p, y)| (p.coordinates.clone().insert_row(p.coordinates.len(), 1.0), y))
+        .collect();
+    let dimensions = samples[0].0.len();
+    let lambda = config.lambda.max(f64::EPSILON);
+
+    let mut w = DVector::zeros(dimensions);
+    let mut order: Vec<usize> = (0..samples.len()).collect();
+    let mut t = 0usize;
+    for _ in 0..config.epochs.max(1) {
+        for i in (1..order.len()).rev() {
+            order.swap(i, rng.below(i + 1));
+        }
+        for &i in &order {
+            t += 1;
+            let (x, y) = &samples[i];
+            let eta = 1.0 / (lambda * t as f64);
+            let violated = y * w.dot(x) < 1.0;
+            w *= 1.0 - eta * lambda;
+            if violated {
+                w += x * (eta * y);
+            }
+            // Project onto the ball that contains the optimum
+            let radius = 1.0 / lambda.sqrt();
+            let norm = w.norm();
+            if norm > radius {
+                w *= radius / norm;
+            }
+        }
+    }
+
+    let bias = w[dimensions - 1];
+    (w.rows(0, dimensions - 1).into_owned(), bias)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn example(x: f64, y: f64, label: &str) -> (ConceptualPoint, String) {
+        let point = ConceptualPoint { id: Some(Uuid::new_v4()), ..ConceptualPoint::new(vec![x, y], HashMap::new()) };
+        (point, label.to_string())
+    }
+
+    fn loans() -> Vec<(ConceptualPoint, String)> {
+        let mut examples = Vec::new();
+        for (dx, dy) in [(0.0, 0.0), (0.5, 0.2), (0.2, 0.6), (-0.4, 0.3), (0.3, -0.5)] {
+            examples.push(example(1.0 + dx, 1.0 + dy, "bridge"));
+            examples.push(example(5.0 + dx, 1.0 + dy, "fix & flip"));
+            examples.push(example(3.0 + dx, 5.0 + dy, "construction"));
+        }
+        examples
+    }
+
+    #[test]
+    fn test_all_modes_fit_separable_labels() {
+        let examples = loans();
+        for mode in [SupervisedMode::Prototype, SupervisedMode::MaxMargin(SvmConfig::default()), SupervisedMode::Exemplar] {
+            let model = SupervisedLearner::new(DistanceMetric::Euclidean).with_mode(mode).learn(&examples).unwrap();
+
+            assert_eq!(model.categories.len(), 3);
+            assert_eq!(model.training_accuracy, 1.0, "{mode:?}");
+            assert!(model.convexity_violations.is_empty(), "{mode:?}");
+            for (point, label) in &examples {
+                assert!(model.category(label).unwrap().region.contains(point), "{mode:?}");
+            }
+            let probe = ConceptualPoint::new(vec![4.8, 0.9], HashMap::new());
+            assert_eq!(model.classify(&probe).unwrap(), "fix & flip");
+        }
+    }
+
+    #[test]
+    fn test_svm_separators_have_margin() {
+        let model = SupervisedLearner::new(DistanceMetric::Euclidean)
+            .with_mode(SupervisedMode::MaxMargin(SvmConfig::default()))
+            .learn(&loans())
+            .unwrap();
+        let bridge = model.category("bridge").unwrap();
+        assert_eq!(bridge.region.boundaries.len(), 2);
+
+        // The bridge/fix & flip separator sits roughly midway between the groups
+        let between = ConceptualPoint::new(vec![3.0, 1.0], HashMap::new());
+        let margin = bridge.region.boundaries.iter()
+            .map(|plane| plane.signed_distance(&between).abs())
+            .fold(f64::INFINITY, f64::min);
+        assert!(margin < 1.0);
+    }
+
+    #[test]
+    fn test_non_convex_label_reports_violations() {
+        // "bridge" on both sides of "fix & flip" cannot be one convex region
+        let examples = vec![
+            example(0.0, 0.0, "bridge"),
+            example(0.2, 0.1, "bridge"),
+            example(2.0, 0.0, "fix & flip"),
+            example(2.1, 0.1, "fix & flip"),
+            example(4.0, 0.0, "bridge"),
+            example(4.2, 0.1, "bridge"),
+        ];
+        let exemplar = SupervisedLearner::new(DistanceMetric::Euclidean)
+            .with_mode(SupervisedMode::Exemplar)
+            .learn(&examples)
+            .unwrap();
+        assert_eq!(exemplar.training_accuracy, 1.0);
+        assert_eq!(exemplar.convexity_violations.len(), 2);
+        assert!(exemplar.convexity_violations.iter().all(|v| v.label == "bridge" && v.example_label == "fix & flip"));
+
+        let prototype = SupervisedLearner::new(DistanceMetric::Euclidean).learn(&examples).unwrap();
+        assert!(prototype.training_accuracy < 1.0);
+    }
+}
@@ -0,0 +1,481 @@
+//! Concept trajectories and their distances
+//!
+//! A concept that drifts over time traces a path through the space. This
+//! module stores such paths as timestamped points and compares them with
+//! dynamic time warping (optionally constrained to a Sakoe–Chiba band or an
+//! Itakura parallelogram), derivative DTW and the discrete Fréchet distance.
+//! LB_Keogh lower bounds let trajectory searches skip most full DTW runs.
+
+use crate::{ConceptualError, ConceptualPoint, ConceptualResult, DistanceMetric};
+use nalgebra::DVector;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Timestamped positions of one concept
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConceptTrajectory {
+    /// The concept whose movement this is
+    pub concept_id: Uuid,
+
+    /// Sample times, non-decreasing
+    times: Vec<f64>,
+
+    /// Positions at each sample time
+    points: Vec<ConceptualPoint>,
+}
+
+/// Which cells of the cost matrix a warping path may visit
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum WarpingWindow {
+    /// Any monotone alignment
+    Unconstrained,
+
+    /// Stay within this many steps of the (length-scaled) diagonal
+    SakoeChiba(usize),
+
+    /// Keep the local slope between `1/s` and `s`, pinched at both ends
+    Itakura(f64),
+}
+
+impl WarpingWindow {
+    fn allows(&self, i: usize, j: usize, n: usize, m: usize) -> bool {
+        match *self {
+            WarpingWindow::Unconstrained => true,
+            WarpingWindow::SakoeChiba(radius) => {
+                let diagonal = if n > 1 { i as f64 * (m - 1) as f64 / (n - 1) as f64 } else { 0.0 };
+                (j as f64 - diagonal).abs() <= radius as f64 + 0.5
+            }
+            WarpingWindow::Itakura(slope) => {
+                if n < 2 || m < 2 {
+                    return true;
+                }
+                let s = slope.max(1.0);
+                // Slack of one cell keeps the rounded diagonal admissible
+                let slack = 1.0 / (n.max(m) - 1) as f64;
+                let (x, y) = (i as f64 / (n - 1) as f64, j as f64 / (m - 1) as f64);
+                y <= s * x + slack
+                    && y + slack >= x / s
+                    && 1.0 - y <= s * (1.0 - x) + slack
+                    && 1.0 - y + slack >= (1.0 - x) / s
+            }
+        }
+    }
+}
+
+/// DTW over two point sequences: the cheapest monotone alignment
+fn dtw_points(
+    a: &[ConceptualPoint],
+    b: &[ConceptualPoint],
+    metric: &DistanceMetric,
+    window: WarpingWindow,
+) -> ConceptualResult<f64> {
+    let (n, m) = (a.len(), b.len());
+    if n == 0 || m == 0 {
+        return Err(ConceptualError::InvalidPoint("Cannot warp an empty trajectory".to_string()));
+    }
+
+    let mut previous = vec![f64::INFINITY; m];
+    let mut current = vec![f64::INFINITY; m];
+    for i in 0..n {
+        for j in 0..m {
+            if !window.allows(i, j, n, m) {
+                current[j] = f64::INFINITY;
+                continue;
+            }
+            let cost = metric.calculate(&a[i], &b[j])?;
+            let best = match (i, j) {
+                (0, 0) => 0.0,
+                (0, _) => current[j - 1],
+                (_, 0) => previous[j],
+                _ => previous[j].min(current[j - 1]).min(previous[j - 1]),
+            };
+            current[j] = cost + best;
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+
+    let distance = previous[m - 1];
+    if distance.is_infinite() {
+        return Err(ConceptualError::InvalidPoint("Warping window admits no alignment".to_string()));
+    }
+    Ok(distance)
+}
+
+/// Distance from a point to the box `[lower, upper]` under a metric
+fn distance_to_envelope(
+    point: &DVector<f64>,
+    lower: &DVector<f64>,
+    upper: &DVector<f64>,
+    metric: &DistanceMetric,
+) -> ConceptualResult<f64> {
+    let nearest = DVector::from_fn(point.len(), |d, _| point[d].clamp(lower[d], upper[d]));
+    metric.calculate(
+        &ConceptualPoint::new(point.iter().copied().collect(), Default::default()),
+        &ConceptualPoint::new(nearest.iter().copied().collect(), Default::default()),
+    )
+}
+
+impl ConceptTrajectory {
+    /// Create an empty trajectory for a concept
+    pub fn new(concept_id: Uuid) -> Self {
+        Self {
+            concept_id,
+            times: Vec::new(),
+            points: Vec::new(),
+        }
+    }
+
+    /// Build a trajectory from points sampled at times `0, 1, 2, …`
+    pub fn from_points(concept_id: Uuid, points: Vec<ConceptualPoint>) -> ConceptualResult<Self> {
+        let mut trajectory = Self::new(concept_id);
+        for (t, point) in points.into_iter().enumerate() {
+            trajectory.push(t as f64, point)?;
+        }
+        Ok(trajectory)
+    }
+
+    /// Record the concept's position at a time
+    ///
+    /// Samples are kept in time order; every point must have the same
+    /// number of dimensions.
+    pub fn push(&mut self, time: f64, point: ConceptualPoint) -> ConceptualResult<()> {
+        if !time.is_finite() {
+            return Err(ConceptualError::InvalidPoint("Sample time must be finite".to_string()));
+        }
+        if self.points.first().is_some_and(|p| p.coordinates.len() != point.coordinates.len()) {
+            return Err(ConceptualError::InvalidPoint("Points have different dimensions".to_string()));
+        }
+
+        let at = self.times.partition_point(|t| *t <= time);
+        self.times.insert(at, time);
+        self.points.insert(at, point);
+        Ok(())
+    }
+
+    /// Builder form of `push`
+    pub fn with_sample(mut self, time: f64, point: ConceptualPoint) -> ConceptualResult<Self> {
+        self.push(time, point)?;
+        Ok(self)
+    }
+
+    /// Number of samples
+    pub fn len(&self) -> usize {
+        self.points.len()
+    }
+
+    /// Whether the trajectory has no samples
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+
+    /// Sample times in order
+    pub fn times(&self) -> &[f64] {
+        &self.times
+    }
+
+    /// Positions in time order
+    pub fn points(&self) -> &[ConceptualPoint] {
+        &self.points
+    }
+
+    /// Time between the first and last sample
+    pub fn duration(&self) -> f64 {
+        match (self.times.first(), self.times.last()) {
+            (Some(first), Some(last)) => last - first,
+            _ => 0.0,
+        }
+    }
+
+    /// Position at a time, interpolated linearly and clamped to the ends
+    pub fn position_at(&self, time: f64) -> Option<ConceptualPoint> {
+        let first = self.points.first()?;
+        let after = self.times.partition_point(|t| *t <= time);
+
+        let coordinates = if after == 0 {
+            first.coordinates.clone()
+        } else if after == self.len() {
+            self.points[after - 1].coordinates.clone()
+        } else {
+            let (t0, t1) = (self.times[after - 1], self.times[after]);
+            let w = if t1 > t0 { (time - t0) / (t1 - t0) } else { 0.0 };
+            &self.points[after - 1].coordinates * (1.0 - w) + &self.points[after].coordinates * w
+        };
+
+        Some(ConceptualPoint {
+            coordinates,
+            dimension_map: first.dimension_map.clone(),
+            id: None,
+        })
+    }
+
+    /// Resample at `count` evenly spaced times over the same span
+    pub fn resample(&self, count: usize) -> ConceptualResult<Self> {
+        if self.is_empty() || count == 0 {
+            return Err(ConceptualError::InvalidPoint("Cannot resample to or from nothing".to_string()));
+        }
+
+        let start = self.times[0];
+        let step = if count > 1 { self.duration() / (count - 1) as f64 } else { 0.0 };
+        let times: Vec<f64> = (0..count).map(|k| start + step * k as f64).collect();
+        self.sampled_at(&times)
+    }
+
+    /// Resample at another trajectory's sample times
+    pub fn align_to(&self, other: &ConceptTrajectory) -> ConceptualResult<Self> {
+        if self.is_empty() || other.is_empty() {
+            return Err(ConceptualError::InvalidPoint("Cannot align empty trajectories".to_string()));
+        }
+        self.sampled_at(&other.times)
+    }
+
+    fn sampled_at(&self, times: &[f64]) -> ConceptualResult<Self> {
+        let mut resampled = Self::new(self.concept_id);
+        for &time in times {
+            if let Some(point) = self.position_at(time) {
+                resampled.push(time, point)?;
+            }
+        }
+        Ok(resampled)
+    }
+
+    /// Local slope estimates, used by derivative DTW
+    ///
+    /// Keogh and Pazzani's estimate averages the backward difference with
+    /// the centred one; the ends copy their neighbours.
+    pub fn derivative(&self) -> Vec<ConceptualPoint> {
+        let n = self.len();
+        if n < 3 {
+            let zero = self.points.first().map(|p| DVector::zeros(p.coordinates.len()));
+            return zero.into_iter()
+                .cycle()
+                .take(n)
+                .map(|c| ConceptualPoint::new(c.iter().copied().collect(), Default::default()))
+                .collect();
+        }
+
+        let c = |i: usize| &self.points[i].coordinates;
+        let mut slopes: Vec<DVector<f64>> = (1..n - 1)
+            .map(|i| ((c(i) - c(i - 1)) + (c(i + 1) - c(i - 1)) * 0.5) * 0.5)
+            .collect();
+        slopes.insert(0, slopes[0].clone());
+        slopes.push(slopes[slopes.len() - 1].clone());
+
+        slopes.into_iter()
+            .map(|s| ConceptualPoint::new(s.iter().copied().collect(), Default::default()))
+            .collect()
+    }
+
+    /// Dynamic time warping distance: summed cost of the cheapest alignment
+    ///
+    /// ```mermaid
+    /// graph LR
+    ///     A[Trajectory A] --> C[Cost Matrix]
+    ///     B[Trajectory B] --> C
+    ///     W[Warping Window] --> C
+    ///     C --> D[Cheapest Monotone Path]
+    /// ```
+    pub fn dtw_distance(
+        &self,
+        other: &ConceptTrajectory,
+        metric: &DistanceMetric,
+        window: WarpingWindow,
+    ) -> ConceptualResult<f64> {
+        dtw_points(&self.points, &other.points, metric, window)
+    }
+
+    /// DTW over local slopes, aligning shapes rather than positions
+    pub fn derivative_dtw_distance(
+        &self,
+        other: &ConceptTrajectory,
+        metric: &DistanceMetric,
+        window: WarpingWindow,
+    ) -> ConceptualResult<f64> {
+        dtw_points(&self.derivative(), &other.derivative(), metric, window)
+    }
+
+    /// Discrete Fréchet distance: the shortest leash for walking both paths
+    pub fn frechet_distance(&self, other: &ConceptTrajectory, metric: &DistanceMetric) -> ConceptualResult<f64> {
+        let (n, m) = (self.len(), other.len());
+        if n == 0 || m == 0 {
+            return Err(ConceptualError::InvalidPoint("Cannot compare an empty trajectory".to_string()));
+        }
+
+        let mut leash = vec![vec![0.0; m]; n];
+        for i in 0..n {
+            for j in 0..m {
+                let d = metric.calculate(&self.points[i], &other.points[j])?;
+                let reach = match (i, j) {
+                    (0, 0) => 0.0,
+                    (0, _) => leash[0][j - 1],
+                    (_, 0) => leash[i - 1][0],
+                    _ => f64::min(leash[i - 1][j], leash[i][j - 1]).min(leash[i - 1][j - 1]),
+                };
+                leash[i][j] = f64::max(d, reach);
+            }
+        }
+        Ok(leash[n - 1][m - 1])
+    }
+
+    /// LB_Keogh lower bound on the Sakoe–Chiba DTW distance to `candidate`
+    ///
+    /// Both trajectories must have the same length. Each candidate point is
+    /// charged its distance to this trajectory's envelope within `radius`
+    /// steps, which no alignment inside the band can undercut. Only
+    /// coordinate-wise metrics give a valid bound, so cosine is rejected.
+    pub fn lb_keogh(
+        &self,
+        candidate: &ConceptTrajectory,
+        radius: usize,
+        metric: &DistanceMetric,
+    ) -> ConceptualResult<f64> {
+        if matches!(metric, DistanceMetric::Cosine | DistanceMetric::Custom(_)) {
+            return Err(ConceptualError::InvalidDimension(
+                "LB_Keogh needs a coordinate-wise metric".to_string()
+            ));
+        }
+        let n = self.len();
+        if n == 0 || candidate.len() != n {
+            return Err(ConceptualError::InvalidPoint(
+                "LB_Keogh needs non-empty trajectories of equal length".to_string()
+            ));
+        }
+
+        let mut bound = 0.0;
+        for (i, point) in candidate.points.iter().enumerate() {
+            let window = &self.points[i.saturating_sub(radius)..(i + radius + 1).min(n)];
+            let mut lower = window[0].coordinates.clone();
+            let mut upper = window[0].coordinates.clone();
+            for p in &window[1..] {
+                lower = lower.inf(&p.coordinates);
+                upper = upper.sup(&p.coordinates);
+            }
+            bound += distance_to_envelope(&point.coordinates, &lower, &upper, metric)?;
+        }
+        Ok(bound)
+    }
+
+    /// The `k` candidates nearest to this trajectory under band-limited DTW
+    ///
+    /// Candidates are resampled to this trajectory's length, ranked by
+    /// LB_Keogh, and fully warped only while their bound can still beat the
+    /// current k-th best.
+    pub fn k_nearest_trajectories(
+        &self,
+        candidates: &[ConceptTrajectory],
+        k: usize,
+        radius: usize,
+        metric: &DistanceMetric,
+    ) -> ConceptualResult<Vec<(Uuid, f64)>> {
+        if k == 0 || candidates.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut bounded = Vec::with_capacity(candidates.len());
+        for candidate in candidates {
+            let resampled = candidate.resample(self.len())?;
+            let bound = self.lb_keogh(&resampled, radius, metric)?;
+            bounded.push((bound, resampled));
+        }
+        bounded.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(std::cmp::Ordering::Equal));
+
+        let mut best: Vec<(Uuid, f64)> = Vec::with_capacity(k + 1);
+        for (bound, candidate) in bounded {
+            if best.len() == k && bound >= best[k - 1].1 {
+                break;
+            }
+            let distance = self.dtw_distance(&candidate, metric, WarpingWindow::SakoeChiba(radius))?;
+            let at = best.partition_point(|(_, d)| *d <= distance);
+            best.insert(at, (candidate.concept_id, distance));
+            best.truncate(k);
+        }
+        Ok(best)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn trajectory(values: &[f64]) -> ConceptTrajectory {
+        let points = values.iter().map(|v| ConceptualPoint::new(vec![*v], HashMap::new())).collect();
+        ConceptTrajectory::from_points(Uuid::new_v4(), points).unwrap()
+    }
+
+    #[test]
+    fn test_warping_windows_and_frechet() {
+        let metric = DistanceMetric::Euclidean;
+        let a = trajectory(&[0.0, 0.0, 1.0, 2.0, 2.0]);
+        let b = trajectory(&[0.0, 1.0, 2.0, 2.0, 2.0]);
+
+        // Unconstrained warping absorbs the one-step lag entirely
+        assert_eq!(a.dtw_distance(&b, &metric, WarpingWindow::Unconstrained).unwrap(), 0.0);
+        assert_eq!(a.dtw_distance(&b, &metric, WarpingWindow::SakoeChiba(1)).unwrap(), 0.0);
+        // A zero-width band forces the lock-step alignment
+        assert_eq!(a.dtw_distance(&b, &metric, WarpingWindow::SakoeChiba(0)).unwrap(), 2.0);
+        // The one-step lag is within slope 2 of the diagonal
+        assert_eq!(a.dtw_distance(&b, &metric, WarpingWindow::Itakura(2.0)).unwrap(), 0.0);
+
+        // A longer lag is only partly absorbed: slope 1 is lock-step, wider
+        // slopes approach the unconstrained alignment
+        let late = trajectory(&[0.0, 0.0, 0.0, 0.0, 0.0, 1.0, 2.0, 3.0]);
+        let early = trajectory(&[0.0, 1.0, 2.0, 3.0, 3.0, 3.0, 3.0, 3.0]);
+        assert_eq!(late.dtw_distance(&early, &metric, WarpingWindow::Unconstrained).unwrap(), 0.0);
+        assert_eq!(late.dtw_distance(&early, &metric, WarpingWindow::Itakura(1.0)).unwrap(), 12.0);
+        assert_eq!(late.dtw_distance(&early, &metric, WarpingWindow::Itakura(2.0)).unwrap(), 8.0);
+        assert_eq!(late.dtw_distance(&early, &metric, WarpingWindow::Itakura(4.0)).unwrap(), 4.0);
+
+        // Lengths 3 and 9 cannot stay on the diagonal, so slope 1 admits no path
+        let short = trajectory(&[0.0, 1.0, 2.0]);
+        let long = trajectory(&[0.0, 1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0]);
+        assert!(short.dtw_distance(&long, &metric, WarpingWindow::Itakura(1.0)).is_err());
+        assert!(short.dtw_distance(&long, &metric, WarpingWindow::Itakura(3.0)).is_ok());
+
+        assert_eq!(a.frechet_distance(&b, &metric).unwrap(), 0.0);
+        let shifted = trajectory(&[3.0, 3.0, 4.0, 5.0, 5.0]);
+        assert_eq!(a.frechet_distance(&shifted, &metric).unwrap(), 3.0);
+        // Same shape, so derivative DTW sees no difference
+        assert_eq!(a.derivative_dtw_distance(&shifted, &metric, WarpingWindow::Unconstrained).unwrap(), 0.0);
+    }
+
+    #[test]
+    fn test_resample_and_align() {
+        let mut t = ConceptTrajectory::new(Uuid::new_v4());
+        t.push(2.0, ConceptualPoint::new(vec![4.0], HashMap::new())).unwrap();
+        t.push(0.0, ConceptualPoint::new(vec![0.0], HashMap::new())).unwrap();
+        assert_eq!(t.times(), &[0.0, 2.0]);
+
+        let resampled = t.resample(5).unwrap();
+        let values: Vec<f64> = resampled.points().iter().map(|p| p.coordinates[0]).collect();
+        assert_eq!(values, vec![0.0, 1.0, 2.0, 3.0, 4.0]);
+
+        let reference = trajectory(&[0.0, 0.0, 0.0, 0.0]);
+        let aligned = t.align_to(&reference).unwrap();
+        assert_eq!(aligned.times(), reference.times());
+        assert_eq!(aligned.points()[3].coordinates[0], 4.0);
+
+        assert!(t.push(1.0, ConceptualPoint::new(vec![1.0, 2.0], HashMap::new())).is_err());
+    }
+
+    #[test]
+    fn test_lb_keogh_bounds_and_search() {
+        let metric = DistanceMetric::Euclidean;
+        let query = trajectory(&[0.0, 1.0, 2.0, 1.0, 0.0, 1.0, 2.0, 1.0]);
+        let candidates: Vec<_> = [0.0, 0.5, 3.0, 6.0]
+            .iter()
+            .map(|offset| trajectory(&[0.0, 1.0, 2.0, 1.0, 0.0, 1.0, 2.0, 1.0].map(|v| v + offset)))
+            .collect();
+
+        for candidate in &candidates {
+            let bound = query.lb_keogh(candidate, 1, &metric).unwrap();
+            let exact = query.dtw_distance(candidate, &metric, WarpingWindow::SakoeChiba(1)).unwrap();
+            assert!(bound <= exact + 1e-12);
+        }
+
+        let nearest = query.k_nearest_trajectories(&candidates, 2, 1, &metric).unwrap();
+        assert_eq!(nearest.len(), 2);
+        assert_eq!(nearest[0], (candidates[0].concept_id, 0.0));
+        assert_eq!(nearest[1].0, candidates[1].concept_id);
+        assert!(query.lb_keogh(&candidates[0], 1, &DistanceMetric::Cosine).is_err());
+    }
+}
@@ -0,0 +1,352 @@
+//! Tversky's feature-based similarity
+//!
+//! Geometric distance is symmetric, but human similarity judgements often
+//! are not: a variant is judged more like its prototype than the prototype
+//! is like the variant. Tversky's contrast and ratio models capture this by
+//! weighting the features distinctive to the subject (α) and to the referent
+//! (β) differently. Feature sets come from concept map properties or from
+//! thresholding dimensions of a point.
+
+use crate::{ConceptMap, ConceptualError, ConceptualPoint, ConceptualResult};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+
+/// A set of features, each with a membership degree in `(0, 1]`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct FeatureSet {
+    features: HashMap<String, f64>,
+}
+
+impl FeatureSet {
+    /// Create an empty feature set
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a feature with full membership
+    pub fn with_feature(self, feature: impl Into<String>) -> Self {
+        self.with_degree(feature, 1.0)
+    }
+
+    /// Add a feature with a graded membership, clamped to `[0, 1]`
+    ///
+    /// A degree of zero leaves the feature out.
+    pub fn with_degree(mut self, feature: impl Into<String>, degree: f64) -> Self {
+        self.insert(feature.into(), degree);
+        self
+    }
+
+    fn insert(&mut self, feature: String, degree: f64) {
+        let degree = degree.clamp(0.0, 1.0);
+        if degree > 0.0 {
+            let entry = self.features.entry(feature).or_insert(0.0);
+            *entry = entry.max(degree);
+        }
+    }
+
+    /// Features of a concept map
+    ///
+    /// Every node contributes `type:label`. Node properties contribute
+    /// `key=value` for strings, `key` for `true` flags, and `key` with the
+    /// value as membership degree for numbers already in `[0, 1]`. Other
+    /// numbers are measurements rather than degrees and are skipped; map
+    /// them to degrees with `with_degree` and a normaliser of your choice.
+    pub fn from_concept_map(map: &ConceptMap) -> Self {
+        let mut set = Self::new();
+        for node in map.graph.node_weights() {
+            set.insert(format!("{}:{}", node.concept_type, node.label), 1.0);
+            for (key, value) in &node.properties {
+                match value {
+                    serde_json::Value::Bool(flag) => {
+                        if *flag {
+                            set.insert(key.clone(), 1.0);
+                        }
+                    }
+                    serde_json::Value::Number(number) => {
+                        if let Some(degree) = number.as_f64().filter(|d| (0.0..=1.0).contains(d)) {
+                            set.insert(key.clone(), degree);
+                        }
+                    }
+                    serde_json::Value::String(text) => set.insert(format!("{key}={text}"), 1.0),
+                    serde_json::Value::Null => {}
+                    other => set.insert(format!("{key}={other}"), 1.0),
+                }
+            }
+        }
+        set
+    }
+
+    /// Features of a point: each threshold present when its coordinate reaches it
+    pub fn from_thresholds(point: &ConceptualPoint, thresholds: &[FeatureThreshold]) -> ConceptualResult<Self> {
+        let mut set = Self::new();
+        for threshold in thresholds {
+            let value = point.coordinates.get(threshold.dimension).ok_or_else(|| {
+                ConceptualError::InvalidDimension(format!(
+                    "Feature {} reads dimension {} of a {}-dimensional point",
+                    threshold.feature,
+                    threshold.dimension,
+                    point.coordinates.len()
+                ))
+            })?;
+            if *value >= threshold.threshold {
+                set.insert(threshold.feature.clone(), 1.0);
+            }
+        }
+        Ok(set)
+    }
+
+    /// Membership degree of a feature, zero when absent
+    pub fn degree(&self, feature: &str) -> f64 {
+        self.features.get(feature).copied().unwrap_or(0.0)
+    }
+
+    /// Whether the feature is present at all
+    pub fn contains(&self, feature: &str) -> bool {
+        self.features.contains_key(feature)
+    }
+
+    /// Number of features present
+    pub fn len(&self) -> usize {
+        self.features.len()
+    }
+
+    /// Whether no feature is present
+    pub fn is_empty(&self) -> bool {
+        self.features.is_empty()
+    }
+
+    /// Features with their membership degrees
+    pub fn iter(&self) -> impl Iterator<Item = (&String, f64)> {
+        self.features.iter().map(|(feature, degree)| (feature, *degree))
+    }
+}
+
+/// A feature defined by a dimension reaching a threshold
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FeatureThreshold {
+    /// Name of the feature
+    pub feature: String,
+
+    /// Coordinate index of the dimension
+    pub dimension: usize,
+
+    /// Minimum coordinate value for the feature to be present
+    pub threshold: f64,
+}
+
+impl FeatureThreshold {
+    /// Create a threshold feature
+    pub fn new(feature: impl Into<String>, dimension: usize, threshold: f64) -> Self {
+        Self {
+            feature: feature.into(),
+            dimension,
+            threshold,
+        }
+    }
+}
+
+/// How common and distinctive features combine into a similarity
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TverskyForm {
+    /// `θ·f(A∩B) − α·f(A−B) − β·f(B−A)`, unbounded
+    Contrast,
+
+    /// `θ·f(A∩B) / (θ·f(A∩B) + α·f(A−B) + β·f(B−A))`, in `[0, 1]`
+    Ratio,
+}
+
+/// Feature measures of a subject against a referent
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct FeatureOverlap {
+    /// Salience of the shared features, `f(A∩B)`
+    pub common: f64,
+
+    /// Salience of features only the subject has, `f(A−B)`
+    pub subject_distinctive: f64,
+
+    /// Salience of features only the referent has, `f(B−A)`
+    pub referent_distinctive: f64,
+}
+
+/// Tversky's similarity model with its weighting parameters
+///
+/// Similarity is directional: `similarity(a, b)` judges how much subject
+/// `a` resembles referent `b`. With `α > β` the subject's own distinctive
+/// features count more, so a sparse variant resembles a feature-rich
+/// prototype more than the reverse.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TverskyModel {
+    /// Contrast or ratio form
+    pub form: TverskyForm,
+
+    /// Weight of the common features
+    pub theta: f64,
+
+    /// Weight of the subject's distinctive features
+    pub alpha: f64,
+
+    /// Weight of the referent's distinctive features
+    pub beta: f64,
+
+    /// Salience per feature; unlisted features have salience 1
+    pub salience: HashMap<String, f64>,
+}
+
+impl TverskyModel {
+    /// Create a contrast model; negative weights are treated as zero
+    pub fn contrast(theta: f64, alpha: f64, beta: f64) -> Self {
+        Self::new(TverskyForm::Contrast, theta, alpha, beta)
+    }
+
+    /// Create a ratio model; negative weights are treated as zero
+    pub fn ratio(theta: f64, alpha: f64, beta: f64) -> Self {
+        Self::new(TverskyForm::Ratio, theta, alpha, beta)
+    }
+
+    fn new(form: TverskyForm, theta: f64, alpha: f64, beta: f64) -> Self {
+        Self {
+            form,
+            theta: theta.max(0.0),
+            alpha: alpha.max(0.0),
+            beta: beta.max(0.0),
+            salience: HashMap::new(),
+        }
+    }
+
+    /// Set the salience of a feature
+    pub fn with_salience(mut self, feature: impl Into<String>, salience: f64) -> Self {
+        self.salience.insert(feature.into(), salience.max(0.0));
+        self
+    }
+
+    fn salience_of(&self, feature: &str) -> f64 {
+        self.salience.get(feature).copied().unwrap_or(1.0)
+    }
+
+    /// Salience-weighted common and distinctive feature measures
+    ///
+    /// Graded memberships combine as fuzzy sets: the intersection takes the
+    /// minimum degree and each difference the positive excess.
+    pub fn overlap(&self, subject: &FeatureSet, referent: &FeatureSet) -> FeatureOverlap {
+        let features: BTreeSet<&String> = subject.features.keys().chain(referent.features.keys()).collect();
+
+        let mut overlap = FeatureOverlap {
+            common: 0.0,
+            subject_distinctive: 0.0,
+            referent_distinctive: 0.0,
+        };
+        for feature in features {
+            let salience = self.salience_of(feature);
+            let (a, b) = (subject.degree(feature), referent.degree(feature));
+            overlap.common += salience * a.min(b);
+            overlap.subject_distinctive += salience * (a - b).max(0.0);
+            overlap.referent_distinctive += salience * (b - a).max(0.0);
+        }
+        overlap
+    }
+
+    /// How much `subject` resembles `referent`
+    ///
+    /// ```mermaid
+    /// graph LR
+    ///     A[Subject Features] --> O[Common / Distinctive Measures]
+    ///     B[Referent Features] --> O
+    ///     O --> C{Form}
+    ///     C -->|Contrast| D[θ·common − α·A−B − β·B−A]
+    ///     C -->|Ratio| E[θ·common / weighted total]
+    /// ```
+    pub fn similarity(&self, subject: &FeatureSet, referent: &FeatureSet) -> f64 {
+        let overlap = self.overlap(subject, referent);
+        let common = self.theta * overlap.common;
+        let distinctive = self.alpha * overlap.subject_distinctive + self.beta * overlap.referent_distinctive;
+
+        match self.form {
+            TverskyForm::Contrast => common - distinctive,
+            TverskyForm::Ratio => {
+                if common + distinctive > 0.0 {
+                    common / (common + distinctive)
+                } else {
+                    0.0
+                }
+            }
+        }
+    }
+
+    /// Directional similarity between two concept maps
+    pub fn concept_similarity(&self, subject: &ConceptMap, referent: &ConceptMap) -> f64 {
+        self.similarity(&FeatureSet::from_concept_map(subject), &FeatureSet::from_concept_map(referent))
+    }
+
+    /// `similarity(a, b) − similarity(b, a)`; zero whenever `α = β`
+    pub fn asymmetry(&self, a: &FeatureSet, b: &FeatureSet) -> f64 {
+        self.similarity(a, b) - self.similarity(b, a)
+    }
+}
+
+impl Default for TverskyModel {
+    /// Ratio model with Tversky's focus on the subject, `α = 0.8`, `β = 0.2`
+    fn default() -> Self {
+        Self::ratio(1.0, 0.8, 0.2)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ConceptNode, ContextId};
+
+    #[test]
+    fn test_variant_resembles_prototype_more() {
+        let prototype = FeatureSet::new()
+            .with_feature("wings")
+            .with_feature("feathers")
+            .with_feature("flies")
+            .with_feature("sings");
+        let variant = FeatureSet::new()
+            .with_feature("wings")
+            .with_feature("feathers")
+            .with_feature("swims");
+
+        let ratio = TverskyModel::default();
+        assert!(ratio.similarity(&variant, &prototype) > ratio.similarity(&prototype, &variant));
+        assert!(ratio.asymmetry(&variant, &prototype) > 0.0);
+
+        // Subject distinctive: swims; referent distinctive: flies, sings
+        let contrast = TverskyModel::contrast(1.0, 0.5, 0.25);
+        assert!((contrast.similarity(&variant, &prototype) - (2.0 - 0.5 - 0.5)).abs() < 1e-12);
+        assert!((contrast.similarity(&prototype, &variant) - (2.0 - 1.0 - 0.25)).abs() < 1e-12);
+
+        let symmetric = TverskyModel::ratio(1.0, 0.5, 0.5).with_salience("swims", 3.0);
+        assert_eq!(symmetric.asymmetry(&variant, &prototype), 0.0);
+        assert_eq!(symmetric.similarity(&variant, &prototype), 2.0 / (2.0 + 0.5 * 3.0 + 0.5 * 2.0));
+    }
+
+    #[test]
+    fn test_feature_extraction() {
+        let node = ConceptNode::new("Animal".to_string(), "Robin".to_string())
+            .with_property("flies".to_string(), serde_json::json!(true))
+            .with_property("aquatic".to_string(), serde_json::json!(false))
+            .with_property("color".to_string(), serde_json::json!("red"))
+            .with_property("size".to_string(), serde_json::json!(0.3))
+            .with_property("wingspan_cm".to_string(), serde_json::json!(34))
+            .with_property("offset".to_string(), serde_json::json!(-0.2));
+        let mut map = ConceptMap::new(ContextId::new(), ConceptualPoint::new(vec![0.0], HashMap::new()));
+        map.add_node(node);
+
+        let features = FeatureSet::from_concept_map(&map);
+        assert!(features.contains("Animal:Robin"));
+        assert!(features.contains("flies"));
+        assert!(!features.contains("aquatic"));
+        assert!(features.contains("color=red"));
+        assert_eq!(features.degree("size"), 0.3);
+        assert!(!features.contains("wingspan_cm"));
+        assert!(!features.contains("offset"));
+
+        let point = ConceptualPoint::new(vec![0.9, 0.1], HashMap::new());
+        let thresholds = [FeatureThreshold::new("bright", 0, 0.5), FeatureThreshold::new("heavy", 1, 0.5)];
+        let features = FeatureSet::from_thresholds(&point, &thresholds).unwrap();
+        assert!(features.contains("bright"));
+        assert!(!features.contains("heavy"));
+        assert!(FeatureSet::from_thresholds(&point, &[FeatureThreshold::new("x", 2, 0.0)]).is_err());
+    }
+}
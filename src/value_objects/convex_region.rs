@@ -1,6 +1,6 @@
 //! Convex region value object representing natural categories
 
-use crate::{ConceptualPoint, ConceptualError, ConceptualResult};
+use crate::{ConceptualPoint, ConceptualError, ConceptualResult, SparsePoint};
 use nalgebra::DVector;
 use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
@@ -28,12 +28,107 @@ impl Hyperplane {
         self.normal.dot(&point.coordinates) - self.offset
     }
 
+    /// Signed distance from a sparse point, touching only its non-zero coordinates
+    pub fn signed_distance_sparse(&self, point: &SparsePoint) -> f64 {
+        point.dot_dense(&self.normal) - self.offset
+    }
+
     /// Check if a point is on the positive side of the hyperplane
     pub fn contains_positive(&self, point: &ConceptualPoint) -> bool {
         self.signed_distance(point) >= 0.0
     }
 }
 
+/// Relative widening of hull faces so points interpolated between hull
+/// vertices are not excluded by rounding
+const HULL_SLACK: f64 = 1e-12;
+
+/// Edges of the counter-clockwise 2D hull, facing inwards
+///
+/// `None` when the points are collinear and the hull has no interior.
+fn planar_hull(points: &[&DVector<f64>]) -> Option<Vec<Hyperplane>> {
+    let mut sorted: Vec<(f64, f64)> = points.iter().map(|p| (p[0], p[1])).collect();
+    sorted.sort_by(|a, b| a.0.total_cmp(&b.0).then(a.1.total_cmp(&b.1)));
+    sorted.dedup();
+
+    fn chain<'a>(points: impl Iterator<Item = &'a (f64, f64)>) -> Vec<(f64, f64)> {
+        let cross = |o: (f64, f64), a: (f64, f64), b: (f64, f64)| {
+            (a.0 - o.0) * (b.1 - o.1) - (a.1 - o.1) * (b.0 - o.0)
+        };
+        let mut chain: Vec<(f64, f64)> = Vec::new();
+        for &p in points {
+            while chain.len() >= 2 && cross(chain[chain.len() - 2], chain[chain.len() - 1], p) <= 0.0 {
+                chain.pop();
+            }
+            chain.push(p);
+        }
+        // The last point of each chain starts the other one
+        chain.pop();
+        chain
+    }
+    let mut hull = chain(sorted.iter());
+    hull.extend(chain(sorted.iter().rev()));
+    if hull.len() < 3 {
+        return None;
+    }
+
+    Some((0..hull.len())
+        .map(|i| {
+            let (a, b) = (hull[i], hull[(i + 1) % hull.len()]);
+            // Interior lies to the left of a counter-clockwise edge
+            let normal = DVector::from_vec(vec![a.1 - b.1, b.0 - a.0]).normalize();
+            // Support value over all points, so rounding never excludes a vertex
+            let offset = points.iter()
+                .map(|p| normal.dot(p))
+                .fold(f64::INFINITY, f64::min);
+            Hyperplane::new(normal, offset - HULL_SLACK * (1.0 + offset.abs()))
+        })
+        .collect())
+}
+
+/// Dimensions up to which outer polytopes also face along pairwise diagonals
+///
+/// Diagonal faces number 2·d(d−1) dense planes, cubic in memory, so beyond
+/// this only the 2·d axis faces are kept and the polytope loosens to a box.
+pub(crate) const DIAGONAL_FACE_DIMENSIONS: usize = 16;
+
+/// Tightest half-spaces containing the points along axes and, up to
+/// `DIAGONAL_FACE_DIMENSIONS`, pairwise diagonals
+fn supporting_hyperplanes(points: &[&DVector<f64>], dimensions: usize) -> Vec<Hyperplane> {
+    if points.is_empty() {
+        return Vec::new();
+    }
+
+    let diagonals = dimensions <= DIAGONAL_FACE_DIMENSIONS;
+    let mut directions = Vec::new();
+    for i in 0..dimensions {
+        directions.push(DVector::from_fn(dimensions, |k, _| if k == i { 1.0 } else { 0.0 }));
+        for j in (i + 1..dimensions).filter(|_| diagonals) {
+            for sign in [1.0, -1.0] {
+                directions.push(DVector::from_fn(dimensions, |k, _| {
+                    if k == i { 1.0 } else if k == j { sign } else { 0.0 }
+                }).normalize());
+            }
+        }
+    }
+
+    directions.into_iter()
+        .flat_map(|direction| [direction.clone(), -direction])
+        .map(|direction| {
+            let support = points.iter()
+                .map(|p| direction.dot(p))
+                .fold(f64::NEG_INFINITY, f64::max);
+            Hyperplane::new(-direction, -support - HULL_SLACK * (1.0 + support.abs()))
+        })
+        .collect()
+}
+
+/// Maximum Dykstra sweeps when projecting onto a region
+const PROJECTION_SWEEPS: usize = 1000;
+
+/// Sweep-to-sweep movement below which a projection has converged
+const PROJECTION_TOLERANCE: f64 = 1e-12;
+
 /// A convex region in conceptual space representing a natural category
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ConvexRegion {
@@ -81,12 +176,113 @@ impl ConvexRegion {
         self
     }
 
+    /// Create a region bounded by the convex hull of a set of points
+    ///
+    /// The hull is exact in one and two dimensions (Andrew's monotone
+    /// chain). In higher dimensions it is approximated from outside by
+    /// supporting hyperplanes along every axis and pairwise diagonal, so all
+    /// points are still contained. Past `DIAGONAL_FACE_DIMENSIONS` the
+    /// diagonals are dropped and the region is the points' bounding box.
+    /// Points with ids become members.
+    pub fn from_hull(prototype: ConceptualPoint, points: &[ConceptualPoint]) -> Self {
+        let coordinates: Vec<&DVector<f64>> = points.iter().map(|p| &p.coordinates).collect();
+        let dimensions = prototype.coordinates.len();
+        let boundaries = if dimensions == 2 {
+            planar_hull(&coordinates).unwrap_or_else(|| supporting_hyperplanes(&coordinates, dimensions))
+        } else {
+            supporting_hyperplanes(&coordinates, dimensions)
+        };
+
+        Self {
+            boundaries,
+            member_points: points.iter().filter_map(|p| p.id).collect(),
+            ..Self::from_prototype(prototype)
+        }
+    }
+
+    /// Create a region bounded by the convex hull of some points, with their
+    /// centroid as prototype
+    pub fn hull_around_centroid(points: &[ConceptualPoint]) -> ConceptualResult<Self> {
+        let Some(first) = points.first() else {
+            return Err(ConceptualError::InvalidPoint(
+                "A hull needs at least one point".to_string()
+            ));
+        };
+        let centroid = points.iter()
+            .fold(DVector::zeros(first.coordinates.len()), |sum, p| sum + &p.coordinates)
+            / points.len() as f64;
+        let prototype = ConceptualPoint::new(centroid.as_slice().to_vec(), first.dimension_map.clone());
+        Ok(Self::from_hull(prototype, points))
+    }
+
     /// Check if a point is within this convex region
     /// A point is inside if it's on the positive side of all boundary hyperplanes
     pub fn contains(&self, point: &ConceptualPoint) -> bool {
         self.boundaries.iter().all(|plane| plane.contains_positive(point))
     }
 
+    /// Check if a sparse point is within this convex region
+    pub fn contains_sparse(&self, point: &SparsePoint) -> bool {
+        self.boundaries.iter().all(|plane| plane.signed_distance_sparse(point) >= 0.0)
+    }
+
+    /// Per-dimension bounds when every boundary is axis-aligned
+    ///
+    /// Returns `(lower, upper)` with infinite entries for unbounded sides,
+    /// or `None` if some boundary is oblique.
+    pub fn axis_aligned_bounds(&self, dimensions: usize) -> Option<(DVector<f64>, DVector<f64>)> {
+        let mut lower = DVector::from_element(dimensions, f64::NEG_INFINITY);
+        let mut upper = DVector::from_element(dimensions, f64::INFINITY);
+
+        for plane in &self.boundaries {
+            if plane.normal.len() != dimensions {
+                return None;
+            }
+            let mut axes = plane.normal.iter().enumerate().filter(|(_, n)| **n != 0.0);
+            let (axis, n) = axes.next()?;
+            if axes.next().is_some() {
+                return None;
+            }
+
+            let bound = plane.offset / n;
+            if *n > 0.0 {
+                lower[axis] = lower[axis].max(bound);
+            } else {
+                upper[axis] = upper[axis].min(bound);
+            }
+        }
+        Some((lower, upper))
+    }
+
+    /// Euclidean projection of coordinates onto the region
+    ///
+    /// Uses Dykstra's algorithm over the boundary half-spaces. Coordinates
+    /// already inside are returned unchanged.
+    pub fn project(&self, coordinates: &DVector<f64>) -> DVector<f64> {
+        let mut x = coordinates.clone();
+        let mut corrections = vec![DVector::zeros(x.len()); self.boundaries.len()];
+
+        for _ in 0..PROJECTION_SWEEPS {
+            let start = x.clone();
+            for (plane, correction) in self.boundaries.iter().zip(corrections.iter_mut()) {
+                let shifted = &x + &*correction;
+                let norm_squared = plane.normal.norm_squared();
+                let gap = plane.offset - plane.normal.dot(&shifted);
+                let projected = if gap > 0.0 && norm_squared > 0.0 {
+                    &shifted + &plane.normal * (gap / norm_squared)
+                } else {
+                    shifted.clone()
+                };
+                *correction = shifted - &projected;
+                x = projected;
+            }
+            if (&x - start).norm() <= PROJECTION_TOLERANCE {
+                break;
+            }
+        }
+        x
+    }
+
     /// Update the prototype based on member points
     pub fn update_prototype(&mut self, points: &[ConceptualPoint]) -> ConceptualResult<()> {
         if points.is_empty() {
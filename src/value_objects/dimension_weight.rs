@@ -67,6 +67,27 @@ impl DimensionWeight {
         }
     }
 
+    /// The same kind of weight with every value multiplied by `factor`
+    ///
+    /// Context modifiers and attention bounds scale along with the base, so
+    /// the weight keeps behaving the same way relative to its new value.
+    pub fn rescaled(&self, factor: f64) -> Self {
+        match self {
+            DimensionWeight::Constant(w) => DimensionWeight::Constant(w * factor),
+            DimensionWeight::Contextual { base_weight, context_modifiers } => DimensionWeight::Contextual {
+                base_weight: base_weight * factor,
+                context_modifiers: context_modifiers.iter()
+                    .map(|(context, w)| (context.clone(), w * factor))
+                    .collect(),
+            },
+            DimensionWeight::Attentional { current_weight, min_weight, max_weight } => DimensionWeight::Attentional {
+                current_weight: current_weight * factor,
+                min_weight: min_weight * factor,
+                max_weight: max_weight * factor,
+            },
+        }
+    }
+
     /// Update attentional weight
     pub fn update_attention(&mut self, new_weight: f64) {
         if let DimensionWeight::Attentional { current_weight, min_weight, max_weight } = self {
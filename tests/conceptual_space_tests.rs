@@ -2,10 +2,12 @@
 
 use cim_domain::{CommandEnvelope, CommandHandler};
 use cim_domain_conceptualspaces::{
-    similarity::*, CategoryBoundaryDetection, CategoryFormation, ConceptualMetric, ConceptualPoint,
-    ConceptualSpace, ConceptualSpaceCommandHandler, ConceptualSpaceId, ConvexRegion,
-    CreateConceptualSpace, DimensionId, DimensionWeight, DistanceMetric, Hyperplane, RTreeIndex,
-    ReplaceDimensionWeights, SimilarityEngine, SpatialIndex,
+    similarity::*, AddRegion, CategoryBoundaryDetection, CategoryFormation, ConceptualMetric,
+    ConceptualPoint, ConceptualSpace, ConceptualSpaceCommandHandler, ConceptualSpaceId, ConvexRegion,
+    CreateConceptualSpace, DimensionId, DimensionWeight, DistanceMetric, FindSimilarConcepts,
+    Hyperplane, IndexRestore, IndexSnapshot, PointStorage, RTreeIndex, RemoveRegion,
+    ReplaceDimensionWeights, SimilarityEngine, SpaceIndex, SparsePoint, SpatialIndex,
+    SpatialIndexConfig,
 };
 use std::collections::HashMap;
 use uuid::Uuid;
@@ -282,3 +284,351 @@ async fn test_f21_prototype_similarity() {
     assert!(similarity >= 0.0 && similarity <= 1.0);
     assert!(similarity < 1.0); // Should be less than 1 due to distance
 }
+
+/// Test F22: Index-backed FindSimilarConcepts
+///
+/// ```mermaid
+/// graph TD
+///     A[Create Space With KD-Tree] --> B[Add Points]
+///     B --> C[Run Query]
+///     C --> D[Verify Nearest First Within Radius]
+/// ```
+#[tokio::test]
+async fn test_f22_find_similar_concepts_uses_index() {
+    let mut space = ConceptualSpace::new(
+        "Query Test Space".to_string(),
+        vec![DimensionId::new(), DimensionId::new()],
+        ConceptualMetric::uniform(2, 2.0),
+    )
+    .with_spatial_index(SpatialIndexConfig::KdTree)
+    .unwrap();
+
+    let mut ids = Vec::new();
+    for coords in [vec![0.0, 0.0], vec![1.0, 0.0], vec![3.0, 0.0], vec![10.0, 0.0]] {
+        ids.push(space.add_point(ConceptualPoint::new(coords, HashMap::new())).unwrap());
+    }
+
+    let query = FindSimilarConcepts {
+        space_id: space.id,
+        reference: ConceptualPoint::new(vec![0.2, 0.0], HashMap::new()),
+        limit: 5,
+        max_distance: Some(3.0),
+    };
+
+    let result = query.execute(&space).unwrap();
+    let found: Vec<_> = result.points.iter().map(|(id, _, _)| *id).collect();
+    assert_eq!(found, vec![ids[0], ids[1], ids[2]]);
+}
+
+/// Test F23: Batch neighbour queries and distance matrix
+///
+/// ```mermaid
+/// graph TD
+///     A[Create Space] --> B[Batch KNN and Range]
+///     B --> C[Compare With Per-Query Results]
+///     C --> D[Check Distance Matrix]
+/// ```
+#[tokio::test]
+async fn test_f23_batch_queries_match_sequential() {
+    let mut space = ConceptualSpace::new(
+        "Batch Test Space".to_string(),
+        vec![DimensionId::new(), DimensionId::new()],
+        ConceptualMetric::uniform(2, 2.0),
+    )
+    .with_spatial_index(SpatialIndexConfig::KdTree)
+    .unwrap();
+
+    for i in 0..50 {
+        let x = (i % 7) as f64;
+        let y = (i / 7) as f64 * 0.5;
+        space.add_point(ConceptualPoint::new(vec![x, y], HashMap::new())).unwrap();
+    }
+
+    let queries: Vec<_> = (0..20)
+        .map(|i| ConceptualPoint::new(vec![i as f64 * 0.3, 1.1], HashMap::new()))
+        .collect();
+
+    let batch = space.k_nearest_neighbors_batch(&queries, 4).unwrap();
+    let ranges = space.range_search_batch(&queries, 1.5).unwrap();
+    for (i, query) in queries.iter().enumerate() {
+        let single: Vec<_> = space
+            .k_nearest_neighbors(query, 4)
+            .unwrap()
+            .into_iter()
+            .map(|(id, d)| (*id, d))
+            .collect();
+        assert_eq!(batch[i], single);
+
+        let mut expected: Vec<_> = space
+            .range_search(query, 1.5)
+            .unwrap()
+            .into_iter()
+            .map(|(id, _)| *id)
+            .collect();
+        let mut found = ranges[i].clone();
+        expected.sort();
+        found.sort();
+        assert_eq!(found, expected);
+    }
+
+    let matrix = space.distance_matrix().unwrap();
+    assert_eq!(matrix.ids.len(), 50);
+    let (a, b) = (matrix.ids[3], matrix.ids[17]);
+    let expected = space
+        .metric
+        .distance(&space.points.get(&a).unwrap(), &space.points.get(&b).unwrap())
+        .unwrap();
+    assert_eq!(matrix.get(&a, &b), Some(expected));
+    assert_eq!(matrix.get(&b, &a), Some(expected));
+    assert_eq!(matrix.get(&a, &a), Some(0.0));
+}
+
+/// Test F24: Index snapshots restore or rebuild
+///
+/// ```mermaid
+/// graph TD
+///     A[Create Space] --> B[Snapshot Index to Bytes]
+///     B --> C[Restore Into Unchanged Space]
+///     B --> D[Restore After Mutation]
+///     D --> E[Stale Index Rebuilt]
+/// ```
+#[tokio::test]
+async fn test_f24_index_snapshot_roundtrip() {
+    let mut space = ConceptualSpace::new(
+        "Snapshot Test Space".to_string(),
+        vec![DimensionId::new(), DimensionId::new()],
+        ConceptualMetric::uniform(2, 2.0),
+    )
+    .with_spatial_index(SpatialIndexConfig::KdTree)
+    .unwrap();
+
+    for i in 0..30 {
+        let x = (i % 6) as f64;
+        let y = (i / 6) as f64;
+        space.add_point(ConceptualPoint::new(vec![x, y], HashMap::new())).unwrap();
+    }
+
+    let bytes = space.index_snapshot().to_bytes().unwrap();
+    let query = ConceptualPoint::new(vec![2.2, 1.9], HashMap::new());
+    let before: Vec<_> = space
+        .k_nearest_neighbors(&query, 3)
+        .unwrap()
+        .into_iter()
+        .map(|(id, d)| (*id, d))
+        .collect();
+
+    // Unchanged content: the snapshot is installed as is
+    let snapshot = IndexSnapshot::from_bytes(&bytes).unwrap();
+    assert_eq!(space.restore_index(snapshot).unwrap(), IndexRestore::Restored);
+    assert_eq!(space.spatial_index().config(), SpatialIndexConfig::KdTree);
+    let after: Vec<_> = space
+        .k_nearest_neighbors(&query, 3)
+        .unwrap()
+        .into_iter()
+        .map(|(id, d)| (*id, d))
+        .collect();
+    assert_eq!(before, after);
+
+    // Changed content: the stale snapshot is detected and rebuilt
+    let added = space
+        .add_point(ConceptualPoint::new(vec![2.2, 1.9], HashMap::new()))
+        .unwrap();
+    let snapshot = IndexSnapshot::from_bytes(&bytes).unwrap();
+    assert_eq!(space.restore_index(snapshot).unwrap(), IndexRestore::Rebuilt);
+    assert_eq!(space.spatial_index().size(), 31);
+    assert_eq!(*space.k_nearest_neighbors(&query, 1).unwrap()[0].0, added);
+
+    // Foreign formats are rejected before decoding
+    let mut corrupted = bytes.clone();
+    corrupted[0] = 0xff;
+    assert!(IndexSnapshot::<SpaceIndex>::from_bytes(&corrupted).is_err());
+}
+
+/// Test F25: Sparse point storage in a high-dimensional space
+///
+/// ```mermaid
+/// graph TD
+///     A[Create 2000-D Sparse Space] --> B[Add Sparse Points]
+///     B --> C[Query Nearest Neighbours]
+///     C --> D[Compare With Dense Space]
+/// ```
+#[tokio::test]
+async fn test_f25_sparse_space_matches_dense() {
+    let dims = 2000;
+    let dimension_ids: Vec<_> = (0..dims).map(|_| DimensionId::new()).collect();
+    let mut sparse = ConceptualSpace::new(
+        "Sparse Features".to_string(),
+        dimension_ids.clone(),
+        ConceptualMetric::uniform(dims, 2.0),
+    )
+    .with_point_storage(PointStorage::Sparse)
+    .unwrap();
+    let mut dense = ConceptualSpace::new(
+        "Dense Features".to_string(),
+        dimension_ids,
+        ConceptualMetric::uniform(dims, 2.0),
+    );
+
+    let empty_snapshot = sparse.index_snapshot().to_bytes().unwrap().len();
+
+    for i in 0..40usize {
+        let point = SparsePoint::new(
+            dims,
+            [(i * 37 % dims, 1.0), (i * 91 % dims, 0.5 + i as f64 * 0.01), (7, i as f64 * 0.1)],
+        )
+        .unwrap();
+        dense.add_point(point.to_dense(HashMap::new())).unwrap();
+        sparse.add_sparse_point(point).unwrap();
+    }
+    assert_eq!(sparse.points.storage(), PointStorage::Sparse);
+    // The flat index scans the sparse rows rather than holding dense copies
+    assert_eq!(sparse.index_snapshot().to_bytes().unwrap().len(), empty_snapshot);
+    assert!(sparse.clone().with_spatial_index(SpatialIndexConfig::KdTree).is_err());
+
+    let query = SparsePoint::new(dims, [(37, 1.0), (91, 0.5), (7, 0.1)])
+        .unwrap()
+        .to_dense(HashMap::new());
+    let from_sparse = sparse.k_nearest_neighbors(&query, 5).unwrap();
+    let from_dense = dense.k_nearest_neighbors(&query, 5).unwrap();
+
+    assert_eq!(from_sparse.len(), 5);
+    for ((sparse_id, sparse_d), (dense_id, dense_d)) in from_sparse.iter().zip(&from_dense) {
+        assert_eq!(sparse_id, dense_id);
+        assert!((sparse_d - dense_d).abs() < 1e-9);
+    }
+}
+
+/// Test F26: The default index scans the space's own point store
+///
+/// ```mermaid
+/// graph TD
+///     A[Add Points] --> B[Snapshot Flat Index]
+///     B --> C[Check No Coordinates Held]
+///     C --> D[Query After Move and Remove]
+/// ```
+#[tokio::test]
+async fn test_f26_flat_index_shares_point_store() {
+    let mut space = ConceptualSpace::new(
+        "Shared Store".to_string(),
+        vec![DimensionId::new(), DimensionId::new()],
+        ConceptualMetric::uniform(2, 2.0),
+    );
+    let ids: Vec<Uuid> = (0..500)
+        .map(|i| {
+            let point = ConceptualPoint::new(vec![i as f64, (i % 7) as f64], HashMap::new());
+            space.add_point(point).unwrap()
+        })
+        .collect();
+
+    // The snapshot carries the metric and a count, not 500 points
+    assert_eq!(space.spatial_index().size(), 500);
+    let bytes = space.index_snapshot().to_bytes().unwrap();
+    let snapshot = IndexSnapshot::<SpaceIndex>::from_bytes(&bytes).unwrap();
+    assert!(snapshot.is_current(space.content_hash()));
+    assert!(matches!(snapshot.index, SpaceIndex::Flat(_)));
+    assert_eq!(snapshot.index.size(), 500);
+    // No stored coordinate, such as the last point's 499.0, was written out
+    assert!(!serde_json::to_string(&snapshot.index).unwrap().contains("499"));
+
+    let query = ConceptualPoint::new(vec![1000.0, 0.0], HashMap::new());
+    space.move_point(&ids[3], vec![999.0, 0.0]).unwrap();
+    assert_eq!(*space.k_nearest_neighbors(&query, 1).unwrap()[0].0, ids[3]);
+
+    space.remove_point(&ids[3]).unwrap();
+    assert_eq!(space.spatial_index().size(), 499);
+    assert_eq!(*space.k_nearest_neighbors(&query, 1).unwrap()[0].0, ids[499]);
+    assert_eq!(space.points.get(&ids[499]).unwrap().coordinates.as_slice(), &[499.0, 2.0]);
+}
+
+/// Test F27: Region removal through the command handler
+///
+/// ```mermaid
+/// graph TD
+///     A[Create Space] --> B[Add Region]
+///     B --> C[Remove Region]
+///     C --> D[Accepted]
+///     C --> E[Remove Again]
+///     E --> F[Rejected]
+/// ```
+#[tokio::test]
+async fn test_f27_remove_region_command() {
+    let mut handler = ConceptualSpaceCommandHandler::new();
+    let space_id = ConceptualSpaceId(Uuid::new_v4());
+
+    let create = CreateConceptualSpace {
+        space_id,
+        name: "Removal Space".to_string(),
+        dimension_ids: vec![DimensionId::new(), DimensionId::new()],
+        metric: ConceptualMetric::uniform(2, 2.0),
+    };
+    let ack = handler.handle(CommandEnvelope::new(create, "test_user".to_string()));
+    assert_eq!(ack.status, cim_domain::CommandStatus::Accepted);
+
+    let region = ConvexRegion::from_prototype(ConceptualPoint::new(vec![0.0, 0.0], HashMap::new()));
+    let region_id = region.id;
+    let ack = handler.handle(CommandEnvelope::new(AddRegion { space_id, region }, "test_user".to_string()));
+    assert_eq!(ack.status, cim_domain::CommandStatus::Accepted);
+
+    let remove = RemoveRegion { space_id, region_id };
+    let ack = handler.handle(CommandEnvelope::new(remove.clone(), "test_user".to_string()));
+    assert_eq!(ack.status, cim_domain::CommandStatus::Accepted);
+    assert!(ack.reason.is_none());
+
+    // The region is gone, so a second removal names it as unknown
+    let ack = handler.handle(CommandEnvelope::new(remove, "test_user".to_string()));
+    assert_eq!(ack.status, cim_domain::CommandStatus::Rejected);
+    assert!(ack.reason.unwrap().contains(&region_id.to_string()));
+
+    let unknown = RemoveRegion { space_id, region_id: Uuid::new_v4() };
+    let ack = handler.handle(CommandEnvelope::new(unknown, "test_user".to_string()));
+    assert_eq!(ack.status, cim_domain::CommandStatus::Rejected);
+}
+
+/// Test F28: Spaces stored before the point store still load
+///
+/// ```mermaid
+/// graph TD
+///     A[Baseline JSON, Points Keyed by Id] --> B[Deserialize]
+///     B --> C[Index Rebuilt]
+///     C --> D[Round Trip Current Layout]
+/// ```
+#[tokio::test]
+async fn test_f28_baseline_space_json_loads() {
+    let dimension_ids = vec![DimensionId::new(), DimensionId::new()];
+    let metric = ConceptualMetric::uniform(2, 2.0);
+    let legacy_points: HashMap<Uuid, ConceptualPoint> = [[0.0, 0.0], [3.0, 4.0], [10.0, 0.0]]
+        .iter()
+        .map(|c| {
+            let point = ConceptualPoint::new(c.to_vec(), HashMap::new());
+            (point.id.unwrap(), point)
+        })
+        .collect();
+
+    // The layout a space had before the point store and its index existed
+    let baseline = serde_json::json!({
+        "id": ConceptualSpaceId::new(),
+        "name": "Stored Before",
+        "dimension_ids": dimension_ids,
+        "metric": metric,
+        "regions": {},
+        "points": legacy_points,
+    });
+    let space: ConceptualSpace = serde_json::from_value(baseline).unwrap();
+
+    assert_eq!(space.points.len(), 3);
+    assert_eq!(space.spatial_index().size(), 3);
+    assert_eq!(space.spatial_index().config(), SpatialIndexConfig::Flat);
+    for (id, point) in &legacy_points {
+        assert_eq!(space.points.get(id).unwrap().coordinates, point.coordinates);
+    }
+    let query = ConceptualPoint::new(vec![9.0, 1.0], HashMap::new());
+    let nearest = *space.k_nearest_neighbors(&query, 1).unwrap()[0].0;
+    assert_eq!(legacy_points[&nearest].coordinates.as_slice(), &[10.0, 0.0]);
+
+    // The current layout round-trips, index included
+    let json = serde_json::to_string(&space).unwrap();
+    let restored: ConceptualSpace = serde_json::from_str(&json).unwrap();
+    assert_eq!(restored.points.ids(), space.points.ids());
+    assert_eq!(restored.spatial_index().size(), 3);
+    assert_eq!(*restored.k_nearest_neighbors(&query, 1).unwrap()[0].0, nearest);
+}